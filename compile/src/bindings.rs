@@ -0,0 +1,522 @@
+//! Generates the TypeScript bindings for lo-web: interfaces, DataView readers and writers
+//! for the FFI structs (from `dim_lo_core::abi`), and typed wrappers for the `lo_*` exports
+//! (parsed from the lo-web sources).
+
+use std::fmt::Write;
+
+use dim_lo_core::abi::{
+    layout_table, layout_table_len, FfiKind, FfiLayout, ABI_VERSION, FFI_ENUMS, FFI_LAYOUTS,
+};
+
+const LO_WEB_LIB: &str = include_str!("../../lo-web/src/lib.rs");
+const LO_WEB_VALIDATE: &str = include_str!("../../lo-web/src/validate.rs");
+
+const PREAMBLE: &str = r#"// Generated by `cargo compile` from the lo-core and lo-web sources. Do not edit.
+
+/** An address in the memory of the WASM module. */
+export type Ptr = number;
+
+function times<T>(n: number, f: (i: number) => T): T[] {
+  const result: T[] = [];
+  for (let i = 0; i < n; i++) {
+    result.push(f(i));
+  }
+  return result;
+}
+
+/** Reads `len` consecutive structs of `size` bytes each, starting at `ptr`. */
+export function readArray<T>(
+  view: DataView,
+  ptr: Ptr,
+  len: number,
+  size: number,
+  read: (view: DataView, ptr: Ptr) => T,
+): T[] {
+  return times(len, (i) => read(view, ptr + i * size));
+}
+
+/** Writes `values` as consecutive structs of `size` bytes each, starting at `ptr`. */
+export function writeArray<T>(
+  view: DataView,
+  ptr: Ptr,
+  values: readonly T[],
+  size: number,
+  write: (view: DataView, ptr: Ptr, value: T) => void,
+): void {
+  values.forEach((value, i) => write(view, ptr + i * size, value));
+}
+"#;
+
+fn camel_case(snake: &str) -> String {
+    let mut result = String::new();
+    let mut upper = false;
+    for c in snake.chars() {
+        if c == '_' {
+            upper = !result.is_empty();
+        } else if upper {
+            result.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+pub(crate) fn screaming_case(pascal: &str) -> String {
+    let mut result = String::new();
+    for (i, c) in pascal.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            result.push('_');
+        }
+        result.push(c.to_ascii_uppercase());
+    }
+    result
+}
+
+pub(crate) fn layout(name: &str) -> &'static FfiLayout {
+    FFI_LAYOUTS
+        .iter()
+        .find(|l| l.name == name)
+        .unwrap_or_else(|| panic!("no layout for {name}"))
+}
+
+fn kind_size(kind: FfiKind) -> usize {
+    match kind {
+        FfiKind::U8 | FfiKind::Bool | FfiKind::Enum(_) => 1,
+        FfiKind::U16 | FfiKind::I16 => 2,
+        FfiKind::U32 => 4,
+        FfiKind::Struct(name) => layout(name).size,
+    }
+}
+
+fn ts_type(kind: FfiKind) -> &'static str {
+    match kind {
+        FfiKind::U8 | FfiKind::U16 | FfiKind::U32 | FfiKind::I16 => "number",
+        FfiKind::Bool => "boolean",
+        FfiKind::Enum(name) | FfiKind::Struct(name) => name,
+    }
+}
+
+/// An expression reading a value of `kind` at `ptr`.
+fn read_expr(kind: FfiKind, ptr: &str) -> String {
+    match kind {
+        FfiKind::U8 => format!("view.getUint8({ptr})"),
+        FfiKind::U16 => format!("view.getUint16({ptr}, true)"),
+        FfiKind::U32 => format!("view.getUint32({ptr}, true)"),
+        FfiKind::I16 => format!("view.getInt16({ptr}, true)"),
+        FfiKind::Bool => format!("view.getUint8({ptr}) !== 0"),
+        FfiKind::Enum(name) => format!("view.getUint8({ptr}) as {name}"),
+        FfiKind::Struct(name) => format!("read{name}(view, {ptr})"),
+    }
+}
+
+/// A statement writing `value` of `kind` at `ptr`.
+fn write_stmt(kind: FfiKind, ptr: &str, value: &str) -> String {
+    match kind {
+        FfiKind::U8 | FfiKind::Enum(_) => format!("view.setUint8({ptr}, {value});"),
+        FfiKind::U16 => format!("view.setUint16({ptr}, {value}, true);"),
+        FfiKind::U32 => format!("view.setUint32({ptr}, {value}, true);"),
+        FfiKind::I16 => format!("view.setInt16({ptr}, {value}, true);"),
+        FfiKind::Bool => format!("view.setUint8({ptr}, {value} ? 1 : 0);"),
+        FfiKind::Struct(name) => format!("write{name}(view, {ptr}, {value});"),
+    }
+}
+
+fn generate_enums(out: &mut String) {
+    for e in FFI_ENUMS {
+        writeln!(out, "\nexport enum {} {{", e.name).unwrap();
+        for (variant, value) in e.variants {
+            writeln!(out, "  {variant} = {value},").unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+    }
+}
+
+fn generate_structs(out: &mut String) {
+    for l in FFI_LAYOUTS {
+        let name = l.name;
+        writeln!(out, "\nexport interface {name} {{").unwrap();
+        for f in l.fields {
+            let ty = ts_type(f.kind);
+            let array = if f.len > 1 { "[]" } else { "" };
+            writeln!(out, "  {}: {ty}{array};", camel_case(f.name)).unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+
+        writeln!(
+            out,
+            "\nexport const {}_SIZE = {};",
+            screaming_case(name),
+            l.size
+        )
+        .unwrap();
+
+        writeln!(
+            out,
+            "\nexport function read{name}(view: DataView, ptr: Ptr): {name} {{\n  return {{"
+        )
+        .unwrap();
+        for f in l.fields {
+            let field = camel_case(f.name);
+            let offset = f.offset;
+            if f.len > 1 {
+                let size = kind_size(f.kind);
+                let read = read_expr(f.kind, &format!("ptr + {offset} + {size} * i"));
+                writeln!(out, "    {field}: times({}, (i) => {read}),", f.len).unwrap();
+            } else {
+                let read = read_expr(f.kind, &format!("ptr + {offset}"));
+                writeln!(out, "    {field}: {read},").unwrap();
+            }
+        }
+        writeln!(out, "  }};\n}}").unwrap();
+
+        writeln!(
+            out,
+            "\nexport function write{name}(view: DataView, ptr: Ptr, value: {name}): void {{"
+        )
+        .unwrap();
+        for f in l.fields {
+            let field = camel_case(f.name);
+            let offset = f.offset;
+            if f.len > 1 {
+                let size = kind_size(f.kind);
+                let write = write_stmt(
+                    f.kind,
+                    &format!("ptr + {offset} + {size} * i"),
+                    &format!("value.{field}[i]"),
+                );
+                writeln!(out, "  for (let i = 0; i < {}; i++) {{", f.len).unwrap();
+                writeln!(out, "    {write}").unwrap();
+                writeln!(out, "  }}").unwrap();
+            } else {
+                let write = write_stmt(
+                    f.kind,
+                    &format!("ptr + {offset}"),
+                    &format!("value.{field}"),
+                );
+                writeln!(out, "  {write}").unwrap();
+            }
+        }
+        writeln!(out, "}}").unwrap();
+    }
+}
+
+/// Collects the `///` lines right above `line_idx`.
+pub(crate) fn doc_comment(lines: &[&str], line_idx: usize) -> Vec<String> {
+    let mut docs = lines[..line_idx]
+        .iter()
+        .rev()
+        .map(|l| l.trim())
+        .take_while(|l| l.starts_with("///") || l.starts_with("#["))
+        .filter_map(|l| l.strip_prefix("///"))
+        .map(|l| l.trim().to_owned())
+        .collect::<Vec<_>>();
+    docs.reverse();
+    docs
+}
+
+pub(crate) fn write_doc(out: &mut String, indent: &str, docs: &[String]) {
+    if docs.is_empty() {
+        return;
+    }
+    writeln!(out, "{indent}/**").unwrap();
+    for doc in docs {
+        writeln!(out, "{indent} * {doc}").unwrap();
+    }
+    writeln!(out, "{indent} */").unwrap();
+}
+
+/// The `ValidationCode` enum of lo-web.
+fn generate_validation_codes(out: &mut String) {
+    let lines = LO_WEB_VALIDATE.lines().collect::<Vec<_>>();
+    let start = lines
+        .iter()
+        .position(|l| l.starts_with("pub enum ValidationCode"))
+        .expect("ValidationCode not found");
+
+    out.push('\n');
+    write_doc(out, "", &doc_comment(&lines, start));
+    writeln!(out, "export enum ValidationCode {{\n  Ok = 0,").unwrap();
+    for (idx, line) in lines.iter().enumerate().skip(start + 1) {
+        let line = line.trim();
+        if line == "}" {
+            break;
+        }
+        if line.is_empty() || line.starts_with("///") || line.starts_with("#[") {
+            continue;
+        }
+        match line.trim_end_matches(',').split_once(" = ") {
+            Some((variant, value)) if is_identifier(variant) && value.parse::<u32>().is_ok() => {
+                write_doc(out, "  ", &doc_comment(&lines, idx));
+                writeln!(out, "  {variant} = {value},").unwrap();
+            }
+            _ => panic!(
+                "line {}: expected a ValidationCode variant with its value, got {line}",
+                idx + 1
+            ),
+        }
+    }
+    writeln!(
+        out,
+        r#"}}
+
+export interface ValidationResult {{
+  code: ValidationCode;
+  /** The offending bucket, item, mod or stat, depending on the code. */
+  index: number;
+}}
+
+export function readValidationResult(view: DataView, ptr: Ptr): ValidationResult {{
+  return {{
+    code: view.getUint32(ptr, true) as ValidationCode,
+    index: view.getUint32(ptr + 4, true),
+  }};
+}}"#
+    )
+    .unwrap();
+}
+
+pub(crate) struct Export {
+    pub name: String,
+    /// Names and Rust types.
+    pub params: Vec<(String, String)>,
+    pub ret: Option<String>,
+    pub docs: Vec<String>,
+}
+
+/// Finds every function exported with `#[no_mangle]` in `source`. Panics if an export
+/// can't be parsed, rather than leaving it out of the bindings.
+pub(crate) fn parse_exports(source: &str) -> Vec<Export> {
+    let lines = source.lines().collect::<Vec<_>>();
+    let mut exports = vec![];
+    for (idx, line) in lines.iter().enumerate() {
+        let line = line.trim();
+        if !line.starts_with("#[") || !(line.contains("no_mangle") || line.contains("export_name"))
+        {
+            continue;
+        }
+        if line != "#[no_mangle]" && line != "#[unsafe(no_mangle)]" {
+            panic!("line {}: unsupported export attribute {line}", idx + 1);
+        }
+        exports.push(parse_export(&lines, idx));
+    }
+    exports
+}
+
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
+/// Splits at the commas that aren't nested in brackets, e.g. of an array type.
+fn split_params(params: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let (mut depth, mut start) = (0, 0);
+    for (idx, c) in params.char_indices() {
+        match c {
+            '(' | '[' | '<' => depth += 1,
+            ')' | ']' | '>' if !params[..idx].ends_with('-') => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&params[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&params[start..]);
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// Parses the function after the `#[no_mangle]` in line `attr_idx`, which may span lines.
+fn parse_export(lines: &[&str], attr_idx: usize) -> Export {
+    let fail = |what: String| -> ! { panic!("export at line {}: {what}", attr_idx + 1) };
+
+    let mut signature = String::new();
+    for line in lines[attr_idx + 1..]
+        .iter()
+        .map(|l| l.trim())
+        .skip_while(|l| l.starts_with("#["))
+    {
+        signature.push_str(line);
+        signature.push(' ');
+        if line.contains('{') || line.ends_with(';') {
+            break;
+        }
+    }
+    let Some((mut rest, _)) = signature.split_once('{') else {
+        fail(format!("no function body in {signature}"))
+    };
+    let signature = rest.trim_end();
+    for qualifier in ["pub(crate) ", "pub ", "unsafe ", "extern \"C\" "] {
+        rest = rest.strip_prefix(qualifier).unwrap_or(rest);
+    }
+    let Some((name, rest)) = rest.strip_prefix("fn ").and_then(|s| s.split_once('(')) else {
+        fail(format!("unexpected signature {signature}"))
+    };
+    if !is_identifier(name) {
+        fail(format!("unexpected function name {name}"));
+    }
+    let Some((params, ret)) = rest.rsplit_once(')') else {
+        fail(format!("unexpected signature {signature}"))
+    };
+
+    let params = split_params(params)
+        .into_iter()
+        .map(|p| match p.split_once(':') {
+            Some((name, ty)) if is_identifier(name.trim()) => {
+                (name.trim().to_owned(), ty.trim().to_owned())
+            }
+            _ => fail(format!("can't parse parameter {p} of {name}")),
+        })
+        .collect();
+    let ret = match ret.trim() {
+        "" => None,
+        ret => match ret.strip_prefix("->") {
+            Some(ty) => Some(ty.trim().to_owned()),
+            None => fail(format!("can't parse return type {ret} of {name}")),
+        },
+    };
+    Export {
+        name: name.to_owned(),
+        params,
+        ret,
+        docs: doc_comment(lines, attr_idx),
+    }
+}
+
+fn generate_exports(out: &mut String) {
+    let exports = parse_exports(LO_WEB_LIB);
+
+    writeln!(
+        out,
+        "\n/** The raw exports of lo_web.wasm. */\nexport interface LoWebExports {{\n  memory: WebAssembly.Memory;"
+    )
+    .unwrap();
+    for e in &exports {
+        let params = e
+            .params
+            .iter()
+            .map(|(p, _)| format!("{}: number", camel_case(p)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let ret = if e.ret.is_some() { "number" } else { "void" };
+        writeln!(out, "  {}({params}): {ret};", e.name).unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+
+    let table = layout_table::<{ layout_table_len() }>();
+    writeln!(
+        out,
+        r#"
+export const ABI_VERSION = {ABI_VERSION};
+
+/** The layout table these bindings were generated from, see `lo_abi_layout_ptr`. */
+export const ABI_LAYOUT: readonly number[] = {table:?};
+
+/** Typed wrappers for the exports of lo_web.wasm. */
+export class LoWeb {{
+  readonly exports: LoWebExports;
+
+  constructor(instance: WebAssembly.Instance) {{
+    this.exports = instance.exports as unknown as LoWebExports;
+  }}
+
+  /**
+   * A view of the module's memory. Get a new one after calls that may allocate,
+   * since growing the memory detaches the old buffer.
+   */
+  view(): DataView {{
+    return new DataView(this.exports.memory.buffer);
+  }}
+
+  /** Throws if the module was built with different struct layouts than these bindings. */
+  checkAbi(): void {{
+    const version = this.abiVersion();
+    if (version !== ABI_VERSION) {{
+      throw new Error(`lo_web ABI version ${{version}}, expected ${{ABI_VERSION}}`);
+    }}
+    const ptr = this.abiLayoutPtr();
+    const view = this.view();
+    const layout = times(this.abiLayoutLen(), (i) => view.getUint32(ptr + 4 * i, true));
+    if (layout.length !== ABI_LAYOUT.length || layout.some((x, i) => x !== ABI_LAYOUT[i])) {{
+      throw new Error('lo_web struct layouts differ from the generated bindings');
+    }}
+  }}"#
+    )
+    .unwrap();
+
+    for e in &exports {
+        let method = camel_case(e.name.strip_prefix("lo_").unwrap_or(&e.name));
+        let params = e
+            .params
+            .iter()
+            .map(|(p, _)| camel_case(p))
+            .collect::<Vec<_>>();
+        let typed_params = params
+            .iter()
+            .map(|p| {
+                let ty = if p == "ctx" || p == "res" {
+                    "Ptr"
+                } else {
+                    "number"
+                };
+                format!("{p}: {ty}")
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        out.push('\n');
+        write_doc(out, "  ", &e.docs);
+        let call = format!("this.exports.{}({})", e.name, params.join(", "));
+        match &e.ret {
+            None => writeln!(
+                out,
+                "  {method}({typed_params}): void {{\n    {call};\n  }}"
+            ),
+            Some(ret) => {
+                // WASM returns u32 and pointers as i32.
+                let ty = if ret.starts_with('*') {
+                    "Ptr"
+                } else {
+                    "number"
+                };
+                writeln!(
+                    out,
+                    "  {method}({typed_params}): {ty} {{\n    return {call} >>> 0;\n  }}"
+                )
+            }
+        }
+        .unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+
+    writeln!(
+        out,
+        r#"
+/** Instantiates lo_web.wasm and checks that it matches these bindings. */
+export async function loadLoWeb(source: Response | PromiseLike<Response> | BufferSource): Promise<LoWeb> {{
+  const {{ instance }} =
+    source instanceof ArrayBuffer || ArrayBuffer.isView(source)
+      ? await WebAssembly.instantiate(source)
+      : await WebAssembly.instantiateStreaming(source);
+  const lo = new LoWeb(instance);
+  lo.checkAbi();
+  return lo;
+}}"#
+    )
+    .unwrap();
+}
+
+/// The TypeScript module for lo_web.wasm.
+pub fn generate() -> String {
+    let mut out = PREAMBLE.to_owned();
+    generate_enums(&mut out);
+    generate_structs(&mut out);
+    generate_validation_codes(&mut out);
+    generate_exports(&mut out);
+    out
+}
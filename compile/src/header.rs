@@ -0,0 +1,192 @@
+//! Generates `dim_lo.h`, the C header for lo-ffi: the FFI structs and enums (from
+//! `dim_lo_core::abi`) with static assertions of their layouts, and declarations of the
+//! `dim_lo_*` exports (parsed from the lo-ffi sources).
+
+use std::fmt::Write;
+
+use dim_lo_core::abi::{FfiKind, FfiLayout, ABI_VERSION, FFI_ENUMS, FFI_LAYOUTS};
+
+use crate::bindings::{layout, parse_exports, screaming_case, write_doc};
+
+const LO_FFI_LIB: &str = include_str!("../../lo-ffi/src/lib.rs");
+
+const PREAMBLE: &str = r#"/* Generated by `cargo compile` from the lo-core and lo-ffi sources. Do not edit. */
+
+#ifndef DIM_LO_H
+#define DIM_LO_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+#define DIM_LO_ASSERT(cond, msg) static_assert(cond, msg)
+#define DIM_LO_ALIGNOF(type) alignof(type)
+extern "C" {
+#else
+#define DIM_LO_ASSERT(cond, msg) _Static_assert(cond, msg)
+#define DIM_LO_ALIGNOF(type) _Alignof(type)
+#endif
+"#;
+
+const POSTAMBLE: &str = r#"
+#ifdef __cplusplus
+}
+#endif
+
+#endif
+"#;
+
+fn field_type(kind: FfiKind) -> &'static str {
+    match kind {
+        FfiKind::U8 => "uint8_t",
+        FfiKind::U16 => "uint16_t",
+        FfiKind::U32 => "uint32_t",
+        FfiKind::I16 => "int16_t",
+        FfiKind::Bool => "bool",
+        FfiKind::Enum(name) | FfiKind::Struct(name) => name,
+    }
+}
+
+/// The C type of a Rust type in an export signature.
+fn c_type(rust: &str) -> String {
+    if let Some(pointee) = rust.strip_prefix("*mut ") {
+        return format!("{} *", c_type(pointee));
+    }
+    if let Some(pointee) = rust.strip_prefix("*const ") {
+        return format!("const {} *", c_type(pointee));
+    }
+    match rust {
+        "usize" => "size_t",
+        "u8" => "uint8_t",
+        "u16" => "uint16_t",
+        "u32" => "uint32_t",
+        "i16" => "int16_t",
+        "c_char" => "char",
+        other => other,
+    }
+    .to_owned()
+}
+
+/// `ty name`, without a space after pointers.
+fn declaration(ty: &str, name: &str) -> String {
+    if ty.ends_with('*') {
+        format!("{ty}{name}")
+    } else {
+        format!("{ty} {name}")
+    }
+}
+
+fn generate_enums(out: &mut String) {
+    for e in FFI_ENUMS {
+        writeln!(out, "\ntypedef uint8_t {};\nenum {{", e.name).unwrap();
+        for (variant, value) in e.variants {
+            let constant = screaming_case(&format!("{}{variant}", e.name));
+            writeln!(out, "    {constant} = {value},").unwrap();
+        }
+        writeln!(out, "}};").unwrap();
+    }
+}
+
+/// Writes `l` after the structs it contains, unless it was written already.
+fn generate_struct(out: &mut String, written: &mut Vec<&'static str>, l: &'static FfiLayout) {
+    if written.contains(&l.name) {
+        return;
+    }
+    for f in l.fields {
+        if let FfiKind::Struct(name) = f.kind {
+            generate_struct(out, written, layout(name));
+        }
+    }
+    written.push(l.name);
+
+    let name = l.name;
+    writeln!(out, "\ntypedef struct {name} {{").unwrap();
+    for f in l.fields {
+        let array = if f.len > 1 {
+            format!("[{}]", f.len)
+        } else {
+            String::new()
+        };
+        writeln!(out, "    {} {}{array};", field_type(f.kind), f.name).unwrap();
+    }
+    writeln!(out, "}} {name};").unwrap();
+
+    writeln!(
+        out,
+        "DIM_LO_ASSERT(sizeof({name}) == {}, \"size of {name}\");",
+        l.size
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "DIM_LO_ASSERT(DIM_LO_ALIGNOF({name}) == {}, \"alignment of {name}\");",
+        l.align
+    )
+    .unwrap();
+    for f in l.fields {
+        writeln!(
+            out,
+            "DIM_LO_ASSERT(offsetof({name}, {0}) == {1}, \"offset of {name}::{0}\");",
+            f.name, f.offset
+        )
+        .unwrap();
+    }
+}
+
+fn generate_functions(out: &mut String) {
+    let exports = parse_exports(LO_FFI_LIB);
+
+    // Types that only appear behind pointers, e.g. the context
+    let mut opaque = vec![];
+    for e in &exports {
+        for ty in e.params.iter().map(|(_, ty)| ty).chain(&e.ret) {
+            let name = ty.rsplit(' ').next().unwrap();
+            if name.starts_with(|c: char| c.is_ascii_uppercase())
+                && !FFI_LAYOUTS.iter().any(|l| l.name == name)
+                && !opaque.contains(&name)
+            {
+                opaque.push(name);
+            }
+        }
+    }
+    out.push('\n');
+    for name in opaque {
+        writeln!(out, "typedef struct {name} {name};").unwrap();
+    }
+
+    for e in &exports {
+        let params = if e.params.is_empty() {
+            "void".to_owned()
+        } else {
+            e.params
+                .iter()
+                .map(|(name, ty)| declaration(&c_type(ty), name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let ret = e.ret.as_deref().map_or("void".to_owned(), c_type);
+        out.push('\n');
+        write_doc(out, "", &e.docs);
+        writeln!(
+            out,
+            "{};",
+            declaration(&ret, &format!("{}({params})", e.name))
+        )
+        .unwrap();
+    }
+}
+
+/// The C header for lo-ffi.
+pub fn generate() -> String {
+    let mut out = PREAMBLE.to_owned();
+    writeln!(out, "\n#define DIM_LO_ABI_VERSION {ABI_VERSION}").unwrap();
+    generate_enums(&mut out);
+    let mut written = vec![];
+    for l in FFI_LAYOUTS {
+        generate_struct(&mut out, &mut written, l);
+    }
+    generate_functions(&mut out);
+    out.push_str(POSTAMBLE);
+    out
+}
@@ -1,6 +1,21 @@
-use std::process::Command;
+use std::{env, fs, process::Command};
+
+mod bindings;
+mod header;
+#[cfg(test)]
+mod tests;
+
+const OUT_DIR: &str = "./target/wasm32-unknown-unknown/wasm";
+/// The C header is checked in, so that native hosts can build without running this.
+const HEADER_PATH: &str = "./lo-ffi/include/dim_lo.h";
+/// The TypeScript bindings are checked in too, and copied next to the WASM blob.
+const BINDINGS_PATH: &str = "./lo-web/bindings/lo_web.ts";
 
 fn main() {
+    // `--bindings-only` skips the WASM build, e.g. to update the bindings without Binaryen.
+    // The bindings include the C header of lo-ffi.
+    let bindings_only = env::args().any(|a| a == "--bindings-only");
+
     let cmd = |command: &str, args: &[&str]| {
         let mut child = Command::new(command)
             .args(args)
@@ -14,6 +29,20 @@ fn main() {
         }
     };
 
+    if !bindings_only {
+        build(cmd);
+    }
+
+    fs::create_dir_all(OUT_DIR).expect("failed to create output directory");
+    let bindings = bindings::generate();
+    fs::write(BINDINGS_PATH, &bindings).expect("failed to write bindings");
+    fs::write(format!("{OUT_DIR}/lo_web.ts"), bindings).expect("failed to write bindings");
+    fs::write(HEADER_PATH, header::generate()).expect("failed to write header");
+
+    println!("ok");
+}
+
+fn build(cmd: impl Fn(&str, &[&str])) {
     cmd(
         "cargo",
         &[
@@ -34,6 +63,4 @@ fn main() {
             "./target/wasm32-unknown-unknown/wasm/lo_web.opt.wasm",
         ],
     );
-
-    println!("ok");
 }
@@ -0,0 +1,65 @@
+use crate::bindings::parse_exports;
+
+/// The checked-in header and bindings must be what `cargo compile --bindings-only`
+/// generates from the current sources. Regenerate them after changing the FFI.
+#[test]
+fn check_generated_files_up_to_date() {
+    assert!(
+        crate::header::generate() == include_str!("../../lo-ffi/include/dim_lo.h"),
+        "lo-ffi/include/dim_lo.h is out of date, run `cargo compile --bindings-only`"
+    );
+    assert!(
+        crate::bindings::generate() == include_str!("../../lo-web/bindings/lo_web.ts"),
+        "lo-web/bindings/lo_web.ts is out of date, run `cargo compile --bindings-only`"
+    );
+}
+
+#[test]
+fn check_parse_exports() {
+    let source = r#"
+/// Does things.
+#[no_mangle]
+pub extern "C" fn lo_things(
+    ctx: *mut Context,
+    stats: *const [u16; NUM_STATS],
+    num: usize,
+) -> *mut Results {
+}
+
+#[unsafe(no_mangle)]
+fn lo_nothing() {}
+"#;
+    let exports = parse_exports(source);
+    assert_eq!(exports.len(), 2);
+    let things = &exports[0];
+    assert_eq!(things.name, "lo_things");
+    assert_eq!(things.docs, ["Does things."]);
+    let params = things
+        .params
+        .iter()
+        .map(|(name, ty)| (name.as_str(), ty.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        params,
+        [
+            ("ctx", "*mut Context"),
+            ("stats", "*const [u16; NUM_STATS]"),
+            ("num", "usize")
+        ]
+    );
+    assert_eq!(things.ret.as_deref(), Some("*mut Results"));
+    assert_eq!(exports[1].name, "lo_nothing");
+    assert!(exports[1].params.is_empty() && exports[1].ret.is_none());
+}
+
+#[test]
+#[should_panic(expected = "can't parse parameter")]
+fn check_parse_exports_fails_loudly() {
+    parse_exports("#[no_mangle]\nfn lo_pattern((a, b): (u32, u32)) {}\n");
+}
+
+#[test]
+#[should_panic(expected = "unsupported export attribute")]
+fn check_parse_exports_attributes() {
+    parse_exports("#[export_name = \"lo_renamed\"]\nfn renamed() {}\n");
+}
@@ -0,0 +1,235 @@
+//! Describes the memory layout of the FFI types so that hosts can check
+//! at startup that they agree with the library about where fields are.
+
+use core::num::NonZeroU32;
+
+use crate::types::{
+    BonusStatMode, EnergyType, ProcessArgs, ProcessArmorSet, ProcessItem, ProcessMinMaxStats,
+    ProcessMod, ProcessSetBonus, ProcessStatMod, ProcessStats, ProcessTierBounds, ProcessTuning,
+    SignedStats, StatRuleset, Stats,
+};
+
+/// Bumped whenever the layout of an FFI type or the meaning of one of its fields changes.
+pub const ABI_VERSION: u32 = 1;
+
+/// What a field holds, so that bindings for other languages can be generated.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FfiKind {
+    U8,
+    U16,
+    U32,
+    I16,
+    Bool,
+    /// A `u8` enum, described in `FFI_ENUMS`.
+    Enum(&'static str),
+    /// Another FFI struct, described in `FFI_LAYOUTS`.
+    Struct(&'static str),
+}
+
+/// Implemented by every type that appears in an FFI struct.
+pub trait FfiType {
+    const KIND: FfiKind;
+    /// How many values of `KIND` the type consists of.
+    const LEN: usize = 1;
+}
+
+pub struct FfiField {
+    pub name: &'static str,
+    pub offset: usize,
+    pub size: usize,
+    pub kind: FfiKind,
+    /// 1, or the number of elements if the field is an array.
+    pub len: usize,
+}
+
+pub struct FfiLayout {
+    pub name: &'static str,
+    pub size: usize,
+    pub align: usize,
+    /// All fields, in declaration order.
+    pub fields: &'static [FfiField],
+}
+
+pub struct FfiEnum {
+    pub name: &'static str,
+    /// All variants and their discriminants, in declaration order.
+    pub variants: &'static [(&'static str, u32)],
+}
+
+macro_rules! ffi_scalars {
+    ($($ty:ty => $kind:ident, $len:literal;)*) => {
+        $(
+            impl FfiType for $ty {
+                const KIND: FfiKind = FfiKind::$kind;
+                const LEN: usize = $len;
+            }
+        )*
+    };
+}
+
+ffi_scalars! {
+    u8 => U8, 1;
+    u16 => U16, 1;
+    u32 => U32, 1;
+    i16 => I16, 1;
+    bool => Bool, 1;
+    Option<NonZeroU32> => U32, 1;
+    Stats => U16, 6;
+    SignedStats => I16, 6;
+}
+
+impl<T: FfiType, const N: usize> FfiType for [T; N] {
+    const KIND: FfiKind = T::KIND;
+    const LEN: usize = N * T::LEN;
+}
+
+const fn field_size<T, F>(_: fn(&T) -> &F) -> usize {
+    core::mem::size_of::<F>()
+}
+
+const fn field_kind<T, F: FfiType>(_: fn(&T) -> &F) -> (FfiKind, usize) {
+    (F::KIND, F::LEN)
+}
+
+/// Collects the `repr(u8)` enums into `FFI_ENUMS`. Like fields, variants must be
+/// listed exhaustively.
+macro_rules! ffi_enums {
+    ($($ty:ident { $($variant:ident),* $(,)? };)*) => {
+        $(
+            const _: () = {
+                if core::mem::size_of::<$ty>() != 1 {
+                    panic!()
+                }
+            };
+            const _: fn($ty) = |v| match v {
+                $($ty::$variant => {})*
+            };
+            impl FfiType for $ty {
+                const KIND: FfiKind = FfiKind::Enum(stringify!($ty));
+            }
+        )*
+
+        /// All enums used in FFI types.
+        pub const FFI_ENUMS: &[FfiEnum] = &[$(
+            FfiEnum {
+                name: stringify!($ty),
+                variants: &[$((stringify!($variant), $ty::$variant as u32),)*],
+            },
+        )*];
+    };
+}
+
+ffi_enums! {
+    EnergyType { Any, Arc, Solar, Void, Stasis };
+    BonusStatMode { Always, Conditional };
+    StatRuleset { Tiers, Points };
+}
+
+/// Asserts the size and alignment of every type at compile time and collects
+/// the layouts into `FFI_LAYOUTS`. Fields must be listed exhaustively, so that
+/// a new field can't be forgotten here.
+macro_rules! ffi_layouts {
+    ($($ty:ident, $size:literal, $align:literal { $($field:ident),* $(,)? };)*) => {
+        $(
+            const _: () = {
+                if core::mem::size_of::<$ty>() != $size {
+                    panic!()
+                }
+                if core::mem::align_of::<$ty>() != $align {
+                    panic!()
+                }
+            };
+            const _: fn(&$ty) = |v| {
+                let $ty { $($field: _),* } = v;
+            };
+            impl FfiType for $ty {
+                const KIND: FfiKind = FfiKind::Struct(stringify!($ty));
+            }
+        )*
+
+        /// The layouts of all FFI types, in a fixed order.
+        pub const FFI_LAYOUTS: &[FfiLayout] = &[$(
+            FfiLayout {
+                name: stringify!($ty),
+                size: $size,
+                align: $align,
+                fields: &[$(
+                    FfiField {
+                        name: stringify!($field),
+                        offset: core::mem::offset_of!($ty, $field),
+                        size: field_size(|v: &$ty| &v.$field),
+                        kind: field_kind(|v: &$ty| &v.$field).0,
+                        len: field_kind(|v: &$ty| &v.$field).1,
+                    },
+                )*],
+            },
+        )*];
+    };
+}
+
+// FFI guarantees...
+ffi_layouts! {
+    ProcessItem, 44, 4 {
+        id, power, energy_type, energy_val, energy_cap, exotic, mod_tags, stats,
+        bonus_stats, bonus_mode, tuning_stats, set_id, exotic_perk_id,
+    };
+    ProcessMod, 12, 4 { hash, mod_tag, energy_type, energy_val };
+    ProcessStatMod, 24, 4 { inner_mod, stats };
+    ProcessArmorSet, 80, 4 {
+        stats, items, power, total_tier, extra_stat_mods, stats_with_bonus, set_bonuses, tunings,
+    };
+    ProcessStats, 28, 4 {
+        num_valid_sets, skipped_low_tier, skipped_stat_range, skipped_mods_unfit,
+        skipped_double_exotic, skipped_no_exotic, skipped_set_bonus,
+    };
+    ProcessTierBounds, 24, 2 { lower_bounds, upper_bounds };
+    ProcessMinMaxStats, 24, 2 { min, max };
+    ProcessSetBonus, 4, 2 { set_id, count };
+    ProcessTuning, 2, 1 { stat_plus, stat_minus };
+    ProcessArgs, 64, 4 {
+        base_stats, bounds, any_exotic, auto_mods, ruleset, stat_caps, set_bonuses,
+        required_exotic_perk_id,
+    };
+}
+
+/// The number of words in `layout_table`.
+pub const fn layout_table_len() -> usize {
+    let mut len = 2;
+    let mut i = 0;
+    while i < FFI_LAYOUTS.len() {
+        len += 3 + 2 * FFI_LAYOUTS[i].fields.len();
+        i += 1;
+    }
+    len
+}
+
+/// `FFI_LAYOUTS` flattened into words, for hosts that can't read Rust structs:
+///
+/// `[ABI_VERSION, number of types, ...types]`, where each type is
+/// `[size, align, number of fields, ...fields]` and each field is `[offset, size]`.
+///
+/// Types and fields are in the order of `FFI_LAYOUTS`.
+pub const fn layout_table<const N: usize>() -> [u32; N] {
+    assert!(N == layout_table_len());
+    let mut table = [0; N];
+    table[0] = ABI_VERSION;
+    table[1] = FFI_LAYOUTS.len() as u32;
+    let mut pos = 2;
+    let mut i = 0;
+    while i < FFI_LAYOUTS.len() {
+        let layout = &FFI_LAYOUTS[i];
+        table[pos] = layout.size as u32;
+        table[pos + 1] = layout.align as u32;
+        table[pos + 2] = layout.fields.len() as u32;
+        pos += 3;
+        let mut j = 0;
+        while j < layout.fields.len() {
+            table[pos] = layout.fields[j].offset as u32;
+            table[pos + 1] = layout.fields[j].size as u32;
+            pos += 2;
+            j += 1;
+        }
+        i += 1;
+    }
+    table
+}
@@ -0,0 +1,201 @@
+use core::fmt;
+
+use crate::types::{
+    EnergyType, ProcessArgs, ProcessItem, ProcessMod, ProcessStatMod, NUM_ITEM_BUCKETS,
+};
+
+/// The most energy an item can have, and the most a single mod can cost.
+/// Keeping everything at or below this keeps the energy arithmetic from overflowing.
+pub const MAX_ENERGY: u8 = 10;
+
+/// The most stat points, or the biggest penalty, a single item, auto stat mod, base stat or
+/// stat cap can have. With five items, five mods and tuning, a set's stats then stay
+/// far from the limits of the `i16` they are added up in.
+pub const MAX_STAT: u16 = 1000;
+
+/// Which of the three lists of locked mods a mod is in.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum ModList {
+    General = 0,
+    Combat = 1,
+    Activity = 2,
+}
+
+/// Input that `try_dim_lo_process` rejects.
+/// Items are identified by bucket and index in the bucket, mods by list and index in the list.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum ProcessError {
+    /// The item has used more energy than it has.
+    ItemEnergyOverCapacity { bucket: usize, index: usize },
+    /// The item has more than `MAX_ENERGY` energy.
+    ItemEnergyTooHigh { bucket: usize, index: usize },
+    /// The mod costs more than `MAX_ENERGY` energy.
+    ModEnergyTooHigh { list: ModList, index: usize },
+    /// The auto stat mod costs more than `MAX_ENERGY` energy.
+    AutoModEnergyTooHigh { index: usize },
+    /// The mod slot is empty (no hash), but not zeroed.
+    EmptyModNotZeroed { list: ModList, index: usize },
+    /// There's an empty general mod slot before a filled one.
+    GeneralModsNotPacked { index: usize },
+    /// The stat's lower bound is above its upper bound.
+    InvertedBounds { stat: usize },
+    /// The stat's lower bound is above the stat cap, so no set could ever reach it.
+    LowerBoundAboveCap { stat: usize },
+    /// `ProcessArgs::auto_mods` is more than the `NUM_ITEM_BUCKETS` stat mod slots.
+    TooManyAutoMods { auto_mods: u8 },
+    /// One of the item's stats or bonus stats is beyond `MAX_STAT`.
+    ItemStatTooHigh { bucket: usize, index: usize },
+    /// One of the auto stat mod's stats is above `MAX_STAT`.
+    AutoModStatTooHigh { index: usize },
+    /// The stat's base stat or stat cap is beyond `MAX_STAT`.
+    StatTooHigh { stat: usize },
+}
+
+impl fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ProcessError::ItemEnergyOverCapacity { bucket, index } => write!(
+                f,
+                "item {index} in bucket {bucket} uses more energy than it has"
+            ),
+            ProcessError::ItemEnergyTooHigh { bucket, index } => write!(
+                f,
+                "item {index} in bucket {bucket} has more than {MAX_ENERGY} energy"
+            ),
+            ProcessError::ModEnergyTooHigh { list, index } => {
+                write!(f, "{list} mod {index} costs more than {MAX_ENERGY} energy")
+            }
+            ProcessError::AutoModEnergyTooHigh { index } => write!(
+                f,
+                "auto stat mod {index} costs more than {MAX_ENERGY} energy"
+            ),
+            ProcessError::EmptyModNotZeroed { list, index } => {
+                write!(f, "{list} mod {index} has no hash, but isn't zeroed")
+            }
+            ProcessError::GeneralModsNotPacked { index } => {
+                write!(f, "general mod {index} comes after an empty slot")
+            }
+            ProcessError::InvertedBounds { stat } => {
+                write!(f, "stat {stat} has a lower bound above its upper bound")
+            }
+            ProcessError::LowerBoundAboveCap { stat } => {
+                write!(f, "stat {stat} has a lower bound above the stat cap")
+            }
+            ProcessError::TooManyAutoMods { auto_mods } => write!(
+                f,
+                "{auto_mods} auto stat mods, but there are only {NUM_ITEM_BUCKETS} stat mod slots"
+            ),
+            ProcessError::ItemStatTooHigh { bucket, index } => write!(
+                f,
+                "item {index} in bucket {bucket} has a stat beyond {MAX_STAT}"
+            ),
+            ProcessError::AutoModStatTooHigh { index } => {
+                write!(f, "auto stat mod {index} has a stat above {MAX_STAT}")
+            }
+            ProcessError::StatTooHigh { stat } => write!(
+                f,
+                "stat {stat} has a base stat or stat cap beyond {MAX_STAT}"
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ModList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ModList::General => "general",
+            ModList::Combat => "combat",
+            ModList::Activity => "activity",
+        })
+    }
+}
+
+/// Checks the input of `dim_lo_process` for everything it can't handle.
+pub fn validate_inputs(
+    items: &[&[ProcessItem]; NUM_ITEM_BUCKETS],
+    general_mods: &[ProcessMod; NUM_ITEM_BUCKETS],
+    combat_mods: &[ProcessMod; NUM_ITEM_BUCKETS],
+    activity_mods: &[ProcessMod; NUM_ITEM_BUCKETS],
+    optional_stat_mods: &[ProcessStatMod],
+    args: &ProcessArgs,
+) -> Result<(), ProcessError> {
+    for (bucket, list) in items.iter().enumerate() {
+        for (index, item) in list.iter().enumerate() {
+            if item.energy_cap > MAX_ENERGY {
+                return Err(ProcessError::ItemEnergyTooHigh { bucket, index });
+            }
+            if item.energy_val > item.energy_cap {
+                return Err(ProcessError::ItemEnergyOverCapacity { bucket, index });
+            }
+            if item.stats.0.iter().any(|&s| s > MAX_STAT)
+                || item
+                    .bonus_stats
+                    .0
+                    .iter()
+                    .any(|s| s.unsigned_abs() > MAX_STAT)
+            {
+                return Err(ProcessError::ItemStatTooHigh { bucket, index });
+            }
+        }
+    }
+
+    let lists = [
+        (ModList::General, general_mods),
+        (ModList::Combat, combat_mods),
+        (ModList::Activity, activity_mods),
+    ];
+    for (list, mods) in lists {
+        for (index, m) in mods.iter().enumerate() {
+            if m.energy_val > MAX_ENERGY {
+                return Err(ProcessError::ModEnergyTooHigh { list, index });
+            }
+            if m.hash.is_none()
+                && (m.mod_tag.is_some() || m.energy_type != EnergyType::Any || m.energy_val != 0)
+            {
+                return Err(ProcessError::EmptyModNotZeroed { list, index });
+            }
+        }
+    }
+
+    // Locked stat mods are expected at the front, see `stat_mod_set::generate_mods_options`.
+    if let Some(empty) = general_mods.iter().position(|m| m.hash.is_none()) {
+        if let Some(offset) = general_mods[empty..].iter().position(|m| m.hash.is_some()) {
+            return Err(ProcessError::GeneralModsNotPacked {
+                index: empty + offset,
+            });
+        }
+    }
+
+    for (index, m) in optional_stat_mods.iter().enumerate() {
+        if m.inner_mod.energy_val > MAX_ENERGY {
+            return Err(ProcessError::AutoModEnergyTooHigh { index });
+        }
+        if m.stats.0.iter().any(|&s| s > MAX_STAT) {
+            return Err(ProcessError::AutoModStatTooHigh { index });
+        }
+    }
+
+    // The stat mod picks have room for that many mods, see `stat_mod_set`.
+    if args.auto_mods as usize > NUM_ITEM_BUCKETS {
+        return Err(ProcessError::TooManyAutoMods {
+            auto_mods: args.auto_mods,
+        });
+    }
+
+    let bounds = &args.bounds;
+    for (stat, cap) in args.effective_stat_caps().into_iter().enumerate() {
+        if args.base_stats.0[stat].unsigned_abs() > MAX_STAT || cap > MAX_STAT {
+            return Err(ProcessError::StatTooHigh { stat });
+        }
+        if bounds.lower_bounds[stat] > bounds.upper_bounds[stat] {
+            return Err(ProcessError::InvertedBounds { stat });
+        }
+        if bounds.lower_bounds[stat] as u32 * args.points_per_unit() as u32 > cap as u32 {
+            return Err(ProcessError::LowerBoundAboveCap { stat });
+        }
+    }
+
+    Ok(())
+}
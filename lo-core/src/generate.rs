@@ -0,0 +1,249 @@
+//! Seeded synthetic inventories for stress and scale testing. The same seed and options
+//! always give the same `ProcessInput`, on every platform.
+
+use alloc::vec::Vec;
+use core::num::NonZeroU32;
+
+use crate::{
+    error::MAX_ENERGY,
+    input::ProcessInput,
+    types::{
+        EnergyType, ProcessArgs, ProcessItem, ProcessMod, ProcessStatMod, ProcessTierBounds,
+        StatRuleset, Stats, NUM_ITEM_BUCKETS, NUM_STATS,
+    },
+};
+
+/// A small, fast PRNG (SplitMix64). Not for anything but tests.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `lo..=hi`.
+    pub fn range(&mut self, lo: u32, hi: u32) -> u32 {
+        debug_assert!(lo <= hi);
+        let span = (hi - lo) as u64 + 1;
+        lo + ((self.next_u64() as u128 * span as u128) >> 64) as u32
+    }
+
+    /// True with a chance of `percent` percent.
+    pub fn chance(&mut self, percent: u8) -> bool {
+        self.range(0, 99) < percent as u32
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.range(0, i as u32) as usize);
+        }
+    }
+}
+
+pub struct GenerateOptions {
+    pub seed: u64,
+    pub items_per_bucket: [u16; NUM_ITEM_BUCKETS],
+    /// The chance of an item being exotic, in percent.
+    pub exotic_percent: u8,
+    /// The range of an item's total base stats, see `roll_stats`.
+    pub stat_total: (u16, u16),
+    /// The range of item energy capacities.
+    pub energy_cap: (u8, u8),
+    /// Whether items and locked mods have elements, otherwise everything is `EnergyType::Any`.
+    pub elements: bool,
+    /// The number of mod tags. Items take each tag with a 50% chance,
+    /// locked activity mods need one of them.
+    pub mod_tags: u8,
+    /// The number of locked general, combat and activity mods.
+    pub locked_mods: [u8; 3],
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            items_per_bucket: [20; NUM_ITEM_BUCKETS],
+            exotic_percent: 10,
+            stat_total: (52, 68),
+            energy_cap: (7, MAX_ENERGY),
+            elements: false,
+            mod_tags: 0,
+            locked_mods: [0; 3],
+        }
+    }
+}
+
+/// Stats are 2 to 30 each.
+const STAT_RANGE: (u16, u16) = (2, 30);
+
+/// The auto stat mods of the game: a major (+10) and a minor (+5) mod per stat.
+const MAJOR_MOD_COSTS: [u8; NUM_STATS] = [3, 3, 4, 3, 5, 3];
+const MINOR_MOD_COSTS: [u8; NUM_STATS] = [1, 1, 2, 1, 2, 1];
+
+/// Armor rolls its stats in two groups, mobility/resilience/recovery and
+/// discipline/intellect/strength, which both get a share of the total. Within a group,
+/// one stat usually spikes, which picking the stats one after another in random order
+/// reproduces.
+fn roll_stats(rng: &mut Rng, options: &GenerateOptions) -> Stats {
+    let (stat_min, stat_max) = STAT_RANGE;
+    let group_min = stat_min * 3;
+    let group_max = stat_max * 3;
+    let (lo, hi) = options.stat_total;
+    let total = rng.range(
+        lo.min(hi).clamp(group_min * 2, group_max * 2) as u32,
+        hi.max(lo).clamp(group_min * 2, group_max * 2) as u32,
+    ) as u16;
+    let first = rng.range(
+        group_min.max(total.saturating_sub(group_max)) as u32,
+        group_max.min(total - group_min) as u32,
+    ) as u16;
+
+    let mut stats = [0; NUM_STATS];
+    for (group, group_total) in [(0, first), (3, total - first)] {
+        let mut order = [0, 1, 2];
+        rng.shuffle(&mut order);
+        let mut left = group_total;
+        for (n, &idx) in order.iter().enumerate() {
+            let rest = 2 - n as u16;
+            let stat = if rest == 0 {
+                left
+            } else {
+                rng.range(
+                    stat_min.max(left.saturating_sub(stat_max * rest)) as u32,
+                    stat_max.min(left - stat_min * rest) as u32,
+                ) as u16
+            };
+            stats[group + idx] = stat;
+            left -= stat;
+        }
+    }
+    Stats(stats)
+}
+
+fn roll_element(rng: &mut Rng, options: &GenerateOptions) -> EnergyType {
+    if !options.elements {
+        return EnergyType::Any;
+    }
+    [
+        EnergyType::Arc,
+        EnergyType::Solar,
+        EnergyType::Void,
+        EnergyType::Stasis,
+    ][rng.range(0, 3) as usize]
+}
+
+const EMPTY_MOD: ProcessMod = ProcessMod {
+    hash: None,
+    mod_tag: None,
+    energy_type: EnergyType::Any,
+    energy_val: 0,
+};
+
+/// Mod hashes are made up: 1-12 for the auto stat mods, 100 and up for locked mods.
+fn locked_mods(
+    rng: &mut Rng,
+    options: &GenerateOptions,
+    list: usize,
+    costs: (u32, u32),
+) -> [ProcessMod; NUM_ITEM_BUCKETS] {
+    let mut mods = [EMPTY_MOD; NUM_ITEM_BUCKETS];
+    let count = (options.locked_mods[list] as usize).min(NUM_ITEM_BUCKETS);
+    for (idx, m) in mods.iter_mut().take(count).enumerate() {
+        // General mods fit any item, activity mods only items with their tag
+        let mod_tag = match options.mod_tags.min(32) {
+            num_tags @ 1.. if list == 2 => NonZeroU32::new(1 << rng.range(0, num_tags as u32 - 1)),
+            _ => None,
+        };
+        *m = ProcessMod {
+            hash: NonZeroU32::new(100 + (list * NUM_ITEM_BUCKETS + idx) as u32),
+            mod_tag,
+            energy_type: match list {
+                0 => EnergyType::Any,
+                _ => roll_element(rng, options),
+            },
+            energy_val: rng.range(costs.0, costs.1) as u8,
+        };
+    }
+    mods
+}
+
+/// Generates an inventory with the game's auto stat mods and bounds that allow any set.
+/// Items are numbered across buckets, in bucket order.
+pub fn generate(options: &GenerateOptions) -> ProcessInput {
+    let mut rng = Rng::new(options.seed);
+    let mod_tags = options.mod_tags.min(32);
+    let (cap_min, cap_max) = options.energy_cap;
+    let cap_max = cap_max.min(MAX_ENERGY);
+
+    let mut next_id = 0u16;
+    let items = options.items_per_bucket.map(|count| {
+        (0..count)
+            .map(|_| {
+                let id = next_id;
+                next_id = next_id.wrapping_add(1);
+                ProcessItem {
+                    id,
+                    power: rng.range(1800, 1810) as u16,
+                    energy_type: roll_element(&mut rng, options),
+                    energy_val: 0,
+                    energy_cap: rng.range(cap_min.min(cap_max) as u32, cap_max as u32) as u8,
+                    exotic: rng.chance(options.exotic_percent),
+                    mod_tags: (0..mod_tags)
+                        .filter(|_| rng.chance(50))
+                        .fold(0, |acc, tag| acc | 1 << tag),
+                    stats: roll_stats(&mut rng, options),
+                    ..Default::default()
+                }
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let general_mods = locked_mods(&mut rng, options, 0, (1, 5));
+    let combat_mods = locked_mods(&mut rng, options, 1, (1, 4));
+    let activity_mods = locked_mods(&mut rng, options, 2, (1, 4));
+
+    let mut auto_mods = Vec::with_capacity(NUM_STATS * 2);
+    for stat in 0..NUM_STATS {
+        for (n, (points, cost)) in [(10, MAJOR_MOD_COSTS[stat]), (5, MINOR_MOD_COSTS[stat])]
+            .into_iter()
+            .enumerate()
+        {
+            let mut stats = [0; NUM_STATS];
+            stats[stat] = points;
+            auto_mods.push(ProcessStatMod {
+                inner_mod: ProcessMod {
+                    hash: NonZeroU32::new((1 + stat * 2 + n) as u32),
+                    mod_tag: None,
+                    energy_type: EnergyType::Any,
+                    energy_val: cost,
+                },
+                stats: Stats(stats),
+            });
+        }
+    }
+
+    ProcessInput {
+        items,
+        general_mods,
+        combat_mods,
+        activity_mods,
+        auto_mods,
+        args: ProcessArgs {
+            bounds: ProcessTierBounds {
+                lower_bounds: [0; NUM_STATS],
+                upper_bounds: [10; NUM_STATS],
+            },
+            auto_mods: 5,
+            ruleset: StatRuleset::Tiers,
+            ..Default::default()
+        },
+    }
+}
@@ -0,0 +1,701 @@
+//! All inputs of a run in one owned struct, and a compact binary format for them,
+//! so that hosts can hand over everything at once and failing inputs can be replayed.
+//!
+//! The packed format is a sequence of little-endian values without any padding:
+//!
+//! * `b"DLOP"`, then the format version (u16, currently 1)
+//! * `any_exotic` (u8, 0 or 1), `auto_mods` (u8, at most 5), `ruleset` (u8),
+//!   `required_exotic_perk_id` (u32)
+//! * `base_stats` (6 × i16), lower bounds, upper bounds, stat caps (6 × u16 each)
+//! * the set bonus constraints (2 × set id u16, count u16)
+//! * the number of items per bucket (5 × u16), the number of auto stat mods (u16)
+//! * all items, bucket by bucket, each one:
+//!   `id` u16, `power` u16, `energy_type` u8, `energy_val` u8, `energy_cap` u8, `exotic` u8,
+//!   `mod_tags` u32, `stats` 6 × u16, `bonus_stats` 6 × i16, `bonus_mode` u8,
+//!   `tuning_stats` u8, `set_id` u16, `exotic_perk_id` u32
+//! * 15 mods (5 general, 5 combat, 5 activity), each one:
+//!   `hash` u32, `mod_tag` u32, `energy_type` u8, `energy_val` u8
+//! * all auto stat mods, each one a mod like above followed by `stats` 6 × u16
+//!
+//! Enums are encoded as their discriminants, `None` hashes and tags as 0.
+//!
+//! Dumps hold the inputs exactly as an FFI host wrote them, for replaying runs whose
+//! input may have been marshalled wrong:
+//!
+//! * `b"DLOD"`, then `ABI_VERSION` (u32)
+//! * the number of items per bucket (5 × u16), the number of auto stat mods (u32)
+//! * `ProcessArgs`, all items, 15 mods and all auto stat mods, each one in its in-memory
+//!   layout (see `abi::FFI_LAYOUTS`), little-endian and with zeroed padding
+//!
+//! Reading a dump only checks bools and enums, so that it can replay exactly what the host
+//! passed. Anything else, like too many `auto_mods`, is left to `try_dim_lo_process`.
+
+use alloc::vec::Vec;
+use core::{
+    fmt,
+    mem::{offset_of, size_of},
+    num::NonZeroU32,
+};
+
+use crate::{
+    abi::ABI_VERSION,
+    error::ProcessError,
+    try_dim_lo_process,
+    types::{
+        BonusStatMode, EnergyType, ProcessArgs, ProcessArmorSet, ProcessItem, ProcessMinMaxStats,
+        ProcessMod, ProcessSetBonus, ProcessStatMod, ProcessStats, ProcessTierBounds, SignedStats,
+        StatRuleset, Stats, NUM_ITEM_BUCKETS, NUM_SET_BONUSES, NUM_STATS,
+    },
+    ProcessRun,
+};
+
+pub const PACKED_MAGIC: [u8; 4] = *b"DLOP";
+pub const PACKED_VERSION: u16 = 1;
+pub const DUMP_MAGIC: [u8; 4] = *b"DLOD";
+/// The size of a dump before the `ProcessArgs`.
+pub const DUMP_HEADER_LEN: usize = 4 + 4 + 2 * NUM_ITEM_BUCKETS + 4;
+
+/// Everything `dim_lo_process` needs.
+pub struct ProcessInput {
+    pub items: [Vec<ProcessItem>; NUM_ITEM_BUCKETS],
+    pub general_mods: [ProcessMod; NUM_ITEM_BUCKETS],
+    pub combat_mods: [ProcessMod; NUM_ITEM_BUCKETS],
+    pub activity_mods: [ProcessMod; NUM_ITEM_BUCKETS],
+    pub auto_mods: Vec<ProcessStatMod>,
+    pub args: ProcessArgs,
+}
+
+/// Why packed input or a dump couldn't be read. Offsets are in bytes from the start of the input.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum PackedError {
+    /// The input doesn't start with `PACKED_MAGIC` or `DUMP_MAGIC`, respectively.
+    BadMagic,
+    /// The format version of packed input, or the ABI version of a dump.
+    UnsupportedVersion(u32),
+    /// The input ended in the middle of the value at this offset.
+    UnexpectedEnd { offset: usize },
+    /// The enum or bool at this offset has an invalid value.
+    InvalidValue { offset: usize },
+    /// There's more input after the last auto stat mod.
+    TrailingBytes { offset: usize },
+}
+
+impl fmt::Display for PackedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            PackedError::BadMagic => f.write_str("not a packed LO input or dump"),
+            PackedError::UnsupportedVersion(v) => write!(f, "unsupported version {v}"),
+            PackedError::UnexpectedEnd { offset } => {
+                write!(f, "input ends unexpectedly at byte {offset}")
+            }
+            PackedError::InvalidValue { offset } => {
+                write!(f, "invalid enum or bool at byte {offset}")
+            }
+            PackedError::TrailingBytes { offset } => {
+                write!(f, "trailing bytes after byte {offset}")
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], PackedError> {
+        let offset = self.offset;
+        let bytes = self
+            .bytes
+            .get(offset..offset + N)
+            .ok_or(PackedError::UnexpectedEnd { offset })?;
+        self.offset += N;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, PackedError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, PackedError> {
+        Ok(u16::from_le_bytes(self.take()?))
+    }
+
+    fn i16(&mut self) -> Result<i16, PackedError> {
+        Ok(i16::from_le_bytes(self.take()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, PackedError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    fn u16s(&mut self) -> Result<[u16; NUM_STATS], PackedError> {
+        let mut values = [0; NUM_STATS];
+        for v in &mut values {
+            *v = self.u16()?;
+        }
+        Ok(values)
+    }
+
+    fn i16s(&mut self) -> Result<[i16; NUM_STATS], PackedError> {
+        let mut values = [0; NUM_STATS];
+        for v in &mut values {
+            *v = self.i16()?;
+        }
+        Ok(values)
+    }
+
+    /// Reads a u8 that must map to a value through `f`.
+    fn byte_as<T>(&mut self, f: impl FnOnce(u8) -> Option<T>) -> Result<T, PackedError> {
+        let offset = self.offset;
+        f(self.u8()?).ok_or(PackedError::InvalidValue { offset })
+    }
+
+    fn bool(&mut self) -> Result<bool, PackedError> {
+        self.byte_as(|b| match b {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        })
+    }
+
+    fn energy_type(&mut self) -> Result<EnergyType, PackedError> {
+        self.byte_as(|b| match b {
+            0 => Some(EnergyType::Any),
+            1 => Some(EnergyType::Arc),
+            2 => Some(EnergyType::Solar),
+            3 => Some(EnergyType::Void),
+            4 => Some(EnergyType::Stasis),
+            _ => None,
+        })
+    }
+
+    fn process_mod(&mut self) -> Result<ProcessMod, PackedError> {
+        Ok(ProcessMod {
+            hash: NonZeroU32::new(self.u32()?),
+            mod_tag: NonZeroU32::new(self.u32()?),
+            energy_type: self.energy_type()?,
+            energy_val: self.u8()?,
+        })
+    }
+
+    fn mods(&mut self) -> Result<[ProcessMod; NUM_ITEM_BUCKETS], PackedError> {
+        let mods = (0..NUM_ITEM_BUCKETS)
+            .map(|_| self.process_mod())
+            .collect::<Result<Vec<_>, _>>()?;
+        match mods.try_into() {
+            Ok(x) => Ok(x),
+            Err(_) => unreachable!(),
+        }
+    }
+
+    fn item(&mut self) -> Result<ProcessItem, PackedError> {
+        Ok(ProcessItem {
+            id: self.u16()?,
+            power: self.u16()?,
+            energy_type: self.energy_type()?,
+            energy_val: self.u8()?,
+            energy_cap: self.u8()?,
+            exotic: self.bool()?,
+            mod_tags: self.u32()?,
+            stats: Stats(self.u16s()?),
+            bonus_stats: SignedStats(self.i16s()?),
+            bonus_mode: self.bonus_mode()?,
+            tuning_stats: self.u8()?,
+            set_id: self.u16()?,
+            exotic_perk_id: self.u32()?,
+        })
+    }
+
+    fn bonus_mode(&mut self) -> Result<BonusStatMode, PackedError> {
+        self.byte_as(|b| match b {
+            0 => Some(BonusStatMode::Always),
+            1 => Some(BonusStatMode::Conditional),
+            _ => None,
+        })
+    }
+
+    fn ruleset(&mut self) -> Result<StatRuleset, PackedError> {
+        self.byte_as(|b| match b {
+            0 => Some(StatRuleset::Tiers),
+            1 => Some(StatRuleset::Points),
+            _ => None,
+        })
+    }
+
+    /// Reads a `T` in its in-memory layout. `read` reads each field at its `offset_of!`.
+    fn raw<T>(
+        &mut self,
+        read: impl FnOnce(&mut Fields<'_, 'a>) -> Result<T, PackedError>,
+    ) -> Result<T, PackedError> {
+        let base = self.offset;
+        if self.bytes.len() < base + size_of::<T>() {
+            return Err(PackedError::UnexpectedEnd { offset: base });
+        }
+        let value = read(&mut Fields { reader: self, base })?;
+        self.offset = base + size_of::<T>();
+        Ok(value)
+    }
+
+    fn raw_mod(&mut self) -> Result<ProcessMod, PackedError> {
+        self.raw(|f| {
+            Ok(ProcessMod {
+                hash: NonZeroU32::new(f.at(offset_of!(ProcessMod, hash)).u32()?),
+                mod_tag: NonZeroU32::new(f.at(offset_of!(ProcessMod, mod_tag)).u32()?),
+                energy_type: f.at(offset_of!(ProcessMod, energy_type)).energy_type()?,
+                energy_val: f.at(offset_of!(ProcessMod, energy_val)).u8()?,
+            })
+        })
+    }
+
+    fn raw_mods(&mut self) -> Result<[ProcessMod; NUM_ITEM_BUCKETS], PackedError> {
+        let mods = (0..NUM_ITEM_BUCKETS)
+            .map(|_| self.raw_mod())
+            .collect::<Result<Vec<_>, _>>()?;
+        match mods.try_into() {
+            Ok(x) => Ok(x),
+            Err(_) => unreachable!(),
+        }
+    }
+
+    fn raw_stat_mod(&mut self) -> Result<ProcessStatMod, PackedError> {
+        self.raw(|f| {
+            Ok(ProcessStatMod {
+                inner_mod: f.at(offset_of!(ProcessStatMod, inner_mod)).raw_mod()?,
+                stats: Stats(f.at(offset_of!(ProcessStatMod, stats)).u16s()?),
+            })
+        })
+    }
+
+    fn raw_item(&mut self) -> Result<ProcessItem, PackedError> {
+        self.raw(|f| {
+            Ok(ProcessItem {
+                id: f.at(offset_of!(ProcessItem, id)).u16()?,
+                power: f.at(offset_of!(ProcessItem, power)).u16()?,
+                energy_type: f.at(offset_of!(ProcessItem, energy_type)).energy_type()?,
+                energy_val: f.at(offset_of!(ProcessItem, energy_val)).u8()?,
+                energy_cap: f.at(offset_of!(ProcessItem, energy_cap)).u8()?,
+                exotic: f.at(offset_of!(ProcessItem, exotic)).bool()?,
+                mod_tags: f.at(offset_of!(ProcessItem, mod_tags)).u32()?,
+                stats: Stats(f.at(offset_of!(ProcessItem, stats)).u16s()?),
+                bonus_stats: SignedStats(f.at(offset_of!(ProcessItem, bonus_stats)).i16s()?),
+                bonus_mode: f.at(offset_of!(ProcessItem, bonus_mode)).bonus_mode()?,
+                tuning_stats: f.at(offset_of!(ProcessItem, tuning_stats)).u8()?,
+                set_id: f.at(offset_of!(ProcessItem, set_id)).u16()?,
+                exotic_perk_id: f.at(offset_of!(ProcessItem, exotic_perk_id)).u32()?,
+            })
+        })
+    }
+
+    fn raw_args(&mut self) -> Result<ProcessArgs, PackedError> {
+        self.raw(|f| {
+            let bounds = offset_of!(ProcessArgs, bounds);
+            let mut set_bonuses = [ProcessSetBonus::default(); NUM_SET_BONUSES];
+            for (idx, bonus) in set_bonuses.iter_mut().enumerate() {
+                let offset =
+                    offset_of!(ProcessArgs, set_bonuses) + idx * size_of::<ProcessSetBonus>();
+                bonus.set_id = f.at(offset + offset_of!(ProcessSetBonus, set_id)).u16()?;
+                bonus.count = f.at(offset + offset_of!(ProcessSetBonus, count)).u16()?;
+            }
+            Ok(ProcessArgs {
+                base_stats: SignedStats(f.at(offset_of!(ProcessArgs, base_stats)).i16s()?),
+                bounds: ProcessTierBounds {
+                    lower_bounds: f
+                        .at(bounds + offset_of!(ProcessTierBounds, lower_bounds))
+                        .u16s()?,
+                    upper_bounds: f
+                        .at(bounds + offset_of!(ProcessTierBounds, upper_bounds))
+                        .u16s()?,
+                },
+                any_exotic: f.at(offset_of!(ProcessArgs, any_exotic)).bool()?,
+                auto_mods: f.at(offset_of!(ProcessArgs, auto_mods)).u8()?,
+                ruleset: f.at(offset_of!(ProcessArgs, ruleset)).ruleset()?,
+                stat_caps: f.at(offset_of!(ProcessArgs, stat_caps)).u16s()?,
+                set_bonuses,
+                required_exotic_perk_id: f
+                    .at(offset_of!(ProcessArgs, required_exotic_perk_id))
+                    .u32()?,
+            })
+        })
+    }
+}
+
+/// The fields of a struct that `Reader::raw` reads.
+struct Fields<'r, 'a> {
+    reader: &'r mut Reader<'a>,
+    base: usize,
+}
+
+impl<'a> Fields<'_, 'a> {
+    fn at(&mut self, offset: usize) -> &mut Reader<'a> {
+        self.reader.offset = self.base + offset;
+        self.reader
+    }
+}
+
+/// Writes values at fixed offsets of a zeroed `T`, the counterpart of `Reader::raw`.
+struct RawWriter<'a>(&'a mut [u8]);
+
+impl RawWriter<'_> {
+    /// Appends a zeroed `T` to `out` and passes a writer for it to `write`.
+    fn append<T>(out: &mut Vec<u8>, write: impl FnOnce(&mut RawWriter)) {
+        let base = out.len();
+        out.resize(base + size_of::<T>(), 0);
+        write(&mut RawWriter(&mut out[base..]));
+    }
+
+    fn bytes(&mut self, offset: usize, values: &[u8]) {
+        self.0[offset..offset + values.len()].copy_from_slice(values);
+    }
+
+    fn u16s(&mut self, offset: usize, values: &[u16]) {
+        for (idx, v) in values.iter().enumerate() {
+            self.bytes(offset + 2 * idx, &v.to_le_bytes());
+        }
+    }
+
+    fn i16s(&mut self, offset: usize, values: &[i16]) {
+        for (idx, v) in values.iter().enumerate() {
+            self.bytes(offset + 2 * idx, &v.to_le_bytes());
+        }
+    }
+
+    fn u32(&mut self, offset: usize, value: u32) {
+        self.bytes(offset, &value.to_le_bytes());
+    }
+
+    fn process_mod(&mut self, offset: usize, m: &ProcessMod) {
+        self.u32(
+            offset + offset_of!(ProcessMod, hash),
+            m.hash.map_or(0, NonZeroU32::get),
+        );
+        self.u32(
+            offset + offset_of!(ProcessMod, mod_tag),
+            m.mod_tag.map_or(0, NonZeroU32::get),
+        );
+        self.bytes(
+            offset + offset_of!(ProcessMod, energy_type),
+            &[m.energy_type as u8],
+        );
+        self.bytes(offset + offset_of!(ProcessMod, energy_val), &[m.energy_val]);
+    }
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u16s(&mut self, values: &[u16]) {
+        for v in values {
+            self.0.extend(v.to_le_bytes());
+        }
+    }
+
+    fn i16s(&mut self, values: &[i16]) {
+        for v in values {
+            self.0.extend(v.to_le_bytes());
+        }
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend(value.to_le_bytes());
+    }
+
+    fn process_mod(&mut self, m: &ProcessMod) {
+        self.u32(m.hash.map_or(0, NonZeroU32::get));
+        self.u32(m.mod_tag.map_or(0, NonZeroU32::get));
+        self.0.extend([m.energy_type as u8, m.energy_val]);
+    }
+}
+
+impl ProcessInput {
+    /// Reads packed input, see the module docs for the format.
+    pub fn from_packed(bytes: &[u8]) -> Result<Self, PackedError> {
+        let mut r = Reader { bytes, offset: 0 };
+        if r.take::<4>().ok() != Some(PACKED_MAGIC) {
+            return Err(PackedError::BadMagic);
+        }
+        let version = r.u16()?;
+        if version != PACKED_VERSION {
+            return Err(PackedError::UnsupportedVersion(version.into()));
+        }
+
+        let any_exotic = r.bool()?;
+        let auto_mods = r.byte_as(|b| (b as usize <= NUM_ITEM_BUCKETS).then_some(b))?;
+        let ruleset = r.ruleset()?;
+        let required_exotic_perk_id = r.u32()?;
+        let base_stats = SignedStats(r.i16s()?);
+        let bounds = ProcessTierBounds {
+            lower_bounds: r.u16s()?,
+            upper_bounds: r.u16s()?,
+        };
+        let stat_caps = r.u16s()?;
+        let mut set_bonuses = [ProcessSetBonus::default(); NUM_SET_BONUSES];
+        for bonus in &mut set_bonuses {
+            bonus.set_id = r.u16()?;
+            bonus.count = r.u16()?;
+        }
+        let args = ProcessArgs {
+            base_stats,
+            bounds,
+            any_exotic,
+            auto_mods,
+            ruleset,
+            stat_caps,
+            set_bonuses,
+            required_exotic_perk_id,
+        };
+
+        let mut num_items = [0; NUM_ITEM_BUCKETS];
+        for n in &mut num_items {
+            *n = r.u16()?;
+        }
+        let num_auto_mods = r.u16()?;
+
+        let mut items: [Vec<ProcessItem>; NUM_ITEM_BUCKETS] = Default::default();
+        for (list, n) in items.iter_mut().zip(num_items) {
+            for _ in 0..n {
+                list.push(r.item()?);
+            }
+        }
+
+        let general_mods = r.mods()?;
+        let combat_mods = r.mods()?;
+        let activity_mods = r.mods()?;
+
+        let mut auto_mods = Vec::with_capacity(num_auto_mods as usize);
+        for _ in 0..num_auto_mods {
+            auto_mods.push(ProcessStatMod {
+                inner_mod: r.process_mod()?,
+                stats: Stats(r.u16s()?),
+            });
+        }
+
+        if r.offset != bytes.len() {
+            return Err(PackedError::TrailingBytes { offset: r.offset });
+        }
+
+        Ok(Self {
+            items,
+            general_mods,
+            combat_mods,
+            activity_mods,
+            auto_mods,
+            args,
+        })
+    }
+
+    /// Writes the input in the packed format. `from_packed` reads it back as it was.
+    pub fn to_packed(&self) -> Vec<u8> {
+        let mut w = Writer(Vec::new());
+        let args = &self.args;
+        w.0.extend(PACKED_MAGIC);
+        w.u16s(&[PACKED_VERSION]);
+        w.0.extend([args.any_exotic as u8, args.auto_mods, args.ruleset as u8]);
+        w.u32(args.required_exotic_perk_id);
+        w.i16s(&args.base_stats.0);
+        w.u16s(&args.bounds.lower_bounds);
+        w.u16s(&args.bounds.upper_bounds);
+        w.u16s(&args.stat_caps);
+        for bonus in &args.set_bonuses {
+            w.u16s(&[bonus.set_id, bonus.count]);
+        }
+        for list in &self.items {
+            w.u16s(&[list.len() as u16]);
+        }
+        w.u16s(&[self.auto_mods.len() as u16]);
+
+        for item in self.items.iter().flatten() {
+            w.u16s(&[item.id, item.power]);
+            w.0.extend([
+                item.energy_type as u8,
+                item.energy_val,
+                item.energy_cap,
+                item.exotic as u8,
+            ]);
+            w.u32(item.mod_tags);
+            w.u16s(&item.stats.0);
+            w.i16s(&item.bonus_stats.0);
+            w.0.extend([item.bonus_mode as u8, item.tuning_stats]);
+            w.u16s(&[item.set_id]);
+            w.u32(item.exotic_perk_id);
+        }
+
+        for m in [&self.general_mods, &self.combat_mods, &self.activity_mods]
+            .into_iter()
+            .flatten()
+        {
+            w.process_mod(m);
+        }
+
+        for m in &self.auto_mods {
+            w.process_mod(&m.inner_mod);
+            w.u16s(&m.stats.0);
+        }
+
+        w.0
+    }
+
+    /// Reads a dump, see the module docs for the format. Unlike packed input, dumps are
+    /// only readable by the ABI version that wrote them.
+    pub fn from_dump(bytes: &[u8]) -> Result<Self, PackedError> {
+        let mut r = Reader { bytes, offset: 0 };
+        if r.take::<4>().ok() != Some(DUMP_MAGIC) {
+            return Err(PackedError::BadMagic);
+        }
+        let version = r.u32()?;
+        if version != ABI_VERSION {
+            return Err(PackedError::UnsupportedVersion(version));
+        }
+
+        let mut num_items = [0; NUM_ITEM_BUCKETS];
+        for n in &mut num_items {
+            *n = r.u16()?;
+        }
+        let num_auto_mods = r.u32()?;
+
+        let args = r.raw_args()?;
+        let mut items: [Vec<ProcessItem>; NUM_ITEM_BUCKETS] = Default::default();
+        for (list, n) in items.iter_mut().zip(num_items) {
+            for _ in 0..n {
+                list.push(r.raw_item()?);
+            }
+        }
+        let general_mods = r.raw_mods()?;
+        let combat_mods = r.raw_mods()?;
+        let activity_mods = r.raw_mods()?;
+        let auto_mods = (0..num_auto_mods)
+            .map(|_| r.raw_stat_mod())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if r.offset != bytes.len() {
+            return Err(PackedError::TrailingBytes { offset: r.offset });
+        }
+
+        Ok(Self {
+            items,
+            general_mods,
+            combat_mods,
+            activity_mods,
+            auto_mods,
+            args,
+        })
+    }
+
+    /// Writes the input as a dump, i.e. the way an FFI host would have laid it out in memory.
+    pub fn to_dump(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend(DUMP_MAGIC);
+        out.extend(ABI_VERSION.to_le_bytes());
+        for n in self.num_items() {
+            out.extend(n.to_le_bytes());
+        }
+        out.extend((self.auto_mods.len() as u32).to_le_bytes());
+
+        let args = &self.args;
+        RawWriter::append::<ProcessArgs>(&mut out, |w| {
+            w.i16s(offset_of!(ProcessArgs, base_stats), &args.base_stats.0);
+            let bounds = offset_of!(ProcessArgs, bounds);
+            w.u16s(
+                bounds + offset_of!(ProcessTierBounds, lower_bounds),
+                &args.bounds.lower_bounds,
+            );
+            w.u16s(
+                bounds + offset_of!(ProcessTierBounds, upper_bounds),
+                &args.bounds.upper_bounds,
+            );
+            w.bytes(
+                offset_of!(ProcessArgs, any_exotic),
+                &[args.any_exotic as u8],
+            );
+            w.bytes(offset_of!(ProcessArgs, auto_mods), &[args.auto_mods]);
+            w.bytes(offset_of!(ProcessArgs, ruleset), &[args.ruleset as u8]);
+            w.u16s(offset_of!(ProcessArgs, stat_caps), &args.stat_caps);
+            for (idx, bonus) in args.set_bonuses.iter().enumerate() {
+                let offset =
+                    offset_of!(ProcessArgs, set_bonuses) + idx * size_of::<ProcessSetBonus>();
+                w.u16s(
+                    offset + offset_of!(ProcessSetBonus, set_id),
+                    &[bonus.set_id],
+                );
+                w.u16s(offset + offset_of!(ProcessSetBonus, count), &[bonus.count]);
+            }
+            w.u32(
+                offset_of!(ProcessArgs, required_exotic_perk_id),
+                args.required_exotic_perk_id,
+            );
+        });
+
+        for item in self.items.iter().flatten() {
+            RawWriter::append::<ProcessItem>(&mut out, |w| {
+                w.u16s(offset_of!(ProcessItem, id), &[item.id]);
+                w.u16s(offset_of!(ProcessItem, power), &[item.power]);
+                w.bytes(
+                    offset_of!(ProcessItem, energy_type),
+                    &[item.energy_type as u8],
+                );
+                w.bytes(offset_of!(ProcessItem, energy_val), &[item.energy_val]);
+                w.bytes(offset_of!(ProcessItem, energy_cap), &[item.energy_cap]);
+                w.bytes(offset_of!(ProcessItem, exotic), &[item.exotic as u8]);
+                w.u32(offset_of!(ProcessItem, mod_tags), item.mod_tags);
+                w.u16s(offset_of!(ProcessItem, stats), &item.stats.0);
+                w.i16s(offset_of!(ProcessItem, bonus_stats), &item.bonus_stats.0);
+                w.bytes(
+                    offset_of!(ProcessItem, bonus_mode),
+                    &[item.bonus_mode as u8],
+                );
+                w.bytes(offset_of!(ProcessItem, tuning_stats), &[item.tuning_stats]);
+                w.u16s(offset_of!(ProcessItem, set_id), &[item.set_id]);
+                w.u32(offset_of!(ProcessItem, exotic_perk_id), item.exotic_perk_id);
+            });
+        }
+
+        for m in [&self.general_mods, &self.combat_mods, &self.activity_mods]
+            .into_iter()
+            .flatten()
+        {
+            RawWriter::append::<ProcessMod>(&mut out, |w| w.process_mod(0, m));
+        }
+
+        for m in &self.auto_mods {
+            RawWriter::append::<ProcessStatMod>(&mut out, |w| {
+                w.process_mod(offset_of!(ProcessStatMod, inner_mod), &m.inner_mod);
+                w.u16s(offset_of!(ProcessStatMod, stats), &m.stats.0);
+            });
+        }
+
+        out
+    }
+
+    /// The number of items per bucket.
+    pub fn num_items(&self) -> [u16; NUM_ITEM_BUCKETS] {
+        self.items.each_ref().map(|l| l.len() as u16)
+    }
+
+    /// Prepares a `ProcessRun` of this input, for running in steps.
+    pub fn start(&self) -> Result<ProcessRun<'_>, ProcessError> {
+        ProcessRun::new(
+            self.items.each_ref().map(|l| l.as_slice()),
+            &self.general_mods,
+            &self.combat_mods,
+            &self.activity_mods,
+            &self.auto_mods,
+            &self.args,
+        )
+    }
+
+    /// Runs `try_dim_lo_process` on this input.
+    pub fn process(
+        &self,
+    ) -> Result<(ProcessStats, Vec<ProcessArmorSet>, ProcessMinMaxStats), ProcessError> {
+        try_dim_lo_process(
+            self.items.each_ref().map(|l| l.as_slice()),
+            &self.general_mods,
+            &self.combat_mods,
+            &self.activity_mods,
+            &self.auto_mods,
+            &self.args,
+        )
+    }
+}
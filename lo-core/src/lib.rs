@@ -1,6 +1,3 @@
-#![feature(array_methods)]
-#![feature(array_windows)]
-#![feature(map_first_last)]
 #![no_std]
 
 extern crate alloc;
@@ -8,17 +5,28 @@ extern crate alloc;
 use alloc::collections::BTreeMap;
 
 use alloc::vec::Vec;
+use error::ProcessError;
 use set_tracker::SetTracker;
-use stat_mod_set::SomeMods;
+use stat_mod_set::{ModsArray, SomeMods};
+use tuning::TuningBuffers;
 use types::{
     EnergyType, ProcessArgs, ProcessArmorSet, ProcessItem, ProcessMinMaxStats, ProcessMod,
-    ProcessStatMod, ProcessStats, Stats, NUM_ITEM_BUCKETS, NUM_STATS,
+    ProcessSetBonus, ProcessStatMod, ProcessStats, ProcessTuning, SignedStats, Stats,
+    NUM_ITEM_BUCKETS, NUM_SET_BONUSES, NUM_STATS,
 };
 
+pub mod abi;
+pub mod error;
+pub mod input;
 mod set_tracker;
 mod stat_mod_set;
+mod tuning;
 pub mod types;
 
+#[cfg(any(test, feature = "generate"))]
+pub mod generate;
+#[cfg(any(test, feature = "oracle"))]
+pub mod oracle;
 #[cfg(test)]
 mod tests;
 
@@ -28,11 +36,16 @@ struct ModAssignmentInvariants<'a> {
     combat_mod_cost: u8,
     activity_mod_cost: u8,
     mod_set: BTreeMap<Stats, SomeMods<'a>>,
-    lower: &'a [u8; NUM_STATS],
+    /// The lower bounds in stat points, regardless of ruleset.
+    lower: [u16; NUM_STATS],
 }
 
-#[inline(never)]
-#[allow(clippy::too_many_arguments)]
+/// Like `try_dim_lo_process`, for hosts that make sure their input is valid.
+///
+/// # Panics
+///
+/// If the input is invalid, i.e. whenever `try_dim_lo_process` returns an error
+/// (see `error::ProcessError`).
 pub fn dim_lo_process(
     items: [&[ProcessItem]; NUM_ITEM_BUCKETS],
     general_mods: &[ProcessMod; NUM_ITEM_BUCKETS],
@@ -41,146 +54,424 @@ pub fn dim_lo_process(
     optional_stat_mods: &[ProcessStatMod],
     args: &ProcessArgs,
 ) -> (ProcessStats, Vec<ProcessArmorSet>, ProcessMinMaxStats) {
-    let mut info = ProcessStats::default();
-    let mut max = [0u16; 6];
-    let mut min = [100u16; 6];
-
-    let empty_stat_mod = ProcessStatMod {
-        inner_mod: ProcessMod {
-            hash: None,
-            mod_tag: None,
-            energy_type: EnergyType::Any,
-            energy_val: 0,
-        },
-        stats: Stats([0; 6]),
-    };
+    try_dim_lo_process(
+        items,
+        general_mods,
+        combat_mods,
+        activity_mods,
+        optional_stat_mods,
+        args,
+    )
+    .unwrap_or_else(|e| panic!("invalid input: {e}"))
+}
+
+/// Finds the best armor sets. Checks the input first (see `error::validate_inputs`),
+/// since the algorithm itself relies on it being sane.
+pub fn try_dim_lo_process(
+    items: [&[ProcessItem]; NUM_ITEM_BUCKETS],
+    general_mods: &[ProcessMod; NUM_ITEM_BUCKETS],
+    combat_mods: &[ProcessMod; NUM_ITEM_BUCKETS],
+    activity_mods: &[ProcessMod; NUM_ITEM_BUCKETS],
+    optional_stat_mods: &[ProcessStatMod],
+    args: &ProcessArgs,
+) -> Result<(ProcessStats, Vec<ProcessArmorSet>, ProcessMinMaxStats), ProcessError> {
+    let mut run = ProcessRun::new(
+        items,
+        general_mods,
+        combat_mods,
+        activity_mods,
+        optional_stat_mods,
+        args,
+    )?;
+    while !run.step(u32::MAX) {}
+    Ok(run.into_results())
+}
 
-    let mod_assignment_invars = {
-        let activity_mod_perms = generate_permutations_of(activity_mods);
-        let combat_mod_perms = generate_permutations_of(combat_mods);
-        let activity_mod_cost = activity_mods.iter().map(|m| m.energy_val).sum();
-        let combat_mod_cost = combat_mods.iter().map(|m| m.energy_val).sum();
+/// The number of sets a run returns.
+pub const NUM_RESULT_SETS: usize = 200;
+
+static EMPTY_STAT_MOD: ProcessStatMod = ProcessStatMod {
+    inner_mod: ProcessMod {
+        hash: None,
+        mod_tag: None,
+        energy_type: EnergyType::Any,
+        energy_val: 0,
+    },
+    stats: Stats([0; 6]),
+};
 
-        let num_stat_mods = general_mods.iter().filter(|m| m.hash.is_some()).count();
+/// A run of the algorithm that can be done in steps, so that hosts without threads
+/// can show the best sets found so far, or stop early.
+pub struct ProcessRun<'a> {
+    items: [&'a [ProcessItem]; NUM_ITEM_BUCKETS],
+    /// A copy, so that hosts can keep their settings next to other things they change.
+    args: ProcessArgs,
+    invars: ModAssignmentInvariants<'a>,
+    set_tracker: SetTracker,
+    set_constraints: Vec<ProcessSetBonus>,
+    tuning_buffers: TuningBuffers,
+    any_exotic_class_items: bool,
+    info: ProcessStats,
+    min: [u16; NUM_STATS],
+    max: [u16; NUM_STATS],
+    /// The helmet, gauntlets, chest and leg armor to continue with, None when done.
+    cursor: Option<[usize; NUM_ITEM_BUCKETS - 1]>,
+}
 
-        // Generate all choices of stat mods along with the stats they provide,
-        // with our pre-picked stat mods included.
-        let mod_set = stat_mod_set::generate_mods_options(
-            &general_mods[0..num_stat_mods],
+impl<'a> ProcessRun<'a> {
+    /// Checks the input (see `error::validate_inputs`) and prepares a run.
+    pub fn new(
+        items: [&'a [ProcessItem]; NUM_ITEM_BUCKETS],
+        general_mods: &'a [ProcessMod; NUM_ITEM_BUCKETS],
+        combat_mods: &'a [ProcessMod; NUM_ITEM_BUCKETS],
+        activity_mods: &'a [ProcessMod; NUM_ITEM_BUCKETS],
+        optional_stat_mods: &'a [ProcessStatMod],
+        args: &ProcessArgs,
+    ) -> Result<Self, ProcessError> {
+        error::validate_inputs(
+            &items,
+            general_mods,
+            combat_mods,
+            activity_mods,
             optional_stat_mods,
-            &empty_stat_mod,
-            args.auto_mods,
-        );
-
-        ModAssignmentInvariants {
-            activity_mod_cost,
-            activity_mod_perms,
-            combat_mod_cost,
-            combat_mod_perms,
-            lower: &args.bounds.lower_bounds,
-            mod_set,
+            args,
+        )?;
+
+        let invars = {
+            let activity_mod_perms = generate_permutations_of(activity_mods);
+            let combat_mod_perms = generate_permutations_of(combat_mods);
+            let activity_mod_cost = activity_mods.iter().map(|m| m.energy_val).sum();
+            let combat_mod_cost = combat_mods.iter().map(|m| m.energy_val).sum();
+
+            let num_stat_mods = general_mods.iter().filter(|m| m.hash.is_some()).count();
+
+            // Generate all choices of stat mods along with the stats they provide,
+            // with our pre-picked stat mods included.
+            let mod_set = stat_mod_set::generate_mods_options(
+                &general_mods[0..num_stat_mods],
+                optional_stat_mods,
+                &EMPTY_STAT_MOD,
+                args.auto_mods,
+            );
+
+            ModAssignmentInvariants {
+                activity_mod_cost,
+                activity_mod_perms,
+                combat_mod_cost,
+                combat_mod_perms,
+                lower: args.bounds.lower_bounds.map(|b| b * args.points_per_unit()),
+                mod_set,
+            }
+        };
+
+        let set_constraints = args
+            .set_bonuses
+            .iter()
+            .filter(|b| b.set_id != 0)
+            .copied()
+            .collect::<Vec<_>>();
+
+        let mut run = Self {
+            items,
+            args: *args,
+            invars,
+            set_tracker: SetTracker::new(10_000),
+            set_constraints,
+            tuning_buffers: TuningBuffers::default(),
+            any_exotic_class_items: false,
+            info: ProcessStats::default(),
+            min: args.effective_stat_caps(),
+            max: [0; NUM_STATS],
+            cursor: items.iter().all(|l| !l.is_empty()).then_some([0; 4]),
+        };
+        run.any_exotic_class_items = items[4].iter().any(|i| i.exotic && run.exotic_allowed(i));
+        Ok(run)
+    }
+
+    /// Sets can hold one exotic, in any bucket including the class item.
+    /// If the user asked for a specific exotic perk combination, other exotics are out.
+    #[inline]
+    fn require_exotic(&self) -> bool {
+        self.args.any_exotic || self.args.required_exotic_perk_id != 0
+    }
+
+    #[inline]
+    fn exotic_allowed(&self, item: &ProcessItem) -> bool {
+        !item.exotic
+            || self.args.required_exotic_perk_id == 0
+            || item.exotic_perk_id == self.args.required_exotic_perk_id
+    }
+
+    /// Looks at roughly `budget` more combinations of items. This may go over budget by
+    /// up to one combination per class item, since class items are always done at once.
+    /// Returns whether the run is done.
+    pub fn step(&mut self, budget: u32) -> bool {
+        let mut processed = 0u32;
+        while let Some(cursor) = self.cursor {
+            if processed >= budget {
+                return false;
+            }
+            let (level, combinations) = self.process_prefix(cursor);
+            processed = processed.saturating_add(combinations);
+            self.cursor = self.advance(cursor, level);
+        }
+        true
+    }
+
+    /// Moves on to the next item in bucket `level`, or further up if that bucket is exhausted.
+    fn advance(
+        &self,
+        mut cursor: [usize; NUM_ITEM_BUCKETS - 1],
+        mut level: usize,
+    ) -> Option<[usize; NUM_ITEM_BUCKETS - 1]> {
+        loop {
+            cursor[level] += 1;
+            cursor[level + 1..].fill(0);
+            if cursor[level] < self.items[level].len() {
+                return Some(cursor);
+            }
+            if level == 0 {
+                return None;
+            }
+            level -= 1;
+        }
+    }
+
+    /// Processes the combinations starting with the helmet, gauntlets, chest and leg armor
+    /// in `cursor`. If a check already fails for the first pieces, this skips every
+    /// combination starting with them. Returns the bucket the cursor needs to advance
+    /// in and how many combinations were processed or skipped.
+    fn process_prefix(&mut self, cursor: [usize; NUM_ITEM_BUCKETS - 1]) -> (usize, u32) {
+        let items = self.items;
+        let [helm, gaunt, chest, leg] = [0, 1, 2, 3].map(|b| &items[b][cursor[b]]);
+        let check_sets = !self.set_constraints.is_empty();
+        let skip = |counter: &mut u32, bucket| {
+            let combinations = combinations_after(&items, bucket);
+            *counter = counter.saturating_add(combinations);
+            (bucket, combinations)
+        };
+
+        if !self.exotic_allowed(helm) {
+            return skip(&mut self.info.skipped_no_exotic, 0);
         }
-    };
 
-    let mut set_tracker = SetTracker::new(10_000);
+        if gaunt.exotic && helm.exotic {
+            return skip(&mut self.info.skipped_double_exotic, 1);
+        }
+
+        if !self.exotic_allowed(gaunt) {
+            return skip(&mut self.info.skipped_no_exotic, 1);
+        }
+
+        // If the remaining three pieces can't make up for the missing set pieces, bail early.
+        if check_sets && missing_set_pieces(&self.set_constraints, &[helm, gaunt]) > 3 {
+            return skip(&mut self.info.skipped_set_bonus, 1);
+        }
+
+        if chest.exotic && (gaunt.exotic || helm.exotic) {
+            return skip(&mut self.info.skipped_double_exotic, 2);
+        }
+
+        if !self.exotic_allowed(chest) {
+            return skip(&mut self.info.skipped_no_exotic, 2);
+        }
+
+        if check_sets && missing_set_pieces(&self.set_constraints, &[helm, gaunt, chest]) > 2 {
+            return skip(&mut self.info.skipped_set_bonus, 2);
+        }
+
+        if leg.exotic && (chest.exotic || gaunt.exotic || helm.exotic) {
+            return skip(&mut self.info.skipped_double_exotic, 3);
+        }
+
+        if !self.exotic_allowed(leg) {
+            return skip(&mut self.info.skipped_no_exotic, 3);
+        }
+
+        if check_sets && missing_set_pieces(&self.set_constraints, &[helm, gaunt, chest, leg]) > 1 {
+            return skip(&mut self.info.skipped_set_bonus, 3);
+        }
+
+        let has_exotic = helm.exotic || gaunt.exotic || chest.exotic || leg.exotic;
+        let require_exotic = self.require_exotic();
+
+        // Without an exotic so far, only an exotic class item could save this set.
+        if require_exotic && !has_exotic && !self.any_exotic_class_items {
+            return skip(&mut self.info.skipped_no_exotic, 3);
+        }
 
-    for helm in items[0] {
-        for gaunt in items[1] {
-            if gaunt.exotic && helm.exotic {
-                info.skipped_double_exotic +=
-                    (items[2].len() * items[3].len() * items[4].len()) as u32;
-                continue;
+        let args = self.args;
+        let caps = args.effective_stat_caps();
+        let points_per_unit = args.points_per_unit();
+
+        'classItemLoop: for class_item in items[4] {
+            if class_item.exotic && has_exotic {
+                self.info.skipped_double_exotic += 1;
+                continue 'classItemLoop;
             }
 
-            for chest in items[2] {
-                if chest.exotic && (gaunt.exotic || helm.exotic) {
-                    info.skipped_double_exotic += (items[3].len() * items[4].len()) as u32;
-                    continue;
+            if !self.exotic_allowed(class_item)
+                || (require_exotic && !has_exotic && !class_item.exotic)
+            {
+                self.info.skipped_no_exotic += 1;
+                continue 'classItemLoop;
+            }
+
+            let set = [helm, gaunt, chest, leg, class_item];
+            if check_sets && missing_set_pieces(&self.set_constraints, &set) > 0 {
+                self.info.skipped_set_bonus += 1;
+                continue 'classItemLoop;
+            }
+
+            let stats = set.iter().fold(args.base_stats, |stats, item| {
+                stats + item.effective_stats()
+            });
+
+            // First, check what effective stats we end up with and whether we actually want this in the
+            // sets tracker. Penalties may have pushed stats below 0, the clamping takes care of that.
+            // With the tier ruleset, a stat is worth its tier, otherwise it's worth its raw points.
+            let mut sorting_tiers = [0u16; NUM_STATS];
+            let mut sorting_total_tier = 0;
+
+            for i in 0..NUM_STATS {
+                let clamped = stats.0[i].clamp(0, caps[i] as i16) as u16;
+                self.max[i] = core::cmp::max(self.max[i], clamped);
+                self.min[i] = core::cmp::min(self.min[i], clamped);
+                sorting_tiers[i] = clamped / points_per_unit;
+                // If a stat has a maximum, we still show sets that have a higher tier,
+                // but we stop caring about the surplus. A user may specify that they
+                // want 5 mobility at most because Dragon's Shadow gives 5 bonus mobility
+                // after dodging, but hiding a really good T6 mobility set just because of
+                // that is wrong, we should just treat it as if it had T5 mobility.
+                // (Such conditional bonuses can also be passed as `BonusStatMode::Conditional`
+                // item bonus stats, which are excluded from `stats` here entirely.)
+                if args.bounds.upper_bounds[i] < sorting_tiers[i] {
+                    sorting_tiers[i] = args.bounds.upper_bounds[i];
                 }
+                sorting_total_tier += sorting_tiers[i];
+            }
+
+            if !self.set_tracker.could_insert(sorting_total_tier) {
+                self.info.skipped_low_tier += 1;
+                continue 'classItemLoop;
+            }
 
-                for leg in items[3] {
-                    if leg.exotic && (chest.exotic || gaunt.exotic || helm.exotic) {
-                        info.skipped_double_exotic += (items[4].len()) as u32;
-                        continue;
-                    }
-
-                    if args.any_exotic
-                        && !helm.exotic
-                        && !gaunt.exotic
-                        && !chest.exotic
-                        && !leg.exotic
-                    {
-                        info.skipped_no_exotic += 1;
-                        continue;
-                    }
-
-                    'classItemLoop: for class_item in items[4] {
-                        let set = [helm, gaunt, chest, leg, class_item];
-                        let stats = set
-                            .iter()
-                            .fold(args.base_stats, |stats, item| stats + item.stats);
-
-                        // First, check what effective stats we end up with and whether we actually want this in the
-                        // sets tracker.
-                        let mut sorting_tiers =
-                            stats.0.map(|s| s / 10).map(|s| s.clamp(0, 10) as u8);
-                        let mut sorting_total_tier = 0;
-
-                        for i in 0..NUM_STATS {
-                            max[i] = core::cmp::max(max[i], stats.0[i].clamp(0, 100));
-                            min[i] = core::cmp::min(min[i], stats.0[i].clamp(0, 100));
-                            // If a stat has a maximum, we still show sets that have a higher tier,
-                            // but we stop caring about the surplus. A user may specify that they
-                            // want 5 mobility at most because Dragon's Shadow gives 5 bonus mobility
-                            // after dodging, but hiding a really good T6 mobility set just because of
-                            // that is wrong, we should just treat it as if it had T5 mobility.
-                            if args.bounds.upper_bounds[i] < sorting_tiers[i] {
-                                sorting_tiers[i] = args.bounds.upper_bounds[i];
-                            }
-                            sorting_total_tier += sorting_tiers[i];
-                        }
-
-                        if !set_tracker.could_insert(sorting_total_tier) {
-                            info.skipped_low_tier += 1;
-                            continue 'classItemLoop;
-                        }
-
-                        let result = can_take_mods_auto(set, &stats, &mod_assignment_invars);
-
-                        match result {
-                            StatModPickResults::Ok(pick) => {
-                                info.num_valid_sets += 1;
-
-                                set_tracker.insert(
-                                    sorting_tiers,
-                                    ProcessArmorSet {
-                                        stats: pick.resulting_stats,
-                                        items: set.map(|i| i.id),
-                                        total_tier: sorting_total_tier,
-                                        power: set.map(|i| i.power).iter().sum::<u16>() / 5,
-                                        extra_stat_mods: pick.pick.map(|m| m.hash),
-                                    },
-                                );
-                            }
-                            StatModPickResults::AutoModsDidntFit
-                            | StatModPickResults::ModsDidntFit => {
-                                info.skipped_mods_unfit += 1;
-                                continue;
-                            }
-                            StatModPickResults::LowStats => {
-                                info.skipped_stat_range += 1;
-                                continue;
-                            }
-                        }
-                    }
+            let result = can_take_mods_auto(set, &stats, &self.invars, &mut self.tuning_buffers);
+
+            match result {
+                StatModPickResults::Ok(pick) => {
+                    self.info.num_valid_sets += 1;
+
+                    // Always-active bonuses were part of the stats we checked bounds against,
+                    // conditional ones are only tacked on for display.
+                    let (always, conditional) = set.iter().fold(
+                        (SignedStats::default(), SignedStats::default()),
+                        |(always, conditional), item| {
+                            (
+                                always + item.always_stats(),
+                                conditional + item.conditional_stats(),
+                            )
+                        },
+                    );
+
+                    self.set_tracker.insert(
+                        sorting_tiers,
+                        ProcessArmorSet {
+                            stats: (pick.resulting_stats - always).saturate(),
+                            items: set.map(|i| i.id),
+                            total_tier: sorting_total_tier,
+                            power: set.map(|i| i.power).iter().sum::<u16>() / 5,
+                            extra_stat_mods: pick.pick.map(|m| m.hash),
+                            stats_with_bonus: (pick.resulting_stats + conditional).saturate(),
+                            set_bonuses: active_set_bonuses(&set),
+                            tunings: pick.tunings,
+                        },
+                    );
+                }
+                StatModPickResults::AutoModsDidntFit | StatModPickResults::ModsDidntFit => {
+                    self.info.skipped_mods_unfit += 1;
+                    continue;
+                }
+                StatModPickResults::LowStats => {
+                    self.info.skipped_stat_range += 1;
+                    continue;
                 }
             }
         }
+
+        (3, items[4].len() as u32)
+    }
+
+    /// The best sets found so far, best first.
+    pub fn best_sets(&self) -> impl Iterator<Item = &ProcessArmorSet> {
+        self.set_tracker.best_sets()
+    }
+
+    /// The statistics of the run so far.
+    pub fn info(&self) -> &ProcessStats {
+        &self.info
+    }
+
+    /// The min and max stats of the sets looked at so far.
+    pub fn min_max(&self) -> ProcessMinMaxStats {
+        ProcessMinMaxStats {
+            min: self.min,
+            max: self.max,
+        }
+    }
+
+    /// Ends the run, whether it's done or not, and returns the results so far.
+    pub fn into_results(self) -> (ProcessStats, Vec<ProcessArmorSet>, ProcessMinMaxStats) {
+        let min_max = self.min_max();
+        let sets = Vec::from_iter(self.set_tracker.sets_by_best().take(NUM_RESULT_SETS));
+        (self.info, sets, min_max)
     }
+}
+
+/// How many combinations of items are left once the items
+/// up to and including `bucket` are fixed. Saturates like the counters it's added to.
+#[inline]
+fn combinations_after(items: &[&[ProcessItem]; NUM_ITEM_BUCKETS], bucket: usize) -> u32 {
+    items[bucket + 1..]
+        .iter()
+        .fold(1, |acc: u32, l| acc.saturating_mul(l.len() as u32))
+}
+
+/// How many more pieces the (partial) armor set `items` needs to
+/// fulfill all set bonus constraints.
+#[inline]
+fn missing_set_pieces(constraints: &[ProcessSetBonus], items: &[&ProcessItem]) -> u16 {
+    constraints
+        .iter()
+        .map(|c| {
+            let have = items.iter().filter(|i| i.set_id == c.set_id).count() as u16;
+            c.count.saturating_sub(have)
+        })
+        .sum()
+}
 
-    let sets = Vec::from_iter(set_tracker.sets_by_best().take(200));
-    let min_max = ProcessMinMaxStats { min, max };
-    (info, sets, min_max)
+/// The armor sets with at least two pieces in `items`, most pieces first.
+fn active_set_bonuses(
+    items: &[&ProcessItem; NUM_ITEM_BUCKETS],
+) -> [ProcessSetBonus; NUM_SET_BONUSES] {
+    let mut bonuses = [ProcessSetBonus::default(); NUM_SET_BONUSES];
+    let mut num_bonuses = 0;
+    for (idx, item) in items.iter().enumerate() {
+        // Only count each set once, at its first piece.
+        if item.set_id == 0 || items[..idx].iter().any(|i| i.set_id == item.set_id) {
+            continue;
+        }
+        let count = items.iter().filter(|i| i.set_id == item.set_id).count() as u16;
+        if count >= 2 {
+            bonuses[num_bonuses] = ProcessSetBonus {
+                set_id: item.set_id,
+                count,
+            };
+            num_bonuses += 1;
+        }
+    }
+    bonuses.sort_by_key(|b| core::cmp::Reverse(b.count));
+    bonuses
 }
 
 #[inline]
@@ -200,7 +491,8 @@ fn energy_spec(md: &ProcessMod) -> (u8, EnergyType) {
 #[derive(Clone, Copy)]
 struct StatModPick<'a> {
     pick: &'a [&'a ProcessMod; NUM_ITEM_BUCKETS],
-    resulting_stats: Stats,
+    resulting_stats: SignedStats,
+    tunings: [ProcessTuning; NUM_ITEM_BUCKETS],
 }
 
 enum StatModPickResults<'a> {
@@ -213,8 +505,9 @@ enum StatModPickResults<'a> {
 #[inline(never)]
 fn can_take_mods_auto<'a>(
     items: [&ProcessItem; NUM_ITEM_BUCKETS],
-    base_stats: &Stats,
+    base_stats: &SignedStats,
     invars: &'a ModAssignmentInvariants<'a>,
+    tuning_buffers: &mut TuningBuffers,
 ) -> StatModPickResults<'a> {
     let [any_items, specific_items @ ..] = get_energy_counts(&items);
     let [_, specific_combat @ ..] = get_energy_counts(&invars.combat_mod_perms[0]);
@@ -244,20 +537,7 @@ fn can_take_mods_auto<'a>(
     }
 
     // Check out which stats are missing to get to the lower bounds.
-    // This always creates non-negative multiples of 5, which are
-    // exactly the stats the auto stat mods map is keyed by.
-    let mut contribution = Stats([0u16; NUM_STATS]);
-    for i in 0..NUM_STATS {
-        contribution.0[i] = (invars.lower[i] as u16 * 10).saturating_sub(base_stats.0[i]);
-        contribution.0[i] += (5 - contribution.0[i] % 5) % 5;
-    }
-
-    // Retrieve the stat mod picks that could help us get to the minimum stats we need.
-    // NB this includes our locked general mods
-    let orig_options = match invars.mod_set.get(&contribution) {
-        Some(mods) => mods.mods.as_slice(),
-        None => return StatModPickResults::LowStats,
-    };
+    let contribution = missing_stats(base_stats, &invars.lower);
 
     // (Unlikely, maybe not even worth including here)
     // Early exit if we don't have enough remaining energy for
@@ -267,13 +547,102 @@ fn can_take_mods_auto<'a>(
         .map(|i| i.energy_cap - i.energy_val)
         .sum::<u8>() as i8;
     total_remaining_energy -= (invars.activity_mod_cost + invars.combat_mod_cost) as i8;
-    if !orig_options
-        .iter()
-        .any(|o| (o.sum_cost as i8) <= total_remaining_energy)
-    {
-        return StatModPickResults::AutoModsDidntFit;
+    let affordable = |options: &[ModsArray]| {
+        options
+            .iter()
+            .any(|o| (o.sum_cost as i8) <= total_remaining_energy)
+    };
+    let find_pick = |options: &'a [ModsArray<'a>], leftover_energy: &[u8; NUM_ITEM_BUCKETS]| {
+        options.iter().find(|&res| {
+            res.sum_cost as i8 <= total_remaining_energy && fits(leftover_energy, &res.costs)
+        })
+    };
+
+    // Retrieve the stat mod picks that could help us get to the minimum stats we need.
+    // NB this includes our locked general mods
+    let orig_options = invars.mod_set.get(&contribution).map(|m| m.mods.as_slice());
+    let mut any_options = orig_options.is_some();
+
+    if let Some(orig_options) = orig_options.filter(|o| affordable(o)) {
+        let result = for_each_mod_assignment(&items, invars, |leftover_energy| {
+            find_pick(orig_options, leftover_energy)
+        });
+        match result {
+            Ok(pick) => {
+                return StatModPickResults::Ok(StatModPick {
+                    pick: &pick.mods,
+                    resulting_stats: *base_stats + contribution,
+                    tunings: [ProcessTuning::default(); NUM_ITEM_BUCKETS],
+                })
+            }
+            // The bucket independent mods don't fit, no tuning can change that.
+            Err(false) => return StatModPickResults::ModsDidntFit,
+            Err(true) => {}
+        }
+    }
+
+    // Auto stat mods alone didn't cut it, try to tune our way to the minimum stats.
+    if contribution != Stats::default() && items.iter().any(|i| i.tuning_stats != 0) {
+        let candidates =
+            tuning::generate_tuning_candidates(&items, base_stats, &invars.lower, tuning_buffers);
+        candidates.retain(|c| match invars.mod_set.get(&c.contribution) {
+            Some(options) => {
+                any_options = true;
+                affordable(&options.mods)
+            }
+            None => false,
+        });
+
+        if !candidates.is_empty() {
+            let result = for_each_mod_assignment(&items, invars, |leftover_energy| {
+                candidates.iter().find_map(|candidate| {
+                    let options = &invars.mod_set.get(&candidate.contribution)?.mods;
+                    find_pick(options, leftover_energy).map(|pick| (*candidate, pick))
+                })
+            });
+            match result {
+                Ok((candidate, pick)) => {
+                    return StatModPickResults::Ok(StatModPick {
+                        pick: &pick.mods,
+                        resulting_stats: *base_stats + candidate.delta + candidate.contribution,
+                        tunings: candidate.tunings,
+                    })
+                }
+                Err(false) => return StatModPickResults::ModsDidntFit,
+                Err(true) => {}
+            }
+        }
     }
 
+    if any_options {
+        StatModPickResults::AutoModsDidntFit
+    } else {
+        StatModPickResults::LowStats
+    }
+}
+
+/// The stats a set with `stats` is missing to get to the `lower` bounds (in stat points).
+/// This always creates non-negative multiples of 5, which are
+/// exactly the stats the auto stat mods map is keyed by.
+/// Stats that penalties pushed below 0 need to be made up for in full,
+/// so don't saturate before subtracting.
+#[inline]
+fn missing_stats(stats: &SignedStats, lower: &[u16; NUM_STATS]) -> Stats {
+    Stats(core::array::from_fn(|i| {
+        let missing = (lower[i] as i16 - stats.0[i]).max(0) as u16;
+        missing + (5 - missing % 5) % 5
+    }))
+}
+
+/// Calls `try_fit` with the leftover energy of the items (sorted descending) for every
+/// assignment of activity and combat mods that fits wrt tag, element, energy, until
+/// `try_fit` returns something. If it never does, returns whether any assignment fit at all.
+#[inline]
+fn for_each_mod_assignment<R>(
+    items: &[&ProcessItem; NUM_ITEM_BUCKETS],
+    invars: &ModAssignmentInvariants,
+    mut try_fit: impl FnMut(&[u8; NUM_ITEM_BUCKETS]) -> Option<R>,
+) -> Result<R, bool> {
     let mut assigned_mods_at_least_once = false;
 
     'activityModLoop: for activity_perm in &invars.activity_mod_perms {
@@ -334,25 +703,13 @@ fn can_take_mods_auto<'a>(
 
             leftover_energy.sort_by_key(|&x| core::cmp::Reverse(x));
 
-            let pick = orig_options.iter().find(|&res| {
-                res.sum_cost as i8 <= total_remaining_energy && fits(&leftover_energy, &res.costs)
-            });
-
-            if let Some(pick) = pick {
-                let stats = *base_stats + contribution;
-                return StatModPickResults::Ok(StatModPick {
-                    pick: &pick.mods,
-                    resulting_stats: stats,
-                });
+            if let Some(result) = try_fit(&leftover_energy) {
+                return Ok(result);
             }
         }
     }
 
-    if assigned_mods_at_least_once {
-        StatModPickResults::AutoModsDidntFit
-    } else {
-        StatModPickResults::ModsDidntFit
-    }
+    Err(assigned_mods_at_least_once)
 }
 
 fn fits(rem: &[u8; NUM_ITEM_BUCKETS], assign: &[u8; NUM_ITEM_BUCKETS]) -> bool {
@@ -0,0 +1,301 @@
+//! A deliberately naive reference for auto stat mods and tuning, to test
+//! `can_take_mods_auto` against. It tries every multiset of auto stat mods, every tuning
+//! and every placement of every mod on every item, and accepts a set if any of them
+//! fits and reaches the lower bounds. Only usable for tiny inputs.
+//!
+//! Unlike LO, the oracle accepts picks that overshoot the lower bounds. LO only looks for
+//! picks that hit the (rounded up) missing stats exactly, which finds a pick whenever
+//! there is one as long as every stat has a +5 mod that costs no more than its +10 mod,
+//! like in the game. Inputs for the oracle need to stick to that.
+//!
+//! Built for tests and with the `oracle` feature, for lo-offline.
+
+use alloc::vec::Vec;
+
+use crate::{
+    energies_match,
+    input::ProcessInput,
+    tuning::TUNING_POINTS,
+    types::{
+        ProcessItem, ProcessMod, ProcessStatMod, ProcessTuning, SignedStats, NUM_ITEM_BUCKETS,
+        NUM_STATS,
+    },
+};
+
+/// Every item has one slot per kind of mod.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    Stat = 0,
+    Combat = 1,
+    Activity = 2,
+}
+
+/// Whether `mods` can all be placed, each into its kind of slot on a different item,
+/// with enough energy, matching elements and mod tags.
+pub fn can_place(items: &[&ProcessItem; NUM_ITEM_BUCKETS], mods: &[(Slot, &ProcessMod)]) -> bool {
+    fn place<'a>(
+        items: &[&ProcessItem; NUM_ITEM_BUCKETS],
+        mods: &[(Slot, &'a ProcessMod)],
+        slots: &mut [[Option<&'a ProcessMod>; 3]; NUM_ITEM_BUCKETS],
+    ) -> bool {
+        let Some(((slot, m), rest)) = mods.split_first() else {
+            return true;
+        };
+        for (idx, item) in items.iter().enumerate() {
+            let placed = slots[idx];
+            let used = placed.iter().flatten().map(|m| m.energy_val).sum::<u8>();
+            let fits = placed[*slot as usize].is_none()
+                && item.energy_val + used + m.energy_val <= item.energy_cap
+                && energies_match(item.energy_type, m.energy_type)
+                && placed
+                    .iter()
+                    .flatten()
+                    .all(|other| energies_match(other.energy_type, m.energy_type))
+                && m.mod_tag.is_none_or(|tag| item.mod_tags & tag.get() != 0);
+            if fits {
+                slots[idx][*slot as usize] = Some(m);
+                if place(items, rest, slots) {
+                    return true;
+                }
+                slots[idx][*slot as usize] = None;
+            }
+        }
+        false
+    }
+
+    place(items, mods, &mut [[None; 3]; NUM_ITEM_BUCKETS])
+}
+
+/// Every combination of tunings, including none at all. A tuning can only take points
+/// from a stat the item has at least `TUNING_POINTS` in.
+pub fn tuning_options(
+    items: &[&ProcessItem; NUM_ITEM_BUCKETS],
+) -> Vec<[ProcessTuning; NUM_ITEM_BUCKETS]> {
+    let mut options = alloc::vec![[ProcessTuning::default(); NUM_ITEM_BUCKETS]];
+    for (idx, item) in items.iter().enumerate() {
+        let mut next = options.clone();
+        for plus in (0..NUM_STATS).filter(|&s| item.tuning_stats & (1 << s) != 0) {
+            for minus in (0..NUM_STATS).filter(|&s| s != plus) {
+                if item.stats.0[minus] < TUNING_POINTS as u16 {
+                    continue;
+                }
+                for option in &options {
+                    let mut option = *option;
+                    option[idx] = ProcessTuning {
+                        stat_plus: plus as u8,
+                        stat_minus: minus as u8,
+                    };
+                    next.push(option);
+                }
+            }
+        }
+        options = next;
+    }
+    options
+}
+
+/// The stats `tunings` move around.
+pub fn tuning_delta(tunings: &[ProcessTuning; NUM_ITEM_BUCKETS]) -> SignedStats {
+    let mut delta = SignedStats::default();
+    for t in tunings.iter().filter(|t| t.stat_plus != t.stat_minus) {
+        delta.0[t.stat_plus as usize] += TUNING_POINTS;
+        delta.0[t.stat_minus as usize] -= TUNING_POINTS;
+    }
+    delta
+}
+
+/// Calls `f` with every multiset of up to `max` of `mods`, as indices in ascending order.
+fn for_each_multiset(mods: usize, max: usize, f: &mut impl FnMut(&[usize]) -> bool) -> bool {
+    fn extend(
+        mods: usize,
+        max: usize,
+        picked: &mut Vec<usize>,
+        f: &mut impl FnMut(&[usize]) -> bool,
+    ) -> bool {
+        if f(picked) {
+            return true;
+        }
+        if picked.len() == max {
+            return false;
+        }
+        let start = picked.last().copied().unwrap_or(0);
+        for idx in start..mods {
+            picked.push(idx);
+            if extend(mods, max, picked, f) {
+                return true;
+            }
+            picked.pop();
+        }
+        false
+    }
+    extend(mods, max, &mut Vec::new(), f)
+}
+
+pub struct OracleInput<'a> {
+    pub items: [&'a ProcessItem; NUM_ITEM_BUCKETS],
+    /// The stats of the set, including base stats, before stat mods and tuning.
+    pub stats: SignedStats,
+    pub general_mods: &'a [ProcessMod; NUM_ITEM_BUCKETS],
+    pub combat_mods: &'a [ProcessMod; NUM_ITEM_BUCKETS],
+    pub activity_mods: &'a [ProcessMod; NUM_ITEM_BUCKETS],
+    pub auto_mods: &'a [ProcessStatMod],
+    /// Stat mods in total, like `ProcessArgs::auto_mods`.
+    pub num_stat_mods: u8,
+    /// In stat points.
+    pub lower: [u16; NUM_STATS],
+}
+
+impl OracleInput<'_> {
+    /// The locked mods, in their slots.
+    fn locked_mods(&self) -> Vec<(Slot, &ProcessMod)> {
+        let lists = [
+            (Slot::Stat, self.general_mods),
+            (Slot::Combat, self.combat_mods),
+            (Slot::Activity, self.activity_mods),
+        ];
+        lists
+            .into_iter()
+            .flat_map(|(slot, mods)| {
+                mods.iter()
+                    .filter(|m| m.hash.is_some())
+                    .map(move |m| (slot, m))
+            })
+            .collect()
+    }
+
+    /// How many auto stat mods may be added to the locked general mods.
+    fn max_auto_mods(&self) -> usize {
+        let num_general = self
+            .general_mods
+            .iter()
+            .filter(|m| m.hash.is_some())
+            .count();
+        // Not clamped to the slots, placing the mods rejects picks that don't fit
+        (self.num_stat_mods as usize).saturating_sub(num_general)
+    }
+
+    /// Whether some pick of auto stat mods and tunings reaches the lower bounds
+    /// with all mods placed.
+    pub fn accepts(&self) -> bool {
+        let locked = self.locked_mods();
+        let max_auto = self.max_auto_mods();
+
+        tuning_options(&self.items).iter().any(|tunings| {
+            let tuned = self.stats + tuning_delta(tunings);
+            for_each_multiset(self.auto_mods.len(), max_auto, &mut |picked| {
+                let stats = picked
+                    .iter()
+                    .fold(tuned, |acc, &idx| acc + self.auto_mods[idx].stats);
+                if (0..NUM_STATS).any(|i| stats.0[i] < self.lower[i] as i16) {
+                    return false;
+                }
+                let mut mods = locked.clone();
+                mods.extend(
+                    picked
+                        .iter()
+                        .map(|&idx| (Slot::Stat, &self.auto_mods[idx].inner_mod)),
+                );
+                can_place(&self.items, &mods)
+            })
+        })
+    }
+
+    /// Whether LO's pick is one the oracle would take: `stat_mods` are the hashes of the
+    /// picked auto mods (empty slots are `None`), and `resulting_stats` must be what
+    /// they and `tunings` give.
+    pub fn check_pick(
+        &self,
+        stat_mods: &[Option<core::num::NonZeroU32>; NUM_ITEM_BUCKETS],
+        tunings: &[ProcessTuning; NUM_ITEM_BUCKETS],
+        resulting_stats: &SignedStats,
+    ) -> Result<(), &'static str> {
+        for (item, t) in self.items.iter().zip(tunings) {
+            if t.stat_plus != t.stat_minus && item.tuning_stats & (1 << t.stat_plus) == 0 {
+                return Err("tuned into a stat the item can't be tuned into");
+            }
+        }
+        let mut mods = self.locked_mods();
+        let mut stats = self.stats + tuning_delta(tunings);
+        for hash in stat_mods.iter().flatten() {
+            let Some(m) = self
+                .auto_mods
+                .iter()
+                .find(|m| m.inner_mod.hash == Some(*hash))
+            else {
+                return Err("picked an unknown stat mod");
+            };
+            stats = stats + m.stats;
+            mods.push((Slot::Stat, &m.inner_mod));
+        }
+        if stat_mods.iter().flatten().count() > self.max_auto_mods() {
+            return Err("picked too many stat mods");
+        }
+        if stats != *resulting_stats {
+            return Err("resulting stats don't match the pick");
+        }
+        if (0..NUM_STATS).any(|i| stats.0[i] < self.lower[i] as i16) {
+            return Err("pick doesn't reach the lower bounds");
+        }
+        if !can_place(&self.items, &mods) {
+            return Err("pick doesn't fit");
+        }
+        Ok(())
+    }
+}
+
+/// Whether the oracle takes `set`: at most one exotic, exactly one if the user requires
+/// one, with the required exotic perks, the required set bonuses and mods that fit.
+pub fn accepts_set(input: &ProcessInput, set: [&ProcessItem; NUM_ITEM_BUCKETS]) -> bool {
+    let args = &input.args;
+    let exotics = set.iter().filter(|i| i.exotic).count();
+    let require_exotic = args.any_exotic || args.required_exotic_perk_id != 0;
+    if exotics > 1 || (require_exotic && exotics == 0) {
+        return false;
+    }
+    if args.required_exotic_perk_id != 0
+        && set
+            .iter()
+            .any(|i| i.exotic && i.exotic_perk_id != args.required_exotic_perk_id)
+    {
+        return false;
+    }
+    for bonus in args.set_bonuses.iter().filter(|b| b.set_id != 0) {
+        if (set.iter().filter(|i| i.set_id == bonus.set_id).count() as u16) < bonus.count {
+            return false;
+        }
+    }
+
+    let points_per_unit = args.points_per_unit();
+    OracleInput {
+        items: set,
+        stats: set
+            .iter()
+            .fold(args.base_stats, |acc, i| acc + i.effective_stats()),
+        general_mods: &input.general_mods,
+        combat_mods: &input.combat_mods,
+        activity_mods: &input.activity_mods,
+        auto_mods: &input.auto_mods,
+        num_stat_mods: args.auto_mods,
+        lower: args.bounds.lower_bounds.map(|b| b * points_per_unit),
+    }
+    .accepts()
+}
+
+/// Calls `f` with every combination of items.
+pub fn for_each_set(
+    items: &[Vec<ProcessItem>; NUM_ITEM_BUCKETS],
+    mut f: impl FnMut([&ProcessItem; NUM_ITEM_BUCKETS]),
+) {
+    let [helmets, gauntlets, chests, legs, class_items] = items;
+    for helmet in helmets {
+        for gauntlet in gauntlets {
+            for chest in chests {
+                for leg in legs {
+                    for class_item in class_items {
+                        f([helmet, gauntlet, chest, leg, class_item]);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,16 +1,16 @@
-use alloc::{vec::Vec, collections::BTreeMap};
+use alloc::{collections::BTreeMap, vec::Vec};
 
 use crate::types::{ProcessArmorSet, NUM_STATS};
 
-/// Tiers in stat order for purposes of sorting only!
+/// Tiers (or stat points, depending on the ruleset) in stat order for purposes of sorting only!
 /// We don't count tiers beyond what the user set as max (e.g. if the
 /// user says max mobility 5 and we have 7, we treat this as if it had mobility 5),
 /// and we also don't count auto stat mods (they're not interesting because they
 /// only ever buff bad sets that need stat mods in the first place)
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
 struct SetSortingKey {
-    sorting_total_tier: u8,
-    sorting_tiers: [u8; NUM_STATS],
+    sorting_total_tier: u16,
+    sorting_tiers: [u16; NUM_STATS],
 }
 
 pub struct SetTracker {
@@ -26,7 +26,7 @@ impl SetTracker {
         }
     }
 
-    pub fn could_insert(&self, tier: u8) -> bool {
+    pub fn could_insert(&self, tier: u16) -> bool {
         match self.tracker.first_key_value() {
             Some((k, _)) => k.sorting_total_tier <= tier,
             None => true,
@@ -34,13 +34,13 @@ impl SetTracker {
     }
 
     /// Insert a set into the tracker with the given
-    pub fn insert(&mut self, sorting_tiers: [u8; NUM_STATS], set: ProcessArmorSet) {
+    pub fn insert(&mut self, sorting_tiers: [u16; NUM_STATS], set: ProcessArmorSet) {
         let key = SetSortingKey {
             sorting_total_tier: set.total_tier,
             sorting_tiers,
         };
 
-        self.tracker.entry(key).or_insert_with(Vec::new).push(set);
+        self.tracker.entry(key).or_default().push(set);
         self.trim_worst();
     }
 
@@ -56,7 +56,11 @@ impl SetTracker {
         }
     }
 
+    pub fn best_sets(&self) -> impl Iterator<Item = &ProcessArmorSet> {
+        self.tracker.iter().rev().flat_map(|(_, val)| val)
+    }
+
     pub fn sets_by_best(self) -> impl Iterator<Item = ProcessArmorSet> {
-        self.tracker.into_iter().rev().flat_map(|(_ , val)| val)
+        self.tracker.into_iter().rev().flat_map(|(_, val)| val)
     }
 }
@@ -171,7 +171,6 @@ pub fn generate_mods_options<'a>(
                 cost_list[num_existing_mods + 1] = (None, 0);
             }
         }
-        cost_list[num_existing_mods] = (None, 0);
     }
 
     // Destructure the map and create it with the picks sorted ascending by the number of mods.
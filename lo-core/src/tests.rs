@@ -1,17 +1,31 @@
-use core::num::NonZeroU32;
+use alloc::vec::Vec;
+use core::{
+    mem::{offset_of, size_of},
+    num::NonZeroU32,
+};
 
 use crate::{
+    abi::{layout_table, layout_table_len, ABI_VERSION, FFI_LAYOUTS},
     dim_lo_process,
+    error::{ModList, ProcessError, MAX_STAT},
+    generate::{generate, GenerateOptions, Rng},
+    input::{PackedError, ProcessInput, DUMP_HEADER_LEN},
+    oracle::{accepts_set, can_place, for_each_set, tuning_options, OracleInput, Slot},
+    try_dim_lo_process,
     types::{
-        EnergyType, ProcessArgs, ProcessItem, ProcessMod, ProcessStatMod, Stats, NUM_ITEM_BUCKETS,
-        NUM_STATS,
+        BonusStatMode, EnergyType, ProcessArgs, ProcessItem, ProcessMod, ProcessSetBonus,
+        ProcessStatMod, SignedStats, StatRuleset, Stats, NUM_ITEM_BUCKETS, NUM_STATS,
     },
+    ProcessRun,
 };
 
+// Regression tests written by `lo-offline regression`, one module per input
+mod small_inventory;
+
 #[test]
 fn check_auto_assignment() {
     let no_mods = [NO_MOD; 5];
-    let base_stats = Stats([0; NUM_STATS]);
+    let base_stats = SignedStats([0; NUM_STATS]);
     let items: [&[_]; NUM_ITEM_BUCKETS] = [
         &[ProcessItem {
             id: 1,
@@ -22,6 +36,7 @@ fn check_auto_assignment() {
             exotic: false,
             mod_tags: 0,
             stats: Stats([11, 4, 23, 8, 24, 8]),
+            ..Default::default()
         }],
         &[ProcessItem {
             id: 2,
@@ -32,6 +47,7 @@ fn check_auto_assignment() {
             exotic: false,
             mod_tags: 0,
             stats: Stats([10, 4, 24, 8, 14, 18]),
+            ..Default::default()
         }],
         &[ProcessItem {
             id: 3,
@@ -42,6 +58,7 @@ fn check_auto_assignment() {
             exotic: true,
             mod_tags: 0,
             stats: Stats([14, 9, 18, 18, 14, 8]),
+            ..Default::default()
         }],
         &[ProcessItem {
             id: 4,
@@ -52,6 +69,7 @@ fn check_auto_assignment() {
             exotic: false,
             mod_tags: 0,
             stats: Stats([4, 11, 24, 18, 18, 4]),
+            ..Default::default()
         }],
         &[ProcessItem {
             id: 5,
@@ -62,6 +80,7 @@ fn check_auto_assignment() {
             exotic: false,
             mod_tags: 0,
             stats: Stats([2, 2, 2, 2, 2, 2]),
+            ..Default::default()
         }],
     ];
     let args = ProcessArgs {
@@ -72,12 +91,757 @@ fn check_auto_assignment() {
         },
         any_exotic: true,
         auto_mods: 5,
+        ..Default::default()
     };
     let result = dim_lo_process(items, &no_mods, &no_mods, &no_mods, &SAMPLE_MODS, &args);
 
     assert!(!result.1.is_empty())
 }
 
+#[test]
+fn check_bonus_stats() {
+    let no_mods = [NO_MOD; 5];
+    let plain = |id| ProcessItem {
+        id,
+        energy_cap: 10,
+        stats: Stats([10; NUM_STATS]),
+        ..Default::default()
+    };
+    let helm = ProcessItem {
+        bonus_stats: SignedStats([10, 0, 0, 0, 0, 0]),
+        bonus_mode: BonusStatMode::Always,
+        ..plain(1)
+    };
+    let chest = ProcessItem {
+        bonus_stats: SignedStats([0, 0, 10, 0, 0, 0]),
+        bonus_mode: BonusStatMode::Conditional,
+        ..plain(3)
+    };
+    let items: [&[_]; NUM_ITEM_BUCKETS] =
+        [&[helm], &[plain(2)], &[chest], &[plain(4)], &[plain(5)]];
+    let mut args = ProcessArgs {
+        bounds: crate::types::ProcessTierBounds {
+            lower_bounds: [6, 0, 0, 0, 0, 0],
+            upper_bounds: [10; NUM_STATS],
+        },
+        ..Default::default()
+    };
+
+    // The always-active mobility bonus counts toward the lower bound and tiers,
+    // the conditional recovery bonus is only reported.
+    let (_, sets, _) = dim_lo_process(items, &no_mods, &no_mods, &no_mods, &[], &args);
+    assert_eq!(sets.len(), 1);
+    assert_eq!(sets[0].total_tier, 31);
+    assert_eq!(sets[0].stats, Stats([50; NUM_STATS]));
+    assert_eq!(sets[0].stats_with_bonus, Stats([60, 50, 60, 50, 50, 50]));
+
+    args.bounds.lower_bounds = [0, 0, 6, 0, 0, 0];
+    let (info, sets, _) = dim_lo_process(items, &no_mods, &no_mods, &no_mods, &[], &args);
+    assert!(sets.is_empty());
+    assert_eq!(info.skipped_stat_range, 1);
+}
+
+#[test]
+fn check_stat_penalties() {
+    let no_mods = [NO_MOD; 5];
+    let plain = |id, mobility| ProcessItem {
+        id,
+        energy_cap: 10,
+        stats: Stats([mobility, 22, 22, 22, 22, 22]),
+        ..Default::default()
+    };
+    let items: [&[_]; NUM_ITEM_BUCKETS] = [
+        &[plain(1, 1)],
+        &[plain(2, 1)],
+        &[plain(3, 1)],
+        &[plain(4, 1)],
+        &[plain(5, 1)],
+    ];
+    // A fragment takes away 10 mobility, and the other stats go past 100
+    // before a penalty brings them back to exactly 100.
+    let args = ProcessArgs {
+        base_stats: SignedStats([-10, -10, -10, -10, -10, -10]),
+        bounds: crate::types::ProcessTierBounds {
+            lower_bounds: [1, 0, 0, 0, 0, 0],
+            upper_bounds: [10; NUM_STATS],
+        },
+        any_exotic: false,
+        auto_mods: 5,
+        ..Default::default()
+    };
+    let (_, sets, min_max) =
+        dim_lo_process(items, &no_mods, &no_mods, &no_mods, &SAMPLE_MODS, &args);
+
+    // Mobility sits at -5, so it takes 15 points of stat mods to reach T1.
+    assert_eq!(sets.len(), 1);
+    assert_eq!(sets[0].stats, Stats([10, 100, 100, 100, 100, 100]));
+    assert_eq!(sets[0].total_tier, 50);
+    assert_eq!(min_max.min, [0, 100, 100, 100, 100, 100]);
+}
+
+#[test]
+fn check_points_ruleset() {
+    let no_mods = [NO_MOD; 5];
+    let plain = |id, mobility| ProcessItem {
+        id,
+        energy_cap: 10,
+        stats: Stats([mobility, 10, 10, 10, 10, 10]),
+        ..Default::default()
+    };
+    let items: [&[_]; NUM_ITEM_BUCKETS] = [
+        &[plain(6, 28), plain(1, 30)],
+        &[plain(2, 30)],
+        &[plain(3, 30)],
+        &[plain(4, 30)],
+        &[plain(5, 30)],
+    ];
+    let mut args = ProcessArgs {
+        bounds: crate::types::ProcessTierBounds {
+            lower_bounds: [150, 0, 0, 0, 0, 0],
+            upper_bounds: [200; NUM_STATS],
+        },
+        ruleset: StatRuleset::Points,
+        stat_caps: [200; NUM_STATS],
+        auto_mods: 1,
+        ..Default::default()
+    };
+    let (_, sets, min_max) =
+        dim_lo_process(items, &no_mods, &no_mods, &no_mods, &SAMPLE_MODS, &args);
+
+    // Mobility goes past 100 and counts in points, and the 148 mobility set
+    // gets a stat mod to reach the 150 threshold, but ranks below the other set.
+    assert_eq!(sets.len(), 2);
+    assert_eq!(sets[0].items[0], 1);
+    assert_eq!(sets[0].total_tier, 150 + 5 * 50);
+    assert_eq!(sets[1].stats.0[0], 153);
+    assert_eq!(sets[1].total_tier, 148 + 5 * 50);
+    assert_eq!(min_max.min[0], 148);
+    assert_eq!(min_max.max[0], 150);
+
+    // Caps and upper bounds limit the points that count for sorting.
+    args.stat_caps = [140, 200, 200, 200, 200, 200];
+    args.bounds.lower_bounds = [0; NUM_STATS];
+    args.bounds.upper_bounds = [200, 40, 200, 200, 200, 200];
+    let (_, sets, min_max) =
+        dim_lo_process(items, &no_mods, &no_mods, &no_mods, &SAMPLE_MODS, &args);
+    assert_eq!(sets[0].total_tier, 140 + 40 + 4 * 50);
+    assert_eq!(sets[1].total_tier, 140 + 40 + 4 * 50);
+    assert_eq!(min_max.max[0], 140);
+}
+
+#[test]
+fn check_set_bonuses() {
+    let no_mods = [NO_MOD; 5];
+    let piece = |id, set_id| ProcessItem {
+        id,
+        energy_cap: 10,
+        set_id,
+        stats: Stats([10; NUM_STATS]),
+        ..Default::default()
+    };
+    let items: [&[_]; NUM_ITEM_BUCKETS] = [
+        &[piece(1, 7), piece(2, 9)],
+        &[piece(3, 7), piece(4, 0)],
+        &[piece(5, 9), piece(6, 7)],
+        &[piece(7, 9)],
+        &[piece(8, 0), piece(9, 7)],
+    ];
+    let args = ProcessArgs {
+        bounds: crate::types::ProcessTierBounds {
+            lower_bounds: [0; NUM_STATS],
+            upper_bounds: [10; NUM_STATS],
+        },
+        set_bonuses: [
+            ProcessSetBonus {
+                set_id: 7,
+                count: 2,
+            },
+            ProcessSetBonus {
+                set_id: 9,
+                count: 2,
+            },
+        ],
+        ..Default::default()
+    };
+    let (info, sets, _) = dim_lo_process(items, &no_mods, &no_mods, &no_mods, &[], &args);
+
+    // Two pieces of set 7 and two pieces of set 9 can be had in seven ways
+    // out of 16 combinations.
+    assert_eq!(info.num_valid_sets, 7);
+    assert_eq!(info.skipped_set_bonus, 9);
+    for set in &sets {
+        let sevens = set.set_bonuses.iter().find(|b| b.set_id == 7).unwrap();
+        let nines = set.set_bonuses.iter().find(|b| b.set_id == 9).unwrap();
+        assert!(sevens.count >= 2 && nines.count >= 2);
+        assert!(set.set_bonuses[0].count >= set.set_bonuses[1].count);
+    }
+}
+
+#[test]
+fn check_tuning() {
+    let no_mods = [NO_MOD; 5];
+    let plain = |id, tuning_stats| ProcessItem {
+        id,
+        energy_cap: 10,
+        tuning_stats,
+        stats: Stats([10, 10, 11, 10, 10, 12]),
+        ..Default::default()
+    };
+    // Only the helmet can be tuned, and only into recovery.
+    let items: [&[_]; NUM_ITEM_BUCKETS] = [
+        &[plain(1, 1 << 2)],
+        &[plain(2, 0)],
+        &[plain(3, 0)],
+        &[plain(4, 0)],
+        &[plain(5, 0)],
+    ];
+    let mut args = ProcessArgs {
+        bounds: crate::types::ProcessTierBounds {
+            lower_bounds: [5, 5, 5, 5, 5, 5],
+            upper_bounds: [10; NUM_STATS],
+        },
+        ..Default::default()
+    };
+    // Without auto mods, 5 recovery can only come from strength's surplus.
+    args.bounds.lower_bounds[2] = 6;
+    let (_, sets, _) = dim_lo_process(items, &no_mods, &no_mods, &no_mods, &[], &args);
+    assert_eq!(sets.len(), 1);
+    assert_eq!(sets[0].stats, Stats([50, 50, 60, 50, 50, 55]));
+    assert_eq!(sets[0].tunings[0].stat_plus, 2);
+    assert_eq!(sets[0].tunings[0].stat_minus, 5);
+    assert!(sets[0].tunings[1..]
+        .iter()
+        .all(|t| t.stat_plus == t.stat_minus));
+
+    // Tuning can't help with intellect.
+    args.bounds.lower_bounds[2] = 5;
+    args.bounds.lower_bounds[4] = 6;
+    let (info, sets, _) = dim_lo_process(items, &no_mods, &no_mods, &no_mods, &[], &args);
+    assert!(sets.is_empty());
+    assert_eq!(info.skipped_stat_range, 1);
+
+    // Tuning only takes points from stats the item has 5 of: the helmet has 4 strength,
+    // and the other stats have no surplus.
+    args.bounds.lower_bounds[2] = 6;
+    args.bounds.lower_bounds[4] = 5;
+    let weak = ProcessItem {
+        stats: Stats([10, 10, 11, 10, 10, 4]),
+        ..plain(1, 1 << 2)
+    };
+    let strong = |id| ProcessItem {
+        stats: Stats([10, 10, 11, 10, 10, 14]),
+        ..plain(id, 0)
+    };
+    let weak_items: [&[_]; NUM_ITEM_BUCKETS] = [
+        &[weak],
+        &[strong(2)],
+        &[strong(3)],
+        &[strong(4)],
+        &[strong(5)],
+    ];
+    let (info, sets, _) = dim_lo_process(weak_items, &no_mods, &no_mods, &no_mods, &[], &args);
+    assert!(sets.is_empty());
+    assert_eq!(info.skipped_stat_range, 1);
+
+    // With stat mods, tuning makes up for the expensive recovery mod:
+    // 1 energy left fits a small mobility mod, but not a small recovery mod.
+    let tight = |id, tuning_stats| ProcessItem {
+        energy_val: 9,
+        ..plain(id, tuning_stats)
+    };
+    let items: [&[_]; NUM_ITEM_BUCKETS] = [
+        &[tight(1, 1 << 2)],
+        &[tight(2, 0)],
+        &[tight(3, 0)],
+        &[tight(4, 0)],
+        &[tight(5, 0)],
+    ];
+    args.bounds.lower_bounds = [6, 5, 6, 5, 5, 5];
+    args.auto_mods = 5;
+    let (_, sets, _) = dim_lo_process(items, &no_mods, &no_mods, &no_mods, &SAMPLE_MODS, &args);
+    assert_eq!(sets.len(), 1);
+    assert_eq!(sets[0].stats, Stats([60, 50, 60, 50, 50, 55]));
+    assert_eq!(sets[0].tunings[0].stat_plus, 2);
+}
+
+#[test]
+fn check_exotic_class_items() {
+    let no_mods = [NO_MOD; 5];
+    let item = |id, exotic, exotic_perk_id| ProcessItem {
+        id,
+        energy_cap: 10,
+        exotic,
+        exotic_perk_id,
+        stats: Stats([10; NUM_STATS]),
+        ..Default::default()
+    };
+    let items: [&[_]; NUM_ITEM_BUCKETS] = [
+        &[item(1, true, 0), item(2, false, 0)],
+        &[item(3, false, 0)],
+        &[item(4, false, 0)],
+        &[item(5, false, 0)],
+        &[item(6, true, 11), item(7, true, 12), item(8, false, 0)],
+    ];
+    let mut args = ProcessArgs {
+        bounds: crate::types::ProcessTierBounds {
+            lower_bounds: [0; NUM_STATS],
+            upper_bounds: [10; NUM_STATS],
+        },
+        any_exotic: true,
+        ..Default::default()
+    };
+
+    // The exotic helmet only goes with the legendary class item, and the
+    // legendary helmet needs one of the exotic class items.
+    let (info, sets, _) = dim_lo_process(items, &no_mods, &no_mods, &no_mods, &[], &args);
+    assert_eq!(info.num_valid_sets, 3);
+    assert_eq!(info.skipped_double_exotic, 2);
+    assert_eq!(info.skipped_no_exotic, 1);
+    let mut picked = sets
+        .iter()
+        .map(|s| (s.items[0], s.items[4]))
+        .collect::<Vec<_>>();
+    picked.sort();
+    assert_eq!(picked, [(1, 8), (2, 6), (2, 7)]);
+
+    // Asking for a perk combination rules out every other exotic.
+    args.required_exotic_perk_id = 12;
+    let (info, sets, _) = dim_lo_process(items, &no_mods, &no_mods, &no_mods, &[], &args);
+    assert_eq!(info.num_valid_sets, 1);
+    assert_eq!(info.skipped_double_exotic, 0);
+    assert_eq!(info.skipped_no_exotic, 5);
+    assert_eq!(sets[0].items[4], 7);
+}
+
+#[test]
+fn check_skip_counts_saturate() {
+    // The 300^4 combinations skipped after the helmet don't fit in a u32.
+    let no_mods = [NO_MOD; 5];
+    let helmet = [ProcessItem {
+        exotic: true,
+        exotic_perk_id: 1,
+        ..Default::default()
+    }];
+    let others = Vec::from_iter((0..300).map(|id| ProcessItem {
+        id,
+        ..Default::default()
+    }));
+    let items: [&[_]; NUM_ITEM_BUCKETS] = [&helmet, &others, &others, &others, &others];
+    let args = ProcessArgs {
+        required_exotic_perk_id: 2,
+        ..Default::default()
+    };
+    let (info, sets, _) = dim_lo_process(items, &no_mods, &no_mods, &no_mods, &[], &args);
+    assert!(sets.is_empty());
+    assert_eq!(info.skipped_no_exotic, u32::MAX);
+}
+
+#[test]
+fn check_stepped_run() {
+    let no_mods = [NO_MOD; 5];
+    let piece = |id, stat| ProcessItem {
+        id,
+        energy_cap: 10,
+        stats: Stats(core::array::from_fn(|i| if i == stat { 20 } else { 10 })),
+        exotic: id % 7 == 0,
+        ..Default::default()
+    };
+    let buckets: [Vec<ProcessItem>; NUM_ITEM_BUCKETS] = core::array::from_fn(|b| {
+        (0..6)
+            .map(|i| piece(b as u16 * 10 + i, i as usize))
+            .collect()
+    });
+    let items = buckets.each_ref().map(|b| b.as_slice());
+    let args = ProcessArgs {
+        bounds: crate::types::ProcessTierBounds {
+            lower_bounds: [1; NUM_STATS],
+            upper_bounds: [10; NUM_STATS],
+        },
+        auto_mods: 5,
+        ..Default::default()
+    };
+
+    let (info, sets, _) = dim_lo_process(items, &no_mods, &no_mods, &no_mods, &SAMPLE_MODS, &args);
+
+    let mut run =
+        ProcessRun::new(items, &no_mods, &no_mods, &no_mods, &SAMPLE_MODS, &args).unwrap();
+    let mut steps = 0;
+    while !run.step(100) {
+        steps += 1;
+        if let Some(best) = run.best_sets().next() {
+            assert!(best.total_tier <= sets[0].total_tier);
+        }
+    }
+    // 6^5 combinations, and a step can only go over budget by a few class items.
+    assert!(steps >= 6 * 6 * 6 * 6 * 6 / 106);
+    let (stepped_info, stepped_sets, _) = run.into_results();
+
+    assert_eq!(stepped_info.num_valid_sets, info.num_valid_sets);
+    assert_eq!(
+        stepped_info.skipped_double_exotic,
+        info.skipped_double_exotic
+    );
+    assert_eq!(
+        stepped_sets.iter().map(|s| s.items).collect::<Vec<_>>(),
+        sets.iter().map(|s| s.items).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn check_invalid_input() {
+    let no_mods = [NO_MOD; 5];
+    let item = |energy_val| ProcessItem {
+        energy_val,
+        energy_cap: 8,
+        ..Default::default()
+    };
+    let items = [item(3), item(9)];
+    let lists: [&[_]; NUM_ITEM_BUCKETS] = [&items[..1], &items, &items[..1], &items, &items];
+    let args = ProcessArgs::default();
+    assert_eq!(
+        try_dim_lo_process(lists, &no_mods, &no_mods, &no_mods, &[], &args).err(),
+        Some(ProcessError::ItemEnergyOverCapacity {
+            bucket: 1,
+            index: 1
+        })
+    );
+
+    let lists: [&[_]; NUM_ITEM_BUCKETS] = [&items[..1]; NUM_ITEM_BUCKETS];
+    let mut general_mods = [NO_MOD; 5];
+    general_mods[1].hash = NonZeroU32::new(1);
+    assert_eq!(
+        try_dim_lo_process(lists, &general_mods, &no_mods, &no_mods, &[], &args).err(),
+        Some(ProcessError::GeneralModsNotPacked { index: 1 })
+    );
+
+    let mut combat_mods = [NO_MOD; 5];
+    combat_mods[2].energy_type = EnergyType::Void;
+    assert_eq!(
+        try_dim_lo_process(lists, &no_mods, &combat_mods, &no_mods, &[], &args).err(),
+        Some(ProcessError::EmptyModNotZeroed {
+            list: ModList::Combat,
+            index: 2
+        })
+    );
+
+    let mut args = ProcessArgs::default();
+    args.bounds.lower_bounds[3] = 1;
+    assert_eq!(
+        try_dim_lo_process(lists, &no_mods, &no_mods, &no_mods, &[], &args).err(),
+        Some(ProcessError::InvertedBounds { stat: 3 })
+    );
+
+    args.ruleset = StatRuleset::Points;
+    args.bounds.upper_bounds[3] = 200;
+    args.stat_caps = [150; NUM_STATS];
+    assert_eq!(
+        try_dim_lo_process(lists, &no_mods, &no_mods, &no_mods, &[], &args).err(),
+        None
+    );
+    args.bounds.lower_bounds[3] = 160;
+    assert_eq!(
+        try_dim_lo_process(lists, &no_mods, &no_mods, &no_mods, &[], &args).err(),
+        Some(ProcessError::LowerBoundAboveCap { stat: 3 })
+    );
+
+    // With locked general mods, more than 5 auto mods would overrun the stat mod slots.
+    let mut general_mods = [NO_MOD; 5];
+    general_mods[0] = ProcessMod {
+        ..SAMPLE_MODS[0].inner_mod
+    };
+    general_mods[1] = ProcessMod {
+        ..SAMPLE_MODS[1].inner_mod
+    };
+    let args = ProcessArgs {
+        auto_mods: 7,
+        ..Default::default()
+    };
+    assert_eq!(
+        try_dim_lo_process(
+            lists,
+            &general_mods,
+            &no_mods,
+            &no_mods,
+            &SAMPLE_MODS,
+            &args
+        )
+        .err(),
+        Some(ProcessError::TooManyAutoMods { auto_mods: 7 })
+    );
+    let args = ProcessArgs {
+        auto_mods: 5,
+        ..args
+    };
+    let result = try_dim_lo_process(
+        lists,
+        &general_mods,
+        &no_mods,
+        &no_mods,
+        &SAMPLE_MODS,
+        &args,
+    );
+    assert!(result.is_ok());
+
+    // Stats that could overflow the i16 arithmetic, even though each one fits a u16
+    let items = [ProcessItem {
+        stats: Stats([0, 0, MAX_STAT + 1, 0, 0, 0]),
+        ..Default::default()
+    }];
+    let huge: [&[_]; NUM_ITEM_BUCKETS] = [&items[..], &[], &[], &[], &[]];
+    let args = ProcessArgs::default();
+    assert_eq!(
+        try_dim_lo_process(huge, &no_mods, &no_mods, &no_mods, &[], &args).err(),
+        Some(ProcessError::ItemStatTooHigh {
+            bucket: 0,
+            index: 0
+        })
+    );
+    let items = [ProcessItem {
+        bonus_stats: SignedStats([0, -(MAX_STAT as i16) - 1, 0, 0, 0, 0]),
+        ..Default::default()
+    }];
+    let huge: [&[_]; NUM_ITEM_BUCKETS] = [&[], &[], &[], &items[..], &[]];
+    assert_eq!(
+        try_dim_lo_process(huge, &no_mods, &no_mods, &no_mods, &[], &args).err(),
+        Some(ProcessError::ItemStatTooHigh {
+            bucket: 3,
+            index: 0
+        })
+    );
+    let auto_mods = [ProcessStatMod {
+        inner_mod: NO_MOD,
+        stats: Stats([0, 0, 0, 0, 40000, 0]),
+    }];
+    assert_eq!(
+        try_dim_lo_process(lists, &no_mods, &no_mods, &no_mods, &auto_mods, &args).err(),
+        Some(ProcessError::AutoModStatTooHigh { index: 0 })
+    );
+    let mut args = ProcessArgs::default();
+    args.base_stats.0[2] = i16::MIN;
+    assert_eq!(
+        try_dim_lo_process(lists, &no_mods, &no_mods, &no_mods, &[], &args).err(),
+        Some(ProcessError::StatTooHigh { stat: 2 })
+    );
+    let args = ProcessArgs {
+        ruleset: StatRuleset::Points,
+        stat_caps: [200, 200, 200, 200, 200, 60000],
+        ..Default::default()
+    };
+    assert_eq!(
+        try_dim_lo_process(lists, &no_mods, &no_mods, &no_mods, &[], &args).err(),
+        Some(ProcessError::StatTooHigh { stat: 5 })
+    );
+}
+
+fn sample_input() -> ProcessInput {
+    let item = |id, exotic| ProcessItem {
+        id,
+        power: 1800,
+        energy_type: EnergyType::Solar,
+        energy_val: 2,
+        energy_cap: 10,
+        exotic,
+        stats: Stats([2, 12, 22, 2, 12, 22]),
+        bonus_stats: SignedStats([-10, 0, 0, 0, 0, 5]),
+        bonus_mode: BonusStatMode::Conditional,
+        set_id: id % 3,
+        ..Default::default()
+    };
+    let mut general_mods = [NO_MOD; 5];
+    general_mods[0] = ProcessMod {
+        hash: NonZeroU32::new(77),
+        mod_tag: None,
+        energy_type: EnergyType::Any,
+        energy_val: 4,
+    };
+    ProcessInput {
+        items: [
+            Vec::from([item(1, false), item(2, true)]),
+            Vec::from([item(3, false)]),
+            Vec::from([item(4, false), item(5, true)]),
+            Vec::from([item(6, false)]),
+            Vec::from([item(7, false)]),
+        ],
+        general_mods,
+        combat_mods: [NO_MOD; 5],
+        activity_mods: [NO_MOD; 5],
+        auto_mods: SAMPLE_MODS
+            .iter()
+            .map(|m| ProcessStatMod {
+                inner_mod: ProcessMod { ..m.inner_mod },
+                stats: m.stats,
+            })
+            .collect(),
+        args: ProcessArgs {
+            base_stats: SignedStats([0, 0, -10, 0, 0, 0]),
+            bounds: crate::types::ProcessTierBounds {
+                lower_bounds: [0, 2, 0, 0, 2, 0],
+                upper_bounds: [10; NUM_STATS],
+            },
+            auto_mods: 4,
+            ..Default::default()
+        },
+    }
+}
+
+#[test]
+fn check_packed_input() {
+    let input = sample_input();
+    let packed = input.to_packed();
+    let unpacked = ProcessInput::from_packed(&packed).unwrap();
+    assert_eq!(unpacked.to_packed(), packed);
+    assert_eq!(unpacked.num_items(), [2, 1, 2, 1, 1]);
+    assert_eq!(unpacked.auto_mods.len(), SAMPLE_MODS.len());
+
+    let (info, sets, _) = input.process().unwrap();
+    let (unpacked_info, unpacked_sets, _) = unpacked.process().unwrap();
+    assert!(!sets.is_empty());
+    assert_eq!(info.num_valid_sets, unpacked_info.num_valid_sets);
+    assert_eq!(
+        sets.iter().map(|s| s.items).collect::<Vec<_>>(),
+        unpacked_sets.iter().map(|s| s.items).collect::<Vec<_>>()
+    );
+
+    assert_eq!(
+        ProcessInput::from_packed(&packed[..packed.len() - 1]).err(),
+        Some(PackedError::UnexpectedEnd {
+            offset: packed.len() - 2
+        })
+    );
+    let mut bad = packed.clone();
+    bad.push(0);
+    assert_eq!(
+        ProcessInput::from_packed(&bad).err(),
+        Some(PackedError::TrailingBytes {
+            offset: packed.len()
+        })
+    );
+    let mut bad = packed.clone();
+    bad[6] = 2;
+    assert_eq!(
+        ProcessInput::from_packed(&bad).err(),
+        Some(PackedError::InvalidValue { offset: 6 })
+    );
+    let mut bad = packed.clone();
+    bad[7] = NUM_ITEM_BUCKETS as u8 + 1;
+    assert_eq!(
+        ProcessInput::from_packed(&bad).err(),
+        Some(PackedError::InvalidValue { offset: 7 })
+    );
+    bad[4] = 9;
+    assert_eq!(
+        ProcessInput::from_packed(&bad).err(),
+        Some(PackedError::UnsupportedVersion(9))
+    );
+}
+
+#[test]
+fn check_input_dump() {
+    let input = sample_input();
+    let dump = input.to_dump();
+    assert_eq!(
+        dump.len(),
+        DUMP_HEADER_LEN
+            + size_of::<ProcessArgs>()
+            + 7 * size_of::<ProcessItem>()
+            + 15 * size_of::<ProcessMod>()
+            + SAMPLE_MODS.len() * size_of::<ProcessStatMod>()
+    );
+    let loaded = ProcessInput::from_dump(&dump).unwrap();
+    assert_eq!(loaded.to_packed(), input.to_packed());
+    assert_eq!(loaded.to_dump(), dump);
+
+    // The first item starts right after the args and holds its fields at their usual offsets
+    let item = DUMP_HEADER_LEN + size_of::<ProcessArgs>();
+    assert_eq!(dump[item..item + 2], 1u16.to_le_bytes());
+    let energy_type = item + offset_of!(ProcessItem, energy_type);
+    assert_eq!(dump[energy_type], EnergyType::Solar as u8);
+
+    let mut bad = dump.clone();
+    bad[energy_type] = 5;
+    assert_eq!(
+        ProcessInput::from_dump(&bad).err(),
+        Some(PackedError::InvalidValue {
+            offset: energy_type
+        })
+    );
+    assert_eq!(
+        ProcessInput::from_packed(&dump).err(),
+        Some(PackedError::BadMagic)
+    );
+    let mut bad = dump.clone();
+    bad[4..8].copy_from_slice(&(ABI_VERSION + 1).to_le_bytes());
+    assert_eq!(
+        ProcessInput::from_dump(&bad).err(),
+        Some(PackedError::UnsupportedVersion(ABI_VERSION + 1))
+    );
+    assert_eq!(
+        ProcessInput::from_dump(&dump[..dump.len() - 1]).err(),
+        Some(PackedError::UnexpectedEnd {
+            offset: dump.len() - size_of::<ProcessStatMod>()
+        })
+    );
+}
+
+#[test]
+fn check_abi_layout() {
+    const LEN: usize = layout_table_len();
+    let table = layout_table::<LEN>();
+    assert_eq!(table[0], ABI_VERSION);
+    assert_eq!(table[1] as usize, FFI_LAYOUTS.len());
+
+    // ProcessItem comes first: size, align, number of fields, then (offset, size) pairs
+    assert_eq!(&table[2..5], &[44, 4, 13]);
+    assert_eq!(&table[5..7], &[0, 2]);
+    let stats = FFI_LAYOUTS[0]
+        .fields
+        .iter()
+        .position(|f| f.name == "stats")
+        .unwrap();
+    assert_eq!(&table[5 + 2 * stats..7 + 2 * stats], &[12, 12]);
+}
+
+#[test]
+fn check_generated_inventory() {
+    let options = GenerateOptions {
+        seed: 7,
+        items_per_bucket: [8, 7, 6, 5, 4],
+        exotic_percent: 20,
+        elements: true,
+        mod_tags: 3,
+        locked_mods: [1, 1, 1],
+        ..Default::default()
+    };
+    let input = generate(&options);
+    assert_eq!(generate(&options).to_packed(), input.to_packed());
+    assert_eq!(input.num_items(), [8, 7, 6, 5, 4]);
+    for item in input.items.iter().flatten() {
+        assert!(item.stats.0.iter().all(|s| (2..=30).contains(s)));
+        let total = item.stats.0.iter().sum::<u16>();
+        assert!((52..=68).contains(&total));
+        assert!((7..=10).contains(&item.energy_cap));
+        assert!(item.mod_tags < 1 << 3);
+    }
+
+    let other_seed = generate(&GenerateOptions { seed: 8, ..options });
+    assert_ne!(other_seed.to_packed(), input.to_packed());
+
+    let mut input = input;
+    input.args.bounds.lower_bounds = [3; NUM_STATS];
+    let (info, sets, _) = input.process().unwrap();
+    assert!(info.num_valid_sets > 0);
+    for set in &sets {
+        let items: [&ProcessItem; NUM_ITEM_BUCKETS] = core::array::from_fn(|b| {
+            input.items[b]
+                .iter()
+                .find(|i| i.id == set.items[b])
+                .unwrap()
+        });
+        assert!(items.iter().filter(|i| i.exotic).count() <= 1);
+        assert!(set.stats.0.iter().all(|&s| s >= 30));
+    }
+}
+
 pub const NO_MOD: ProcessMod = ProcessMod {
     hash: None,
     mod_tag: None,
@@ -218,3 +982,302 @@ pub const SAMPLE_MODS: [ProcessStatMod; 12] = [
         stats: Stats([0, 0, 0, 0, 0, 10]),
     },
 ];
+
+/// A random tiny inventory, mods and bounds for `check_auto_mods_against_oracle`.
+struct OracleCase {
+    items: [ProcessItem; NUM_ITEM_BUCKETS],
+    general_mods: [ProcessMod; NUM_ITEM_BUCKETS],
+    combat_mods: [ProcessMod; NUM_ITEM_BUCKETS],
+    activity_mods: [ProcessMod; NUM_ITEM_BUCKETS],
+    auto_mods: Vec<ProcessStatMod>,
+    args: ProcessArgs,
+}
+
+fn random_element(rng: &mut Rng) -> EnergyType {
+    // Mostly Any, elements are what makes placements interesting but also rare
+    match rng.range(0, 5) {
+        0 => EnergyType::Arc,
+        1 => EnergyType::Solar,
+        _ => EnergyType::Any,
+    }
+}
+
+fn random_mods(
+    rng: &mut Rng,
+    max: u32,
+    first_hash: u32,
+    with_element: bool,
+    with_tag: bool,
+) -> [ProcessMod; NUM_ITEM_BUCKETS] {
+    let mut mods = [NO_MOD; NUM_ITEM_BUCKETS];
+    for (idx, m) in mods.iter_mut().take(rng.range(0, max) as usize).enumerate() {
+        *m = ProcessMod {
+            hash: NonZeroU32::new(first_hash + idx as u32),
+            mod_tag: (with_tag && rng.chance(50))
+                .then(|| NonZeroU32::new(1 << rng.range(0, 1)).unwrap()),
+            energy_type: if with_element {
+                random_element(rng)
+            } else {
+                EnergyType::Any
+            },
+            energy_val: rng.range(1, 3) as u8,
+        };
+    }
+    mods
+}
+
+fn random_oracle_case(rng: &mut Rng) -> OracleCase {
+    let items = core::array::from_fn(|id| ProcessItem {
+        id: id as u16,
+        energy_type: random_element(rng),
+        energy_val: rng.range(0, 1) as u8,
+        energy_cap: rng.range(3, 10) as u8,
+        mod_tags: rng.range(0, 3),
+        stats: Stats(core::array::from_fn(|_| rng.range(2, 30) as u16)),
+        tuning_stats: if rng.chance(25) {
+            1 << rng.range(0, NUM_STATS as u32 - 1)
+        } else {
+            0
+        },
+        ..Default::default()
+    });
+
+    // Like in the game, a stat's +5 mod never costs more than its +10 mod, see `oracle`.
+    let mut auto_mods = Vec::new();
+    for stat in 0..NUM_STATS {
+        if !rng.chance(40) {
+            continue;
+        }
+        let minor_cost = rng.range(1, 3);
+        for (points, cost) in [(5, minor_cost), (10, rng.range(minor_cost, 5))] {
+            let mut stats = [0; NUM_STATS];
+            stats[stat] = points;
+            auto_mods.push(ProcessStatMod {
+                inner_mod: ProcessMod {
+                    hash: NonZeroU32::new(1 + auto_mods.len() as u32),
+                    mod_tag: None,
+                    energy_type: EnergyType::Any,
+                    energy_val: cost as u8,
+                },
+                stats: Stats(stats),
+            });
+        }
+    }
+
+    // Lower bounds around the stats of the set, so that a few mods make or break it
+    let set_stats = items
+        .iter()
+        .fold(SignedStats::default(), |acc, i| acc + i.stats);
+    let args = ProcessArgs {
+        bounds: crate::types::ProcessTierBounds {
+            lower_bounds: set_stats
+                .0
+                .map(|s| (s + rng.range(0, 20) as i16 - 15).max(0) as u16),
+            upper_bounds: [500; NUM_STATS],
+        },
+        // Sometimes more than there are slots, which validation must reject
+        auto_mods: rng.range(0, NUM_ITEM_BUCKETS as u32 + 2) as u8,
+        ruleset: StatRuleset::Points,
+        stat_caps: [500; NUM_STATS],
+        ..Default::default()
+    };
+
+    OracleCase {
+        items,
+        general_mods: random_mods(rng, NUM_ITEM_BUCKETS as u32, 100, false, false),
+        combat_mods: random_mods(rng, 2, 200, true, false),
+        activity_mods: random_mods(rng, 2, 300, true, true),
+        auto_mods,
+        args,
+    }
+}
+
+#[test]
+fn check_auto_mods_against_oracle() {
+    let mut rng = Rng::new(46);
+    let (mut accepted, mut rejected, mut invalid) = (0, 0, 0);
+    for case_idx in 0..400 {
+        let case = random_oracle_case(&mut rng);
+        let buckets = case.items.each_ref().map(core::slice::from_ref);
+        let run = ProcessRun::new(
+            buckets,
+            &case.general_mods,
+            &case.combat_mods,
+            &case.activity_mods,
+            &case.auto_mods,
+            &case.args,
+        );
+        let run = match run {
+            Ok(run) => run,
+            Err(e) => {
+                assert!(
+                    case.args.auto_mods as usize > NUM_ITEM_BUCKETS,
+                    "case {case_idx}: {e}"
+                );
+                assert_eq!(
+                    e,
+                    ProcessError::TooManyAutoMods {
+                        auto_mods: case.args.auto_mods
+                    }
+                );
+                invalid += 1;
+                continue;
+            }
+        };
+        assert!(case.args.auto_mods as usize <= NUM_ITEM_BUCKETS);
+
+        let items = case.items.each_ref();
+        let stats = items
+            .iter()
+            .fold(case.args.base_stats, |acc, i| acc + i.effective_stats());
+        let oracle = OracleInput {
+            items,
+            stats,
+            general_mods: &case.general_mods,
+            combat_mods: &case.combat_mods,
+            activity_mods: &case.activity_mods,
+            auto_mods: &case.auto_mods,
+            num_stat_mods: case.args.auto_mods,
+            lower: case.args.bounds.lower_bounds,
+        };
+
+        let expected = oracle.accepts();
+        match crate::can_take_mods_auto(items, &stats, &run.invars, &mut Default::default()) {
+            crate::StatModPickResults::Ok(pick) => {
+                assert!(
+                    expected,
+                    "case {case_idx}: LO accepted a set the oracle rejects"
+                );
+                let hashes = pick.pick.map(|m| m.hash);
+                if let Err(e) = oracle.check_pick(&hashes, &pick.tunings, &pick.resulting_stats) {
+                    panic!("case {case_idx}: {e}");
+                }
+                accepted += 1;
+            }
+            _ => {
+                assert!(
+                    !expected,
+                    "case {case_idx}: LO rejected a set the oracle accepts"
+                );
+                rejected += 1;
+            }
+        }
+    }
+    // Make sure the cases cover both outcomes
+    assert!(
+        accepted > 40 && rejected > 40 && invalid > 20,
+        "{accepted} accepted, {rejected} rejected, {invalid} invalid"
+    );
+}
+
+#[test]
+fn check_oracle_placement() {
+    let item = |energy_type, energy_cap, mod_tags| ProcessItem {
+        energy_type,
+        energy_cap,
+        mod_tags,
+        ..Default::default()
+    };
+    let m = |energy_type, energy_val, mod_tag| ProcessMod {
+        hash: NonZeroU32::new(1),
+        mod_tag: NonZeroU32::new(mod_tag),
+        energy_type,
+        energy_val,
+    };
+    let items = [
+        item(EnergyType::Arc, 4, 0),
+        item(EnergyType::Any, 10, 1),
+        item(EnergyType::Any, 1, 0),
+        item(EnergyType::Any, 1, 0),
+        item(EnergyType::Any, 1, 0),
+    ];
+    let items = items.each_ref();
+    let solar = m(EnergyType::Solar, 3, 0);
+    let arc = m(EnergyType::Arc, 3, 0);
+    let tagged = m(EnergyType::Any, 1, 1);
+    let big = m(EnergyType::Any, 5, 0);
+
+    // Only the second item takes the solar and the tagged mod
+    assert!(can_place(
+        &items,
+        &[(Slot::Combat, &solar), (Slot::Activity, &tagged)]
+    ));
+    assert!(!can_place(
+        &items,
+        &[(Slot::Combat, &solar), (Slot::Combat, &tagged)]
+    ));
+    // Solar and arc can't share an item, but arc fits the first one
+    assert!(can_place(
+        &items,
+        &[(Slot::Combat, &solar), (Slot::Activity, &arc)]
+    ));
+    assert!(!can_place(
+        &items,
+        &[
+            (Slot::Combat, &solar),
+            (Slot::Activity, &solar),
+            (Slot::Stat, &big),
+            (Slot::Stat, &arc)
+        ]
+    ));
+    // 3 + 5 energy on the second item, 3 on the first
+    assert!(can_place(
+        &items,
+        &[
+            (Slot::Combat, &solar),
+            (Slot::Stat, &big),
+            (Slot::Stat, &arc)
+        ]
+    ));
+    assert_eq!(tuning_options(&items).len(), 1);
+}
+
+#[test]
+fn check_valid_sets_against_oracle() {
+    let (mut accepted, mut rejected) = (0, 0);
+    for seed in 0..20 {
+        let mut rng = Rng::new(seed);
+        let general_mods = rng.range(0, 2) as u8;
+        let mut input = generate(&GenerateOptions {
+            seed,
+            items_per_bucket: [2; NUM_ITEM_BUCKETS],
+            exotic_percent: 30,
+            elements: true,
+            mod_tags: 2,
+            locked_mods: [general_mods, 1, 1],
+            ..Default::default()
+        });
+        input.args.bounds.lower_bounds = core::array::from_fn(|_| rng.range(0, 4) as u16);
+        input.args.any_exotic = rng.chance(30);
+        // The oracle is slow to reject sets with many auto mods
+        input.args.auto_mods = general_mods + rng.range(0, 2) as u8;
+
+        // One set at a time, since LO doesn't count sets it skips as too low tier
+        for_each_set(&input.items, |set| {
+            let (info, _, _) = dim_lo_process(
+                set.map(core::slice::from_ref),
+                &input.general_mods,
+                &input.combat_mods,
+                &input.activity_mods,
+                &input.auto_mods,
+                &input.args,
+            );
+            let ids = set.map(|i| i.id);
+            let expected = accepts_set(&input, set);
+            assert_eq!(
+                info.num_valid_sets == 1,
+                expected,
+                "seed {seed}, set {ids:?}"
+            );
+            if expected {
+                accepted += 1;
+            } else {
+                rejected += 1;
+            }
+        });
+    }
+    assert!(
+        accepted > 50 && rejected > 50,
+        "{accepted} accepted, {rejected} rejected"
+    );
+}
@@ -0,0 +1,369 @@
+//! Regression test generated by `lo-offline regression` from `small_inventory.json`.
+//! The assertions pin the results LO gave then, regenerate it if a change in
+//! behavior is intended.
+
+use core::num::NonZeroU32;
+
+use crate::{dim_lo_process, types::*};
+
+const NO_MOD: ProcessMod = ProcessMod {
+    hash: None,
+    mod_tag: None,
+    energy_type: EnergyType::Any,
+    energy_val: 0,
+};
+
+#[test]
+fn check_small_inventory() {
+    let items: [&[_]; NUM_ITEM_BUCKETS] = [
+        &[
+            // Helmet 0 (0)
+            ProcessItem {
+                id: 0,
+                power: 1808,
+                energy_type: EnergyType::Solar,
+                energy_val: 0,
+                energy_cap: 7,
+                exotic: true,
+                mod_tags: 0x0,
+                stats: Stats([6, 5, 3, 26, 2, 13]),
+                ..Default::default()
+            },
+            // Helmet 1 (1)
+            ProcessItem {
+                id: 1,
+                power: 1806,
+                energy_type: EnergyType::Void,
+                energy_val: 0,
+                energy_cap: 10,
+                exotic: false,
+                mod_tags: 0x0,
+                stats: Stats([2, 2, 2, 25, 17, 15]),
+                ..Default::default()
+            },
+        ],
+        &[
+            // Gauntlets 2 (2)
+            ProcessItem {
+                id: 2,
+                power: 1810,
+                energy_type: EnergyType::Stasis,
+                energy_val: 0,
+                energy_cap: 8,
+                exotic: true,
+                mod_tags: 0x1,
+                stats: Stats([8, 3, 23, 2, 2, 29]),
+                ..Default::default()
+            },
+            // Gauntlets 3 (3)
+            ProcessItem {
+                id: 3,
+                power: 1805,
+                energy_type: EnergyType::Solar,
+                energy_val: 0,
+                energy_cap: 8,
+                exotic: true,
+                mod_tags: 0x2,
+                stats: Stats([12, 30, 10, 6, 3, 3]),
+                ..Default::default()
+            },
+        ],
+        &[
+            // Chest 4 (4)
+            ProcessItem {
+                id: 4,
+                power: 1804,
+                energy_type: EnergyType::Arc,
+                energy_val: 0,
+                energy_cap: 10,
+                exotic: false,
+                mod_tags: 0x2,
+                stats: Stats([7, 24, 9, 11, 2, 5]),
+                ..Default::default()
+            },
+            // Chest 5 (5)
+            ProcessItem {
+                id: 5,
+                power: 1800,
+                energy_type: EnergyType::Stasis,
+                energy_val: 0,
+                energy_cap: 8,
+                exotic: false,
+                mod_tags: 0x2,
+                stats: Stats([8, 27, 13, 2, 6, 8]),
+                ..Default::default()
+            },
+        ],
+        &[
+            // Legs 6 (6)
+            ProcessItem {
+                id: 6,
+                power: 1805,
+                energy_type: EnergyType::Arc,
+                energy_val: 0,
+                energy_cap: 9,
+                exotic: false,
+                mod_tags: 0x1,
+                stats: Stats([2, 30, 2, 2, 15, 10]),
+                ..Default::default()
+            },
+            // Legs 7 (7)
+            ProcessItem {
+                id: 7,
+                power: 1808,
+                energy_type: EnergyType::Void,
+                energy_val: 0,
+                energy_cap: 8,
+                exotic: false,
+                mod_tags: 0x1,
+                stats: Stats([7, 30, 10, 3, 2, 4]),
+                ..Default::default()
+            },
+        ],
+        &[
+            // Class 8 (8)
+            ProcessItem {
+                id: 8,
+                power: 1800,
+                energy_type: EnergyType::Stasis,
+                energy_val: 0,
+                energy_cap: 9,
+                exotic: true,
+                mod_tags: 0x3,
+                stats: Stats([14, 2, 4, 8, 4, 21]),
+                ..Default::default()
+            },
+            // Class 9 (9)
+            ProcessItem {
+                id: 9,
+                power: 1806,
+                energy_type: EnergyType::Arc,
+                energy_val: 0,
+                energy_cap: 7,
+                exotic: false,
+                mod_tags: 0x2,
+                stats: Stats([6, 6, 10, 2, 3, 27]),
+                ..Default::default()
+            },
+        ],
+    ];
+    let general_mods = [
+        ProcessMod {
+            hash: NonZeroU32::new(100),
+            mod_tag: None,
+            energy_type: EnergyType::Any,
+            energy_val: 2,
+        },
+        NO_MOD,
+        NO_MOD,
+        NO_MOD,
+        NO_MOD,
+    ];
+    let combat_mods = [
+        ProcessMod {
+            hash: NonZeroU32::new(105),
+            mod_tag: None,
+            energy_type: EnergyType::Void,
+            energy_val: 1,
+        },
+        NO_MOD,
+        NO_MOD,
+        NO_MOD,
+        NO_MOD,
+    ];
+    let activity_mods = [
+        ProcessMod {
+            hash: NonZeroU32::new(110),
+            mod_tag: NonZeroU32::new(1),
+            energy_type: EnergyType::Stasis,
+            energy_val: 1,
+        },
+        NO_MOD,
+        NO_MOD,
+        NO_MOD,
+        NO_MOD,
+    ];
+    let auto_mods = [
+        ProcessStatMod {
+            inner_mod: ProcessMod {
+                hash: NonZeroU32::new(1),
+                mod_tag: None,
+                energy_type: EnergyType::Any,
+                energy_val: 3,
+            },
+            stats: Stats([10, 0, 0, 0, 0, 0]),
+        },
+        ProcessStatMod {
+            inner_mod: ProcessMod {
+                hash: NonZeroU32::new(2),
+                mod_tag: None,
+                energy_type: EnergyType::Any,
+                energy_val: 1,
+            },
+            stats: Stats([5, 0, 0, 0, 0, 0]),
+        },
+        ProcessStatMod {
+            inner_mod: ProcessMod {
+                hash: NonZeroU32::new(3),
+                mod_tag: None,
+                energy_type: EnergyType::Any,
+                energy_val: 3,
+            },
+            stats: Stats([0, 10, 0, 0, 0, 0]),
+        },
+        ProcessStatMod {
+            inner_mod: ProcessMod {
+                hash: NonZeroU32::new(4),
+                mod_tag: None,
+                energy_type: EnergyType::Any,
+                energy_val: 1,
+            },
+            stats: Stats([0, 5, 0, 0, 0, 0]),
+        },
+        ProcessStatMod {
+            inner_mod: ProcessMod {
+                hash: NonZeroU32::new(5),
+                mod_tag: None,
+                energy_type: EnergyType::Any,
+                energy_val: 4,
+            },
+            stats: Stats([0, 0, 10, 0, 0, 0]),
+        },
+        ProcessStatMod {
+            inner_mod: ProcessMod {
+                hash: NonZeroU32::new(6),
+                mod_tag: None,
+                energy_type: EnergyType::Any,
+                energy_val: 2,
+            },
+            stats: Stats([0, 0, 5, 0, 0, 0]),
+        },
+        ProcessStatMod {
+            inner_mod: ProcessMod {
+                hash: NonZeroU32::new(7),
+                mod_tag: None,
+                energy_type: EnergyType::Any,
+                energy_val: 3,
+            },
+            stats: Stats([0, 0, 0, 10, 0, 0]),
+        },
+        ProcessStatMod {
+            inner_mod: ProcessMod {
+                hash: NonZeroU32::new(8),
+                mod_tag: None,
+                energy_type: EnergyType::Any,
+                energy_val: 1,
+            },
+            stats: Stats([0, 0, 0, 5, 0, 0]),
+        },
+        ProcessStatMod {
+            inner_mod: ProcessMod {
+                hash: NonZeroU32::new(9),
+                mod_tag: None,
+                energy_type: EnergyType::Any,
+                energy_val: 5,
+            },
+            stats: Stats([0, 0, 0, 0, 10, 0]),
+        },
+        ProcessStatMod {
+            inner_mod: ProcessMod {
+                hash: NonZeroU32::new(10),
+                mod_tag: None,
+                energy_type: EnergyType::Any,
+                energy_val: 2,
+            },
+            stats: Stats([0, 0, 0, 0, 5, 0]),
+        },
+        ProcessStatMod {
+            inner_mod: ProcessMod {
+                hash: NonZeroU32::new(11),
+                mod_tag: None,
+                energy_type: EnergyType::Any,
+                energy_val: 3,
+            },
+            stats: Stats([0, 0, 0, 0, 0, 10]),
+        },
+        ProcessStatMod {
+            inner_mod: ProcessMod {
+                hash: NonZeroU32::new(12),
+                mod_tag: None,
+                energy_type: EnergyType::Any,
+                energy_val: 1,
+            },
+            stats: Stats([0, 0, 0, 0, 0, 5]),
+        },
+    ];
+    let args = ProcessArgs {
+        base_stats: SignedStats([0, 0, 0, 0, 0, 0]),
+        bounds: ProcessTierBounds {
+            lower_bounds: [0, 0, 2, 0, 3, 0],
+            upper_bounds: [10, 10, 10, 10, 10, 10],
+        },
+        any_exotic: false,
+        auto_mods: 5,
+        ruleset: StatRuleset::Tiers,
+        stat_caps: [0, 0, 0, 0, 0, 0],
+        set_bonuses: [
+            ProcessSetBonus {
+                set_id: 0,
+                count: 0,
+            },
+            ProcessSetBonus {
+                set_id: 0,
+                count: 0,
+            },
+        ],
+        required_exotic_perk_id: 0,
+    };
+
+    let (info, sets, min_max) = dim_lo_process(
+        items,
+        &general_mods,
+        &combat_mods,
+        &activity_mods,
+        &auto_mods,
+        &args,
+    );
+
+    assert_eq!(info.num_valid_sets, 4);
+    assert_eq!(info.skipped_low_tier, 0);
+    assert_eq!(info.skipped_stat_range, 0);
+    assert_eq!(info.skipped_mods_unfit, 4);
+    assert_eq!(info.skipped_double_exotic, 24);
+    assert_eq!(info.skipped_no_exotic, 0);
+    assert_eq!(info.skipped_set_bonus, 0);
+    assert_eq!(min_max.min, [25, 65, 33, 33, 26, 54]);
+    assert_eq!(min_max.max, [35, 95, 58, 47, 44, 89]);
+    assert_eq!(sets.len(), 4);
+
+    // The best sets: items, total tier, stats and auto stat mods
+    let best = [
+        (
+            [1, 2, 4, 7, 9],
+            28,
+            Stats([30, 65, 54, 43, 31, 80]),
+            [10, 0, 0, 0, 0],
+        ),
+        (
+            [1, 2, 5, 7, 9],
+            28,
+            Stats([31, 68, 58, 34, 30, 83]),
+            [0, 0, 0, 0, 0],
+        ),
+        (
+            [1, 2, 5, 6, 9],
+            28,
+            Stats([26, 68, 50, 33, 43, 89]),
+            [0, 0, 0, 0, 0],
+        ),
+    ];
+    for (set, (items, total_tier, stats, mods)) in sets.iter().zip(best) {
+        assert_eq!(set.items, items);
+        assert_eq!(set.total_tier, total_tier);
+        assert_eq!(set.stats, stats);
+        assert_eq!(
+            set.extra_stat_mods.map(|m| m.map_or(0, NonZeroU32::get)),
+            mods
+        );
+    }
+}
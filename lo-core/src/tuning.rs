@@ -0,0 +1,137 @@
+use alloc::vec::Vec;
+
+use crate::types::{ProcessItem, ProcessTuning, SignedStats, Stats, NUM_ITEM_BUCKETS, NUM_STATS};
+
+/// How many points a tuning moves from one stat to another.
+pub const TUNING_POINTS: i16 = 5;
+
+#[cfg_attr(test, derive(Debug))]
+#[derive(Clone, Copy)]
+pub struct TuningCandidate {
+    /// The missing stats after tuning, i.e. the key into the stat mods map.
+    pub contribution: Stats,
+    /// The stats the tunings add to the set (and take away from it).
+    pub delta: SignedStats,
+    pub tunings: [ProcessTuning; NUM_ITEM_BUCKETS],
+    num_tunings: u8,
+}
+
+#[derive(Clone, Copy)]
+struct Reachable {
+    delta: SignedStats,
+    num_tunings: u8,
+    tunings: [ProcessTuning; NUM_ITEM_BUCKETS],
+    /// When it was found, the first one wins among those with as few tunings.
+    order: u32,
+}
+
+/// The buffers `generate_tuning_candidates` works in. A run keeps them across sets,
+/// so that the main loop doesn't allocate once they have grown large enough.
+#[derive(Default)]
+pub struct TuningBuffers {
+    reachable: Vec<Reachable>,
+    next: Vec<Reachable>,
+    candidates: Vec<TuningCandidate>,
+}
+
+/// Tuned armor can move 5 points from any stat the item has at least 5 points in into one
+/// of the stats in its `tuning_stats`. So tuning never takes a stat below 0 on its own.
+/// Just like auto stat mods, auto tuning *must be* correct: if some choice of tunings
+/// lets a set reach the minimum stats with mods that fit, LO must find it. Tuning doesn't
+/// cost energy, but it does change which stats are missing, so the choice of tunings
+/// decides which stat mod picks we need to try.
+///
+/// We don't try every combination of tunings though, that would be up to 31^5 of them.
+///
+/// * Only the sum of all tunings matters, so we build the reachable sums item by item
+///   and deduplicate them on the way.
+/// * Only the stats still missing after tuning matter for the stat mod lookup, so we
+///   deduplicate the sums by that too.
+/// * A tuning into a stat that isn't missing any points never helps. Either nothing takes
+///   points from that stat, then dropping the tuning only gives 5 points back to the other
+///   stat, or some other tuning takes points from it, then that one might as well take
+///   them from wherever this one did.
+///
+/// The result doesn't include the untuned candidate and is sorted by the number of tunings,
+/// so that LO prefers leaving armor as it is.
+pub fn generate_tuning_candidates<'b>(
+    items: &[&ProcessItem; NUM_ITEM_BUCKETS],
+    base_stats: &SignedStats,
+    lower: &[u16; NUM_STATS],
+    buffers: &'b mut TuningBuffers,
+) -> &'b mut Vec<TuningCandidate> {
+    let deficient = (0..NUM_STATS)
+        .filter(|&i| base_stats.0[i] < lower[i] as i16)
+        .fold(0u8, |acc, i| acc | (1 << i));
+
+    let TuningBuffers {
+        reachable,
+        next,
+        candidates,
+    } = buffers;
+    reachable.clear();
+    reachable.push(Reachable {
+        delta: SignedStats::default(),
+        num_tunings: 0,
+        tunings: [ProcessTuning::default(); NUM_ITEM_BUCKETS],
+        order: 0,
+    });
+
+    for (idx, item) in items.iter().enumerate() {
+        let targets = item.tuning_stats & deficient;
+        if targets == 0 {
+            continue;
+        }
+
+        next.clear();
+        next.extend(reachable.iter().enumerate().map(|(order, r)| Reachable {
+            order: order as u32,
+            ..*r
+        }));
+        for r in reachable.iter() {
+            for plus in (0..NUM_STATS).filter(|&s| targets & (1 << s) != 0) {
+                for minus in
+                    (0..NUM_STATS).filter(|&s| s != plus && item.stats.0[s] >= TUNING_POINTS as u16)
+                {
+                    let mut delta = r.delta;
+                    delta.0[plus] += TUNING_POINTS;
+                    delta.0[minus] -= TUNING_POINTS;
+                    let mut tunings = r.tunings;
+                    tunings[idx] = ProcessTuning {
+                        stat_plus: plus as u8,
+                        stat_minus: minus as u8,
+                    };
+                    next.push(Reachable {
+                        delta,
+                        num_tunings: r.num_tunings + 1,
+                        tunings,
+                        order: next.len() as u32,
+                    });
+                }
+            }
+        }
+        // Sorting with the order as a tie-breaker doesn't need the allocation
+        // of a stable sort.
+        next.sort_unstable_by_key(|r| (r.delta, r.num_tunings, r.order));
+        next.dedup_by_key(|r| r.delta);
+        core::mem::swap(reachable, next);
+    }
+
+    // `reachable` is sorted by delta, which breaks ties like the order did above.
+    candidates.clear();
+    candidates.extend(
+        reachable
+            .iter()
+            .filter(|r| r.num_tunings > 0)
+            .map(|r| TuningCandidate {
+                contribution: crate::missing_stats(&(*base_stats + r.delta), lower),
+                delta: r.delta,
+                tunings: r.tunings,
+                num_tunings: r.num_tunings,
+            }),
+    );
+    candidates.sort_unstable_by_key(|c| (c.contribution, c.num_tunings, c.delta));
+    candidates.dedup_by_key(|c| c.contribution);
+    candidates.sort_unstable_by_key(|c| (c.num_tunings, c.contribution));
+    candidates
+}
@@ -1,17 +1,31 @@
-use core::{num::NonZeroU32, ops::Add};
+use core::{
+    num::NonZeroU32,
+    ops::{Add, Sub},
+};
 
 pub const NUM_STATS: usize = 6;
 pub const NUM_ITEM_BUCKETS: usize = 5;
+/// With five armor pieces, at most two different sets can have two or more pieces equipped.
+pub const NUM_SET_BONUSES: usize = 2;
 
 #[repr(transparent)]
 #[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(test, derive(Debug))]
 pub struct Stats(pub [u16; NUM_STATS]);
 
+/// Stats that may be negative, e.g. because a subclass fragment applies -10 to a stat.
+/// Sums of signed stats are only clamped at the very end (see `saturate`), just like
+/// the game adds up every contribution before applying the stat limits.
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(test, derive(Debug))]
+pub struct SignedStats(pub [i16; NUM_STATS]);
+
 #[repr(u8)]
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(test, derive(Debug))]
 pub enum EnergyType {
+    #[default]
     Any = 0,
     Arc = 1,
     Solar = 2,
@@ -19,7 +33,23 @@ pub enum EnergyType {
     Stasis = 4,
 }
 
+/// How the `bonus_stats` of an item are treated.
+#[repr(u8)]
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum BonusStatMode {
+    /// The bonus is always active, so it counts toward bounds and tiers
+    /// just like the stats the item rolled with.
+    #[default]
+    Always = 0,
+    /// The bonus only applies under some condition, e.g. Dragon's Shadow's
+    /// mobility after dodging. It is shown in the results, but sets are
+    /// filtered and sorted as if it didn't exist.
+    Conditional = 1,
+}
+
 #[repr(C)]
+#[derive(Default)]
 pub struct ProcessItem {
     /// The id to map the generated set back to real items
     pub id: u16,
@@ -31,6 +61,43 @@ pub struct ProcessItem {
     /// A bit mask of mod tags this item can slot. Currently imposes a limit of 32 slot tags.
     pub mod_tags: u32,
     pub stats: Stats,
+    /// Stats granted (or taken away) on top of `stats`, e.g. by an exotic intrinsic.
+    pub bonus_stats: SignedStats,
+    pub bonus_mode: BonusStatMode,
+    /// A bit mask of the stats this item's tuning can move 5 points into, taken from
+    /// any other stat. 0 if the item can't be tuned, all bits if it can be tuned freely.
+    pub tuning_stats: u8,
+    /// The armor set this item belongs to, 0 if none.
+    pub set_id: u16,
+    /// Identifies the perks of an exotic, e.g. the perk combination of an exotic
+    /// class item. 0 if there's nothing to tell apart.
+    pub exotic_perk_id: u32,
+}
+
+impl ProcessItem {
+    /// The stats this item contributes toward bounds and tiers.
+    #[inline]
+    pub fn effective_stats(&self) -> SignedStats {
+        self.always_stats() + self.stats
+    }
+
+    /// The bonus stats that are excluded from bounds and tiers.
+    #[inline]
+    pub fn conditional_stats(&self) -> SignedStats {
+        match self.bonus_mode {
+            BonusStatMode::Always => SignedStats::default(),
+            BonusStatMode::Conditional => self.bonus_stats,
+        }
+    }
+
+    /// The bonus stats that count toward bounds and tiers.
+    #[inline]
+    pub fn always_stats(&self) -> SignedStats {
+        match self.bonus_mode {
+            BonusStatMode::Always => self.bonus_stats,
+            BonusStatMode::Conditional => SignedStats::default(),
+        }
+    }
 }
 
 #[repr(C)]
@@ -48,20 +115,65 @@ pub struct ProcessStatMod {
     pub stats: Stats,
 }
 
+/// How stats are scored when filtering and ranking sets.
+#[repr(u8)]
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum StatRuleset {
+    /// Stats count in tiers of 10 points, up to T10 (100 points).
+    /// Bounds and `total_tier` are in tiers.
+    #[default]
+    Tiers = 0,
+    /// Stats count as raw points, up to `ProcessArgs::stat_caps`.
+    /// Bounds and `total_tier` are in stat points.
+    Points = 1,
+}
+
+/// A number of pieces of an armor set. Unused entries have `set_id` 0.
+#[repr(C)]
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct ProcessSetBonus {
+    pub set_id: u16,
+    pub count: u16,
+}
+
+/// A tuning that moves 5 points from `stat_minus` to `stat_plus`.
+/// Equal stats (e.g. an all-zero `ProcessTuning`) mean the item isn't tuned.
+#[repr(C)]
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct ProcessTuning {
+    pub stat_plus: u8,
+    pub stat_minus: u8,
+}
+
 #[repr(C)]
+#[derive(Clone, Copy)]
 pub struct ProcessArmorSet {
+    /// Stats from armor, base stats and stat mods, without item bonus stats.
     pub stats: Stats,
     pub items: [u16; NUM_ITEM_BUCKETS],
     pub power: u16,
-    pub total_tier: u8,
+    /// Total tiers (or stat points with `StatRuleset::Points`) for sorting purposes.
+    pub total_tier: u16,
     pub extra_stat_mods: [Option<NonZeroU32>; 5],
+    /// Like `stats`, but including every item's bonus stats, conditional or not.
+    pub stats_with_bonus: Stats,
+    /// The armor sets with at least two pieces in this set, most pieces first.
+    pub set_bonuses: [ProcessSetBonus; NUM_SET_BONUSES],
+    /// The tunings LO picked to hit the minimum stats, in item order.
+    /// Like auto stat mods, these are included in `stats` but not in `total_tier`.
+    pub tunings: [ProcessTuning; NUM_ITEM_BUCKETS],
 }
 
 #[repr(C)]
-#[derive(Default)]
+#[derive(Clone, Copy, Default)]
 pub struct ProcessTierBounds {
-    pub lower_bounds: [u8; NUM_STATS],
-    pub upper_bounds: [u8; NUM_STATS],
+    /// Minimum tiers or points (depending on the `StatRuleset`) per stat.
+    pub lower_bounds: [u16; NUM_STATS],
+    /// Maximum tiers or points (depending on the `StatRuleset`) per stat.
+    pub upper_bounds: [u16; NUM_STATS],
 }
 
 #[repr(C)]
@@ -72,16 +184,48 @@ pub struct ProcessMinMaxStats {
 }
 
 #[repr(C)]
-#[derive(Default)]
+#[derive(Clone, Copy, Default)]
 pub struct ProcessArgs {
-    pub base_stats: Stats,
+    /// Stats every set gets, e.g. from locked stat mods (positive) or
+    /// subclass fragments (negative).
+    pub base_stats: SignedStats,
     pub bounds: ProcessTierBounds,
     pub any_exotic: bool,
     pub auto_mods: u8,
+    pub ruleset: StatRuleset,
+    /// The highest useful value per stat with `StatRuleset::Points`.
+    /// `StatRuleset::Tiers` always caps stats at 100.
+    pub stat_caps: [u16; NUM_STATS],
+    /// Sets must include at least `count` pieces of every armor set listed here.
+    pub set_bonuses: [ProcessSetBonus; NUM_SET_BONUSES],
+    /// If not 0, sets must include an exotic with this `exotic_perk_id`.
+    pub required_exotic_perk_id: u32,
+}
+
+impl ProcessArgs {
+    /// How many stat points one unit of the bounds is worth.
+    #[inline]
+    pub fn points_per_unit(&self) -> u16 {
+        match self.ruleset {
+            StatRuleset::Tiers => 10,
+            StatRuleset::Points => 1,
+        }
+    }
+
+    /// The highest useful value per stat under the configured ruleset.
+    #[inline]
+    pub fn effective_stat_caps(&self) -> [u16; NUM_STATS] {
+        match self.ruleset {
+            StatRuleset::Tiers => [100; NUM_STATS],
+            StatRuleset::Points => self.stat_caps,
+        }
+    }
 }
 
+/// What happened to the combinations of a run. The counters saturate at `u32::MAX`,
+/// which huge inventories can reach when a whole branch of combinations is skipped.
 #[repr(C)]
-#[derive(Default)]
+#[derive(Clone, Copy, Default)]
 pub struct ProcessStats {
     pub num_valid_sets: u32,
     pub skipped_low_tier: u32,
@@ -89,31 +233,41 @@ pub struct ProcessStats {
     pub skipped_mods_unfit: u32,
     pub skipped_double_exotic: u32,
     pub skipped_no_exotic: u32,
+    pub skipped_set_bonus: u32,
 }
 
-macro_rules! assert_size_align {
-    ($ty:ident, $size:literal, $align:literal) => {
-        const _: () = {
-            if core::mem::size_of::<$ty>() != $size {
-                panic!()
-            }
-            if core::mem::align_of::<$ty>() != $align {
-                panic!()
-            }
-        };
-    };
+impl Add for Stats {
+    type Output = Stats;
+
+    #[inline]
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self.0[0] += rhs.0[0];
+        self.0[1] += rhs.0[1];
+        self.0[2] += rhs.0[2];
+        self.0[3] += rhs.0[3];
+        self.0[4] += rhs.0[4];
+        self.0[5] += rhs.0[5];
+        self
+    }
 }
 
-// FFI guarantees...
-assert_size_align!(ProcessItem, 24, 4);
-assert_size_align!(ProcessMod, 12, 4);
-assert_size_align!(ProcessStatMod, 24, 4);
-assert_size_align!(ProcessArmorSet, 48, 4);
-assert_size_align!(ProcessStats, 24, 4);
-assert_size_align!(ProcessTierBounds, 12, 1);
+impl Add<Stats> for SignedStats {
+    type Output = SignedStats;
 
-impl Add for Stats {
-    type Output = Stats;
+    #[inline]
+    fn add(mut self, rhs: Stats) -> Self::Output {
+        self.0[0] += rhs.0[0] as i16;
+        self.0[1] += rhs.0[1] as i16;
+        self.0[2] += rhs.0[2] as i16;
+        self.0[3] += rhs.0[3] as i16;
+        self.0[4] += rhs.0[4] as i16;
+        self.0[5] += rhs.0[5] as i16;
+        self
+    }
+}
+
+impl Add for SignedStats {
+    type Output = SignedStats;
 
     #[inline]
     fn add(mut self, rhs: Self) -> Self::Output {
@@ -126,3 +280,27 @@ impl Add for Stats {
         self
     }
 }
+
+impl Sub for SignedStats {
+    type Output = SignedStats;
+
+    #[inline]
+    fn sub(mut self, rhs: Self) -> Self::Output {
+        self.0[0] -= rhs.0[0];
+        self.0[1] -= rhs.0[1];
+        self.0[2] -= rhs.0[2];
+        self.0[3] -= rhs.0[3];
+        self.0[4] -= rhs.0[4];
+        self.0[5] -= rhs.0[5];
+        self
+    }
+}
+
+impl SignedStats {
+    /// The stats the game would show, i.e. with penalties that exceed
+    /// what a stat has saturating at 0.
+    #[inline]
+    pub fn saturate(self) -> Stats {
+        Stats(self.0.map(|s| s.max(0) as u16))
+    }
+}
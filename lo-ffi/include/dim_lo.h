@@ -0,0 +1,281 @@
+/* Generated by `cargo compile` from the lo-core and lo-ffi sources. Do not edit. */
+
+#ifndef DIM_LO_H
+#define DIM_LO_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+#define DIM_LO_ASSERT(cond, msg) static_assert(cond, msg)
+#define DIM_LO_ALIGNOF(type) alignof(type)
+extern "C" {
+#else
+#define DIM_LO_ASSERT(cond, msg) _Static_assert(cond, msg)
+#define DIM_LO_ALIGNOF(type) _Alignof(type)
+#endif
+
+#define DIM_LO_ABI_VERSION 1
+
+typedef uint8_t EnergyType;
+enum {
+    ENERGY_TYPE_ANY = 0,
+    ENERGY_TYPE_ARC = 1,
+    ENERGY_TYPE_SOLAR = 2,
+    ENERGY_TYPE_VOID = 3,
+    ENERGY_TYPE_STASIS = 4,
+};
+
+typedef uint8_t BonusStatMode;
+enum {
+    BONUS_STAT_MODE_ALWAYS = 0,
+    BONUS_STAT_MODE_CONDITIONAL = 1,
+};
+
+typedef uint8_t StatRuleset;
+enum {
+    STAT_RULESET_TIERS = 0,
+    STAT_RULESET_POINTS = 1,
+};
+
+typedef struct ProcessItem {
+    uint16_t id;
+    uint16_t power;
+    EnergyType energy_type;
+    uint8_t energy_val;
+    uint8_t energy_cap;
+    bool exotic;
+    uint32_t mod_tags;
+    uint16_t stats[6];
+    int16_t bonus_stats[6];
+    BonusStatMode bonus_mode;
+    uint8_t tuning_stats;
+    uint16_t set_id;
+    uint32_t exotic_perk_id;
+} ProcessItem;
+DIM_LO_ASSERT(sizeof(ProcessItem) == 44, "size of ProcessItem");
+DIM_LO_ASSERT(DIM_LO_ALIGNOF(ProcessItem) == 4, "alignment of ProcessItem");
+DIM_LO_ASSERT(offsetof(ProcessItem, id) == 0, "offset of ProcessItem::id");
+DIM_LO_ASSERT(offsetof(ProcessItem, power) == 2, "offset of ProcessItem::power");
+DIM_LO_ASSERT(offsetof(ProcessItem, energy_type) == 4, "offset of ProcessItem::energy_type");
+DIM_LO_ASSERT(offsetof(ProcessItem, energy_val) == 5, "offset of ProcessItem::energy_val");
+DIM_LO_ASSERT(offsetof(ProcessItem, energy_cap) == 6, "offset of ProcessItem::energy_cap");
+DIM_LO_ASSERT(offsetof(ProcessItem, exotic) == 7, "offset of ProcessItem::exotic");
+DIM_LO_ASSERT(offsetof(ProcessItem, mod_tags) == 8, "offset of ProcessItem::mod_tags");
+DIM_LO_ASSERT(offsetof(ProcessItem, stats) == 12, "offset of ProcessItem::stats");
+DIM_LO_ASSERT(offsetof(ProcessItem, bonus_stats) == 24, "offset of ProcessItem::bonus_stats");
+DIM_LO_ASSERT(offsetof(ProcessItem, bonus_mode) == 36, "offset of ProcessItem::bonus_mode");
+DIM_LO_ASSERT(offsetof(ProcessItem, tuning_stats) == 37, "offset of ProcessItem::tuning_stats");
+DIM_LO_ASSERT(offsetof(ProcessItem, set_id) == 38, "offset of ProcessItem::set_id");
+DIM_LO_ASSERT(offsetof(ProcessItem, exotic_perk_id) == 40, "offset of ProcessItem::exotic_perk_id");
+
+typedef struct ProcessMod {
+    uint32_t hash;
+    uint32_t mod_tag;
+    EnergyType energy_type;
+    uint8_t energy_val;
+} ProcessMod;
+DIM_LO_ASSERT(sizeof(ProcessMod) == 12, "size of ProcessMod");
+DIM_LO_ASSERT(DIM_LO_ALIGNOF(ProcessMod) == 4, "alignment of ProcessMod");
+DIM_LO_ASSERT(offsetof(ProcessMod, hash) == 0, "offset of ProcessMod::hash");
+DIM_LO_ASSERT(offsetof(ProcessMod, mod_tag) == 4, "offset of ProcessMod::mod_tag");
+DIM_LO_ASSERT(offsetof(ProcessMod, energy_type) == 8, "offset of ProcessMod::energy_type");
+DIM_LO_ASSERT(offsetof(ProcessMod, energy_val) == 9, "offset of ProcessMod::energy_val");
+
+typedef struct ProcessStatMod {
+    ProcessMod inner_mod;
+    uint16_t stats[6];
+} ProcessStatMod;
+DIM_LO_ASSERT(sizeof(ProcessStatMod) == 24, "size of ProcessStatMod");
+DIM_LO_ASSERT(DIM_LO_ALIGNOF(ProcessStatMod) == 4, "alignment of ProcessStatMod");
+DIM_LO_ASSERT(offsetof(ProcessStatMod, inner_mod) == 0, "offset of ProcessStatMod::inner_mod");
+DIM_LO_ASSERT(offsetof(ProcessStatMod, stats) == 12, "offset of ProcessStatMod::stats");
+
+typedef struct ProcessSetBonus {
+    uint16_t set_id;
+    uint16_t count;
+} ProcessSetBonus;
+DIM_LO_ASSERT(sizeof(ProcessSetBonus) == 4, "size of ProcessSetBonus");
+DIM_LO_ASSERT(DIM_LO_ALIGNOF(ProcessSetBonus) == 2, "alignment of ProcessSetBonus");
+DIM_LO_ASSERT(offsetof(ProcessSetBonus, set_id) == 0, "offset of ProcessSetBonus::set_id");
+DIM_LO_ASSERT(offsetof(ProcessSetBonus, count) == 2, "offset of ProcessSetBonus::count");
+
+typedef struct ProcessTuning {
+    uint8_t stat_plus;
+    uint8_t stat_minus;
+} ProcessTuning;
+DIM_LO_ASSERT(sizeof(ProcessTuning) == 2, "size of ProcessTuning");
+DIM_LO_ASSERT(DIM_LO_ALIGNOF(ProcessTuning) == 1, "alignment of ProcessTuning");
+DIM_LO_ASSERT(offsetof(ProcessTuning, stat_plus) == 0, "offset of ProcessTuning::stat_plus");
+DIM_LO_ASSERT(offsetof(ProcessTuning, stat_minus) == 1, "offset of ProcessTuning::stat_minus");
+
+typedef struct ProcessArmorSet {
+    uint16_t stats[6];
+    uint16_t items[5];
+    uint16_t power;
+    uint16_t total_tier;
+    uint32_t extra_stat_mods[5];
+    uint16_t stats_with_bonus[6];
+    ProcessSetBonus set_bonuses[2];
+    ProcessTuning tunings[5];
+} ProcessArmorSet;
+DIM_LO_ASSERT(sizeof(ProcessArmorSet) == 80, "size of ProcessArmorSet");
+DIM_LO_ASSERT(DIM_LO_ALIGNOF(ProcessArmorSet) == 4, "alignment of ProcessArmorSet");
+DIM_LO_ASSERT(offsetof(ProcessArmorSet, stats) == 0, "offset of ProcessArmorSet::stats");
+DIM_LO_ASSERT(offsetof(ProcessArmorSet, items) == 12, "offset of ProcessArmorSet::items");
+DIM_LO_ASSERT(offsetof(ProcessArmorSet, power) == 22, "offset of ProcessArmorSet::power");
+DIM_LO_ASSERT(offsetof(ProcessArmorSet, total_tier) == 24, "offset of ProcessArmorSet::total_tier");
+DIM_LO_ASSERT(offsetof(ProcessArmorSet, extra_stat_mods) == 28, "offset of ProcessArmorSet::extra_stat_mods");
+DIM_LO_ASSERT(offsetof(ProcessArmorSet, stats_with_bonus) == 48, "offset of ProcessArmorSet::stats_with_bonus");
+DIM_LO_ASSERT(offsetof(ProcessArmorSet, set_bonuses) == 60, "offset of ProcessArmorSet::set_bonuses");
+DIM_LO_ASSERT(offsetof(ProcessArmorSet, tunings) == 68, "offset of ProcessArmorSet::tunings");
+
+typedef struct ProcessStats {
+    uint32_t num_valid_sets;
+    uint32_t skipped_low_tier;
+    uint32_t skipped_stat_range;
+    uint32_t skipped_mods_unfit;
+    uint32_t skipped_double_exotic;
+    uint32_t skipped_no_exotic;
+    uint32_t skipped_set_bonus;
+} ProcessStats;
+DIM_LO_ASSERT(sizeof(ProcessStats) == 28, "size of ProcessStats");
+DIM_LO_ASSERT(DIM_LO_ALIGNOF(ProcessStats) == 4, "alignment of ProcessStats");
+DIM_LO_ASSERT(offsetof(ProcessStats, num_valid_sets) == 0, "offset of ProcessStats::num_valid_sets");
+DIM_LO_ASSERT(offsetof(ProcessStats, skipped_low_tier) == 4, "offset of ProcessStats::skipped_low_tier");
+DIM_LO_ASSERT(offsetof(ProcessStats, skipped_stat_range) == 8, "offset of ProcessStats::skipped_stat_range");
+DIM_LO_ASSERT(offsetof(ProcessStats, skipped_mods_unfit) == 12, "offset of ProcessStats::skipped_mods_unfit");
+DIM_LO_ASSERT(offsetof(ProcessStats, skipped_double_exotic) == 16, "offset of ProcessStats::skipped_double_exotic");
+DIM_LO_ASSERT(offsetof(ProcessStats, skipped_no_exotic) == 20, "offset of ProcessStats::skipped_no_exotic");
+DIM_LO_ASSERT(offsetof(ProcessStats, skipped_set_bonus) == 24, "offset of ProcessStats::skipped_set_bonus");
+
+typedef struct ProcessTierBounds {
+    uint16_t lower_bounds[6];
+    uint16_t upper_bounds[6];
+} ProcessTierBounds;
+DIM_LO_ASSERT(sizeof(ProcessTierBounds) == 24, "size of ProcessTierBounds");
+DIM_LO_ASSERT(DIM_LO_ALIGNOF(ProcessTierBounds) == 2, "alignment of ProcessTierBounds");
+DIM_LO_ASSERT(offsetof(ProcessTierBounds, lower_bounds) == 0, "offset of ProcessTierBounds::lower_bounds");
+DIM_LO_ASSERT(offsetof(ProcessTierBounds, upper_bounds) == 12, "offset of ProcessTierBounds::upper_bounds");
+
+typedef struct ProcessMinMaxStats {
+    uint16_t min[6];
+    uint16_t max[6];
+} ProcessMinMaxStats;
+DIM_LO_ASSERT(sizeof(ProcessMinMaxStats) == 24, "size of ProcessMinMaxStats");
+DIM_LO_ASSERT(DIM_LO_ALIGNOF(ProcessMinMaxStats) == 2, "alignment of ProcessMinMaxStats");
+DIM_LO_ASSERT(offsetof(ProcessMinMaxStats, min) == 0, "offset of ProcessMinMaxStats::min");
+DIM_LO_ASSERT(offsetof(ProcessMinMaxStats, max) == 12, "offset of ProcessMinMaxStats::max");
+
+typedef struct ProcessArgs {
+    int16_t base_stats[6];
+    ProcessTierBounds bounds;
+    bool any_exotic;
+    uint8_t auto_mods;
+    StatRuleset ruleset;
+    uint16_t stat_caps[6];
+    ProcessSetBonus set_bonuses[2];
+    uint32_t required_exotic_perk_id;
+} ProcessArgs;
+DIM_LO_ASSERT(sizeof(ProcessArgs) == 64, "size of ProcessArgs");
+DIM_LO_ASSERT(DIM_LO_ALIGNOF(ProcessArgs) == 4, "alignment of ProcessArgs");
+DIM_LO_ASSERT(offsetof(ProcessArgs, base_stats) == 0, "offset of ProcessArgs::base_stats");
+DIM_LO_ASSERT(offsetof(ProcessArgs, bounds) == 12, "offset of ProcessArgs::bounds");
+DIM_LO_ASSERT(offsetof(ProcessArgs, any_exotic) == 36, "offset of ProcessArgs::any_exotic");
+DIM_LO_ASSERT(offsetof(ProcessArgs, auto_mods) == 37, "offset of ProcessArgs::auto_mods");
+DIM_LO_ASSERT(offsetof(ProcessArgs, ruleset) == 38, "offset of ProcessArgs::ruleset");
+DIM_LO_ASSERT(offsetof(ProcessArgs, stat_caps) == 40, "offset of ProcessArgs::stat_caps");
+DIM_LO_ASSERT(offsetof(ProcessArgs, set_bonuses) == 52, "offset of ProcessArgs::set_bonuses");
+DIM_LO_ASSERT(offsetof(ProcessArgs, required_exotic_perk_id) == 60, "offset of ProcessArgs::required_exotic_perk_id");
+
+typedef struct DimLoContext DimLoContext;
+typedef struct DimLoResults DimLoResults;
+
+/**
+ * Gets the ABI version, see `DIM_LO_ABI_VERSION`.
+ */
+uint32_t dim_lo_abi_version(void);
+
+/**
+ * Allocates a context with room for `num_items` items and `num_auto_mods` auto stat mods.
+ */
+DimLoContext *dim_lo_init(size_t num_items, size_t num_auto_mods);
+
+/**
+ * Gets a pointer to the settings.
+ */
+ProcessArgs *dim_lo_args(DimLoContext *ctx);
+
+/**
+ * Gets a pointer to the number of items in each of the five buckets. The items of all
+ * buckets together must fit into the room given to `dim_lo_init`.
+ */
+uint16_t *dim_lo_num_items_per_bucket(DimLoContext *ctx);
+
+/**
+ * Gets a pointer to the items, bucket by bucket without gaps.
+ */
+ProcessItem *dim_lo_items(DimLoContext *ctx);
+
+/**
+ * Gets a pointer to the 15 mods: 5 general, 5 combat, then 5 activity mods.
+ */
+ProcessMod *dim_lo_mods(DimLoContext *ctx);
+
+/**
+ * Gets a pointer to the auto stat mods.
+ */
+ProcessStatMod *dim_lo_auto_mods(DimLoContext *ctx);
+
+/**
+ * Runs the algorithm. Returns null if the input is invalid.
+ */
+DimLoResults *dim_lo_run(DimLoContext *ctx);
+
+/**
+ * Gets why the last `dim_lo_run` returned null, null if it didn't. The message
+ * lives until the next run.
+ */
+const char *dim_lo_error(const DimLoContext *ctx);
+
+/**
+ * Gets how many sets were generated.
+ */
+size_t dim_lo_results_num_sets(const DimLoResults *res);
+
+/**
+ * Gets a pointer to the generated sets, best first.
+ */
+const ProcessArmorSet *dim_lo_results_sets(const DimLoResults *res);
+
+/**
+ * Gets a pointer to the run's statistics.
+ */
+const ProcessStats *dim_lo_results_info(const DimLoResults *res);
+
+/**
+ * Gets a pointer to the minimum and maximum stats of all valid sets.
+ */
+const ProcessMinMaxStats *dim_lo_results_min_max(const DimLoResults *res);
+
+/**
+ * Frees a context and results at once. Either may be null.
+ */
+void dim_lo_free(DimLoContext *ctx, DimLoResults *res);
+
+/**
+ * Frees a context. Results of its runs stay valid. `ctx` may be null.
+ */
+void dim_lo_free_context(DimLoContext *ctx);
+
+/**
+ * Frees results. `res` may be null.
+ */
+void dim_lo_free_results(DimLoResults *res);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,270 @@
+//! C API for the loadout optimizer, for native hosts. `include/dim_lo.h` declares
+//! everything exported here and is generated by `cargo compile --bindings-only`.
+//!
+//! Lifecycle:
+//!
+//! 1. `dim_lo_init` allocates a context with room for a number of items and auto stat mods.
+//!    The host fills it through the pointers returned by `dim_lo_args`,
+//!    `dim_lo_num_items_per_bucket`, `dim_lo_items`, `dim_lo_mods` and `dim_lo_auto_mods`.
+//!    Everything starts out zeroed, i.e. without items and mods.
+//! 2. `dim_lo_run` runs the algorithm and returns new results. It doesn't change the inputs,
+//!    so the host may change some of them and run again, as often as it likes. If the input
+//!    is invalid, `dim_lo_run` returns null and `dim_lo_error` tells why.
+//! 3. Results are independent of the context and of other results. Read them through
+//!    the `dim_lo_results_*` functions and free each one with `dim_lo_free_results`.
+//! 4. `dim_lo_free_context` frees the context. `dim_lo_free` frees a context and a result at once.
+//!
+//! Unlike lo-web, this checks every enum and bool before running, since hosts like
+//! Python's ctypes make it easy to write anything into them.
+
+use std::{
+    ffi::{c_char, CString},
+    ptr,
+};
+
+use dim_lo_core::{
+    abi::{FfiKind, FfiType, ABI_VERSION, FFI_ENUMS, FFI_LAYOUTS},
+    try_dim_lo_process,
+    types::{
+        EnergyType, ProcessArgs, ProcessArmorSet, ProcessItem, ProcessMinMaxStats, ProcessMod,
+        ProcessStatMod, ProcessStats, Stats, NUM_ITEM_BUCKETS,
+    },
+};
+
+/// General, combat and activity mods.
+const NUM_MODS: usize = 3 * NUM_ITEM_BUCKETS;
+
+const EMPTY_MOD: ProcessMod = ProcessMod {
+    hash: None,
+    mod_tag: None,
+    energy_type: EnergyType::Any,
+    energy_val: 0,
+};
+
+pub struct DimLoContext {
+    args: ProcessArgs,
+    num_items: [u16; NUM_ITEM_BUCKETS],
+    /// All items, bucket by bucket. Its length is the capacity given to `dim_lo_init`.
+    items: Vec<ProcessItem>,
+    mods: [ProcessMod; NUM_MODS],
+    auto_mods: Vec<ProcessStatMod>,
+    /// Why the last run failed, if it did.
+    error: Option<CString>,
+}
+
+pub struct DimLoResults {
+    sets: Vec<ProcessArmorSet>,
+    info: ProcessStats,
+    min_max: ProcessMinMaxStats,
+}
+
+/// Finds a bool or enum field with an invalid value in the struct `name` at `ptr`.
+unsafe fn invalid_field(name: &str, ptr: *const u8) -> Option<&'static str> {
+    let layout = FFI_LAYOUTS.iter().find(|l| l.name == name).unwrap();
+    for field in layout.fields {
+        let stride = field.size / field.len;
+        for i in 0..field.len {
+            let ptr = ptr.add(field.offset + i * stride);
+            let valid = match field.kind {
+                FfiKind::Bool => *ptr <= 1,
+                FfiKind::Enum(name) => FFI_ENUMS
+                    .iter()
+                    .find(|e| e.name == name)
+                    .unwrap()
+                    .variants
+                    .iter()
+                    .any(|&(_, value)| value == *ptr as u32),
+                FfiKind::Struct(name) => match invalid_field(name, ptr) {
+                    Some(inner) => return Some(inner),
+                    None => true,
+                },
+                FfiKind::U8 | FfiKind::U16 | FfiKind::U32 | FfiKind::I16 => true,
+            };
+            if !valid {
+                return Some(field.name);
+            }
+        }
+    }
+    None
+}
+
+/// Checks the bools and enums of the `len` values at `ptr`. On error, returns
+/// the index of the value and the name of the field.
+unsafe fn check_fields<T: FfiType>(ptr: *const T, len: usize) -> Result<(), (usize, &'static str)> {
+    let FfiKind::Struct(name) = T::KIND else {
+        unreachable!()
+    };
+    for i in 0..len {
+        if let Some(field) = invalid_field(name, ptr.add(i) as *const u8) {
+            return Err((i, field));
+        }
+    }
+    Ok(())
+}
+
+fn run(ctx: &DimLoContext) -> Result<DimLoResults, String> {
+    let num_items = ctx.num_items.iter().map(|&n| n as usize).sum::<usize>();
+    if num_items > ctx.items.len() {
+        return Err(format!(
+            "{num_items} items don't fit into the context, which has room for {}",
+            ctx.items.len()
+        ));
+    }
+
+    unsafe {
+        check_fields(&ctx.args, 1).map_err(|(_, field)| format!("invalid {field} in args"))?;
+        check_fields(ctx.items.as_ptr(), num_items)
+            .map_err(|(i, field)| format!("invalid {field} in item {i}"))?;
+        check_fields(ctx.mods.as_ptr(), NUM_MODS)
+            .map_err(|(i, field)| format!("invalid {field} in mod {i}"))?;
+        check_fields(ctx.auto_mods.as_ptr(), ctx.auto_mods.len())
+            .map_err(|(i, field)| format!("invalid {field} in auto stat mod {i}"))?;
+    }
+
+    let mut lists: [&[ProcessItem]; NUM_ITEM_BUCKETS] = [&[]; NUM_ITEM_BUCKETS];
+    let mut rest = &ctx.items[..];
+    for (list, len) in lists.iter_mut().zip(ctx.num_items) {
+        (*list, rest) = rest.split_at(len as usize);
+    }
+    let mods = |idx: usize| {
+        <&[ProcessMod; NUM_ITEM_BUCKETS]>::try_from(
+            &ctx.mods[idx * NUM_ITEM_BUCKETS..(idx + 1) * NUM_ITEM_BUCKETS],
+        )
+        .unwrap()
+    };
+
+    let (info, sets, min_max) =
+        try_dim_lo_process(lists, mods(0), mods(1), mods(2), &ctx.auto_mods, &ctx.args)
+            .map_err(|e| e.to_string())?;
+    Ok(DimLoResults {
+        sets,
+        info,
+        min_max,
+    })
+}
+
+/// Gets the ABI version, see `DIM_LO_ABI_VERSION`.
+#[no_mangle]
+extern "C" fn dim_lo_abi_version() -> u32 {
+    ABI_VERSION
+}
+
+/// Allocates a context with room for `num_items` items and `num_auto_mods` auto stat mods.
+#[no_mangle]
+extern "C" fn dim_lo_init(num_items: usize, num_auto_mods: usize) -> *mut DimLoContext {
+    let ctx = Box::new(DimLoContext {
+        args: ProcessArgs::default(),
+        num_items: [0; NUM_ITEM_BUCKETS],
+        items: (0..num_items).map(|_| ProcessItem::default()).collect(),
+        mods: [EMPTY_MOD; NUM_MODS],
+        auto_mods: (0..num_auto_mods)
+            .map(|_| ProcessStatMod {
+                inner_mod: EMPTY_MOD,
+                stats: Stats::default(),
+            })
+            .collect(),
+        error: None,
+    });
+    Box::into_raw(ctx)
+}
+
+/// Gets a pointer to the settings.
+#[no_mangle]
+extern "C" fn dim_lo_args(ctx: *mut DimLoContext) -> *mut ProcessArgs {
+    unsafe { &mut (*ctx).args }
+}
+
+/// Gets a pointer to the number of items in each of the five buckets. The items of all
+/// buckets together must fit into the room given to `dim_lo_init`.
+#[no_mangle]
+extern "C" fn dim_lo_num_items_per_bucket(ctx: *mut DimLoContext) -> *mut u16 {
+    unsafe { (*ctx).num_items.as_mut_ptr() }
+}
+
+/// Gets a pointer to the items, bucket by bucket without gaps.
+#[no_mangle]
+extern "C" fn dim_lo_items(ctx: *mut DimLoContext) -> *mut ProcessItem {
+    unsafe { (*ctx).items.as_mut_ptr() }
+}
+
+/// Gets a pointer to the 15 mods: 5 general, 5 combat, then 5 activity mods.
+#[no_mangle]
+extern "C" fn dim_lo_mods(ctx: *mut DimLoContext) -> *mut ProcessMod {
+    unsafe { (*ctx).mods.as_mut_ptr() }
+}
+
+/// Gets a pointer to the auto stat mods.
+#[no_mangle]
+extern "C" fn dim_lo_auto_mods(ctx: *mut DimLoContext) -> *mut ProcessStatMod {
+    unsafe { (*ctx).auto_mods.as_mut_ptr() }
+}
+
+/// Runs the algorithm. Returns null if the input is invalid.
+#[no_mangle]
+extern "C" fn dim_lo_run(ctx: *mut DimLoContext) -> *mut DimLoResults {
+    let ctx = unsafe { &mut *ctx };
+    match run(ctx) {
+        Ok(results) => {
+            ctx.error = None;
+            Box::into_raw(Box::new(results))
+        }
+        Err(message) => {
+            ctx.error = Some(CString::new(message).unwrap());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Gets why the last `dim_lo_run` returned null, null if it didn't. The message
+/// lives until the next run.
+#[no_mangle]
+extern "C" fn dim_lo_error(ctx: *const DimLoContext) -> *const c_char {
+    unsafe { (*ctx).error.as_ref().map_or(ptr::null(), |e| e.as_ptr()) }
+}
+
+/// Gets how many sets were generated.
+#[no_mangle]
+extern "C" fn dim_lo_results_num_sets(res: *const DimLoResults) -> usize {
+    unsafe { (*res).sets.len() }
+}
+
+/// Gets a pointer to the generated sets, best first.
+#[no_mangle]
+extern "C" fn dim_lo_results_sets(res: *const DimLoResults) -> *const ProcessArmorSet {
+    unsafe { (*res).sets.as_ptr() }
+}
+
+/// Gets a pointer to the run's statistics.
+#[no_mangle]
+extern "C" fn dim_lo_results_info(res: *const DimLoResults) -> *const ProcessStats {
+    unsafe { &(*res).info }
+}
+
+/// Gets a pointer to the minimum and maximum stats of all valid sets.
+#[no_mangle]
+extern "C" fn dim_lo_results_min_max(res: *const DimLoResults) -> *const ProcessMinMaxStats {
+    unsafe { &(*res).min_max }
+}
+
+/// Frees a context and results at once. Either may be null.
+#[no_mangle]
+extern "C" fn dim_lo_free(ctx: *mut DimLoContext, res: *mut DimLoResults) {
+    dim_lo_free_context(ctx);
+    dim_lo_free_results(res);
+}
+
+/// Frees a context. Results of its runs stay valid. `ctx` may be null.
+#[no_mangle]
+extern "C" fn dim_lo_free_context(ctx: *mut DimLoContext) {
+    if !ctx.is_null() {
+        let _ctx = unsafe { Box::from_raw(ctx) };
+    }
+}
+
+/// Frees results. `res` may be null.
+#[no_mangle]
+extern "C" fn dim_lo_free_results(res: *mut DimLoResults) {
+    if !res.is_null() {
+        let _res = unsafe { Box::from_raw(res) };
+    }
+}
@@ -0,0 +1,119 @@
+/* Goes through the whole lifecycle of the C API, see `lifecycle.rs` for how it's built. */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "dim_lo.h"
+
+#define CHECK(cond)                                                                  \
+    do {                                                                             \
+        if (!(cond)) {                                                               \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+            exit(1);                                                                 \
+        }                                                                            \
+    } while (0)
+
+#define ITEMS_PER_BUCKET 3
+#define NUM_ITEMS (5 * ITEMS_PER_BUCKET)
+
+static void fill_items(DimLoContext *ctx) {
+    uint16_t *num_items = dim_lo_num_items_per_bucket(ctx);
+    ProcessItem *items = dim_lo_items(ctx);
+    for (int bucket = 0; bucket < 5; bucket++) {
+        num_items[bucket] = ITEMS_PER_BUCKET;
+        for (int i = 0; i < ITEMS_PER_BUCKET; i++) {
+            ProcessItem *item = &items[bucket * ITEMS_PER_BUCKET + i];
+            memset(item, 0, sizeof(*item));
+            item->id = (uint16_t)(bucket * ITEMS_PER_BUCKET + i);
+            item->power = 1800;
+            item->energy_type = ENERGY_TYPE_ARC;
+            item->energy_cap = 10;
+            item->exotic = bucket == 0 && i == 0;
+            for (int stat = 0; stat < 6; stat++) {
+                item->stats[stat] = (uint16_t)(2 + ((bucket + i + stat) % 3) * 10);
+            }
+        }
+    }
+}
+
+int main(void) {
+    CHECK(dim_lo_abi_version() == DIM_LO_ABI_VERSION);
+
+    DimLoContext *ctx = dim_lo_init(NUM_ITEMS, 1);
+    CHECK(ctx != NULL);
+
+    ProcessArgs *args = dim_lo_args(ctx);
+    args->any_exotic = false;
+    args->auto_mods = 1;
+    args->ruleset = STAT_RULESET_TIERS;
+    for (int stat = 0; stat < 6; stat++) {
+        args->bounds.upper_bounds[stat] = 10;
+    }
+    fill_items(ctx);
+
+    ProcessMod *mods = dim_lo_mods(ctx);
+    mods[0].hash = 1234;
+    mods[0].energy_val = 3;
+
+    ProcessStatMod *auto_mods = dim_lo_auto_mods(ctx);
+    auto_mods[0].inner_mod.hash = 99;
+    auto_mods[0].inner_mod.energy_val = 1;
+    auto_mods[0].stats[0] = 10;
+
+    DimLoResults *res = dim_lo_run(ctx);
+    CHECK(res != NULL);
+    CHECK(dim_lo_error(ctx) == NULL);
+    const ProcessStats *info = dim_lo_results_info(res);
+    CHECK(info->num_valid_sets > 0);
+    size_t num_sets = dim_lo_results_num_sets(res);
+    CHECK(num_sets > 0);
+    const ProcessArmorSet *sets = dim_lo_results_sets(res);
+    for (size_t i = 0; i < num_sets; i++) {
+        CHECK(sets[i].power == 1800);
+        for (int bucket = 0; bucket < 5; bucket++) {
+            CHECK(sets[i].items[bucket] / ITEMS_PER_BUCKET == bucket);
+        }
+        if (i > 0) {
+            CHECK(sets[i - 1].total_tier >= sets[i].total_tier);
+        }
+    }
+    const ProcessMinMaxStats *min_max = dim_lo_results_min_max(res);
+    for (int stat = 0; stat < 6; stat++) {
+        CHECK(min_max->min[stat] <= min_max->max[stat]);
+    }
+
+    /* The context can be changed and run again, earlier results stay valid */
+    args->bounds.lower_bounds[0] = 11;
+    DimLoResults *none = dim_lo_run(ctx);
+    CHECK(none == NULL);
+    const char *error = dim_lo_error(ctx);
+    CHECK(error != NULL && strstr(error, "lower bound") != NULL);
+    args->bounds.lower_bounds[0] = 0;
+
+    args->any_exotic = true;
+    DimLoResults *exotic = dim_lo_run(ctx);
+    CHECK(exotic != NULL);
+    CHECK(dim_lo_results_info(exotic)->num_valid_sets < info->num_valid_sets);
+    CHECK(dim_lo_results_sets(exotic)[0].items[0] == 0);
+    CHECK(sets[0].power == 1800);
+    dim_lo_free_results(exotic);
+
+    /* Invalid enums are caught instead of reaching the algorithm */
+    dim_lo_items(ctx)[4].energy_type = 17;
+    CHECK(dim_lo_run(ctx) == NULL);
+    error = dim_lo_error(ctx);
+    CHECK(error != NULL && strcmp(error, "invalid energy_type in item 4") == 0);
+    dim_lo_items(ctx)[4].energy_type = ENERGY_TYPE_ANY;
+
+    /* More items than the context has room for */
+    dim_lo_num_items_per_bucket(ctx)[0] = ITEMS_PER_BUCKET + 1;
+    CHECK(dim_lo_run(ctx) == NULL);
+    CHECK(dim_lo_error(ctx) != NULL);
+
+    dim_lo_free(ctx, res);
+    dim_lo_free(NULL, NULL);
+
+    printf("ok\n");
+    return 0;
+}
@@ -0,0 +1,59 @@
+//! Builds `lifecycle.c` against the header and the static library and runs it.
+//! Uses the C compiler in `CC`, `cc` by default.
+//!
+//! `cargo test` doesn't build the static library, so the test builds it with the same
+//! profile. That happens in a target directory of its own, since the one of the tests is
+//! locked while they run.
+
+#![cfg(unix)]
+
+use std::{env, path::Path, process::Command};
+
+#[test]
+fn c_lifecycle() {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let tmp_dir = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let out = tmp_dir.join("lifecycle");
+
+    let target_dir = tmp_dir.join("lo-ffi-target");
+    let release = !cfg!(debug_assertions);
+    let mut cargo = Command::new(env!("CARGO"));
+    cargo
+        .args([
+            "build",
+            "--quiet",
+            "--package",
+            "lo-ffi",
+            "--lib",
+            "--target-dir",
+        ])
+        .arg(&target_dir)
+        .current_dir(crate_dir);
+    if release {
+        cargo.arg("--release");
+    }
+    let status = cargo.status().expect("failed to call cargo");
+    assert!(status.success(), "failed to build the static library");
+    let lib_dir = target_dir.join(if release { "release" } else { "debug" });
+
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_owned());
+    let status = Command::new(&cc)
+        .args(["-std=c11", "-Wall", "-Wextra", "-Werror", "-o"])
+        .arg(&out)
+        .arg("-I")
+        .arg(crate_dir.join("include"))
+        .arg(crate_dir.join("tests/lifecycle.c"))
+        .arg(lib_dir.join("libdim_lo.a"))
+        .args(["-lpthread", "-ldl", "-lm"])
+        .status()
+        .unwrap_or_else(|e| panic!("failed to call {cc}: {e}"));
+    assert!(status.success(), "failed to build lifecycle.c");
+
+    let output = Command::new(&out).output().unwrap();
+    assert!(
+        output.status.success(),
+        "lifecycle.c failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
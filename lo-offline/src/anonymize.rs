@@ -0,0 +1,226 @@
+//! `lo-offline anonymize`: rewrites an input so it can be shared in a bug report. Item ids
+//! and names are replaced by pseudonyms, and only what LO uses is kept (see `from_input`).
+
+use std::hash::{BuildHasher, RandomState};
+
+use dim_lo_core::input::ProcessInput;
+
+use crate::{
+    export::{self, DimExport},
+    output::{self, ExportRefs, BUCKET_NAMES},
+};
+
+/// The key of the pseudonyms. By default it's fixed, so that anonymizing an input twice
+/// gives the same export. Item ids are public though, so anyone who knows an account's ids
+/// could recognize its exports by their pseudonyms under that key. A key from `--salt` is
+/// as hard to guess as the salt and gives the same pseudonyms in every run with it, e.g.
+/// for several reports about one inventory. With `--random-key`, the pseudonyms of two
+/// runs can't be linked to each other or to the ids at all.
+pub struct PseudonymKey([u64; 2]);
+
+impl PseudonymKey {
+    pub fn fixed() -> Self {
+        Self::from_salt("")
+    }
+
+    pub fn random() -> Self {
+        let state = RandomState::new();
+        PseudonymKey([state.hash_one(0u8), state.hash_one(1u8)])
+    }
+
+    pub fn from_salt(salt: &str) -> Self {
+        PseudonymKey([0, 1].map(|k1| siphash([0, k1], salt.as_bytes())))
+    }
+
+    /// The pseudonym of an item id: its SipHash-2-4 under this key, in hex.
+    fn pseudonym(&self, id: &str) -> String {
+        format!("{:016x}", siphash(self.0, id.as_bytes()))
+    }
+}
+
+/// SipHash-2-4, written out since the one in std is deprecated and `DefaultHasher` may
+/// change between Rust versions, which would change the pseudonyms for a salt.
+fn siphash(key: [u64; 2], data: &[u8]) -> u64 {
+    fn round(v: &mut [u64; 4]) {
+        v[0] = v[0].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(13) ^ v[0];
+        v[0] = v[0].rotate_left(32);
+        v[2] = v[2].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(16) ^ v[2];
+        v[0] = v[0].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(21) ^ v[0];
+        v[2] = v[2].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(17) ^ v[2];
+        v[2] = v[2].rotate_left(32);
+    }
+
+    let mut v = [
+        key[0] ^ 0x736f_6d65_7073_6575,
+        key[1] ^ 0x646f_7261_6e64_6f6d,
+        key[0] ^ 0x6c79_6765_6e65_7261,
+        key[1] ^ 0x7465_6462_7974_6573,
+    ];
+    let mut compress = |m: u64| {
+        v[3] ^= m;
+        round(&mut v);
+        round(&mut v);
+        v[0] ^= m;
+    };
+    let mut chunks = data.chunks_exact(8);
+    for chunk in &mut chunks {
+        compress(u64::from_le_bytes(chunk.try_into().unwrap()));
+    }
+    let mut last = [0; 8];
+    last[..chunks.remainder().len()].copy_from_slice(chunks.remainder());
+    last[7] = data.len() as u8;
+    compress(u64::from_le_bytes(last));
+
+    v[2] ^= 0xff;
+    for _ in 0..4 {
+        round(&mut v);
+    }
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
+
+/// `refs` with pseudonyms for every item, named after their bucket and pseudonym.
+fn anonymous_refs(input: &ProcessInput, refs: &ExportRefs, key: &PseudonymKey) -> ExportRefs {
+    let num_ids = input
+        .items
+        .iter()
+        .flatten()
+        .map(|item| item.id as usize + 1)
+        .max()
+        .unwrap_or(0);
+    let mut items = vec![(String::new(), String::new()); num_ids];
+    for (bucket, list) in input.items.iter().enumerate() {
+        for item in list {
+            let id = key.pseudonym(&refs.item_id(item.id));
+            let name = format!("{} {}", BUCKET_NAMES[bucket], &id[..6]);
+            items[item.id as usize] = (name, id);
+        }
+    }
+    ExportRefs {
+        items,
+        set_hashes: refs.set_hashes.clone(),
+        tags: refs.tags.clone(),
+    }
+}
+
+pub fn anonymize(input: &ProcessInput, refs: &ExportRefs, key: &PseudonymKey) -> DimExport {
+    export::from_input(input, &anonymous_refs(input, refs, key))
+}
+
+/// Checks that `anonymized`, written out and read back, gives the same results as `input`,
+/// down to the order of the sets.
+pub fn verify(
+    input: &ProcessInput,
+    refs: &ExportRefs,
+    key: &PseudonymKey,
+    anonymized: &DimExport,
+) -> Result<(), String> {
+    let bytes = serde_json::to_vec(anonymized).map_err(|e| e.to_string())?;
+    let reparsed = export::parse_export(&bytes).map_err(|e| e.to_string())?;
+    let (anonymized_input, anonymized_refs) = export::map_export(&reparsed);
+
+    // Both with pseudonyms, since LO ids may be renumbered
+    let expected = output::results_json(input, &anonymous_refs(input, refs, key))?;
+    let actual = output::results_json(&anonymized_input, &anonymized_refs)?;
+    match expected
+        .lines()
+        .zip(actual.lines())
+        .position(|(e, a)| e != a)
+    {
+        Some(line) => Err(format!(
+            "results differ at line {} of the JSON results",
+            line + 1
+        )),
+        None if expected.len() != actual.len() => Err("results differ in length".to_owned()),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dim_lo_core::{
+        generate::{self, GenerateOptions},
+        types::{NUM_ITEM_BUCKETS, NUM_STATS},
+    };
+
+    use super::*;
+
+    /// A generated input with made-up DIM ids and names.
+    fn sample_input() -> (DimExport, ProcessInput, ExportRefs) {
+        let input = generate::generate(&GenerateOptions {
+            items_per_bucket: [3; NUM_ITEM_BUCKETS],
+            ..Default::default()
+        });
+        let refs = ExportRefs {
+            items: (0..3 * NUM_ITEM_BUCKETS)
+                .map(|id| (format!("Armor named {id}"), format!("6917529{id:012}")))
+                .collect(),
+            ..Default::default()
+        };
+        (export::from_input(&input, &refs), input, refs)
+    }
+
+    fn to_json(dim_export: &DimExport) -> String {
+        serde_json::to_string(dim_export).unwrap()
+    }
+
+    #[test]
+    fn same_key_same_export() {
+        let (_, input, refs) = sample_input();
+        let anonymized = |key| to_json(&anonymize(&input, &refs, &key));
+
+        assert_eq!(
+            anonymized(PseudonymKey::fixed()),
+            anonymized(PseudonymKey::fixed())
+        );
+        let salted = anonymized(PseudonymKey::from_salt("salt"));
+        assert_eq!(salted, anonymized(PseudonymKey::from_salt("salt")));
+        assert_ne!(salted, anonymized(PseudonymKey::from_salt("pepper")));
+        assert_ne!(salted, anonymized(PseudonymKey::fixed()));
+    }
+
+    #[test]
+    fn ids_and_names_replaced() {
+        let (dim_export, input, refs) = sample_input();
+        let anonymized = anonymize(&input, &refs, &PseudonymKey::from_salt("salt"));
+        let json = to_json(&anonymized);
+
+        let original = dim_export.filtered_items.iter().flatten();
+        let anonymous = anonymized.filtered_items.iter().flatten();
+        assert_eq!(original.clone().count(), anonymous.clone().count());
+        for item in original {
+            assert!(!json.contains(&item.id), "{} is still there", item.id);
+            assert!(!json.contains(&item.name), "{} is still there", item.name);
+        }
+        for (bucket, items) in anonymized.filtered_items.iter().enumerate() {
+            for item in items {
+                assert_eq!(item.id.len(), 16);
+                assert_eq!(
+                    item.name,
+                    format!("{} {}", BUCKET_NAMES[bucket], &item.id[..6])
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn verify_reports_mismatch() {
+        let (_, input, refs) = sample_input();
+        let key = PseudonymKey::from_salt("salt");
+        let mut anonymized = anonymize(&input, &refs, &key);
+        assert_eq!(verify(&input, &refs, &key, &anonymized), Ok(()));
+
+        // Verified against pseudonyms under the same key only
+        let other_key = PseudonymKey::from_salt("pepper");
+        assert!(verify(&input, &refs, &other_key, &anonymized).is_err());
+
+        for item in &mut anonymized.filtered_items[0] {
+            item.stats = [0; NUM_STATS];
+        }
+        let e = verify(&input, &refs, &key, &anonymized).unwrap_err();
+        assert!(e.starts_with("results differ"), "{e}");
+    }
+}
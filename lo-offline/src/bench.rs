@@ -0,0 +1,204 @@
+//! `lo-offline bench`: times repeated runs of the algorithm on one input, split into the
+//! setup (mostly `generate_mods_options`) and the main loop over item combinations.
+
+use std::{
+    hint::black_box,
+    io,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+use dim_lo_core::{error::ProcessError, input::ProcessInput};
+
+/// Timings of one phase over all measured runs, sorted.
+struct Samples(Vec<Duration>);
+
+impl Samples {
+    fn new(mut samples: Vec<Duration>) -> Self {
+        samples.sort_unstable();
+        Self(samples)
+    }
+
+    /// Nearest-rank percentile.
+    fn percentile(&self, p: usize) -> Duration {
+        let rank = (self.0.len() * p).div_ceil(100).max(1);
+        self.0[rank - 1]
+    }
+
+    fn min(&self) -> Duration {
+        self.0[0]
+    }
+
+    fn median(&self) -> Duration {
+        self.percentile(50)
+    }
+
+    fn p95(&self) -> Duration {
+        self.percentile(95)
+    }
+}
+
+pub struct BenchResults {
+    runs: usize,
+    warmup: usize,
+    /// Item combinations per run, i.e. the size of the search space.
+    combinations: u64,
+    num_valid_sets: u32,
+    setup: Samples,
+    main_loop: Samples,
+    total: Samples,
+}
+
+impl BenchResults {
+    fn combinations_per_sec(&self) -> f64 {
+        self.combinations as f64 / self.total.median().as_secs_f64()
+    }
+
+    /// How much of the median run is setup.
+    fn setup_share(&self) -> f64 {
+        self.setup.median().as_secs_f64() / self.total.median().as_secs_f64()
+    }
+}
+
+/// Runs the algorithm `warmup` times, then `runs` more times measuring each phase.
+pub fn bench(
+    input: &ProcessInput,
+    runs: usize,
+    warmup: usize,
+) -> Result<BenchResults, ProcessError> {
+    let mut setup = vec![];
+    let mut main_loop = vec![];
+    let mut total = vec![];
+    let mut num_valid_sets = 0;
+    for i in 0..warmup + runs {
+        let start = Instant::now();
+        let mut run = input.start()?;
+        let setup_done = Instant::now();
+        while !run.step(u32::MAX) {}
+        let done = Instant::now();
+        num_valid_sets = black_box(run.into_results()).0.num_valid_sets;
+
+        if i >= warmup {
+            setup.push(setup_done - start);
+            main_loop.push(done - setup_done);
+            total.push(done - start);
+        }
+    }
+    Ok(BenchResults {
+        runs,
+        warmup,
+        combinations: input.num_items().iter().map(|&n| n as u64).product(),
+        num_valid_sets,
+        setup: Samples::new(setup),
+        main_loop: Samples::new(main_loop),
+        total: Samples::new(total),
+    })
+}
+
+fn millis(d: Duration) -> String {
+    format!("{:.3}ms", d.as_secs_f64() * 1000.0)
+}
+
+pub fn print_table(results: &BenchResults) {
+    println!(
+        "Bench: {} runs after {} warm-up runs, {} combinations, {} valid sets\n",
+        results.runs, results.warmup, results.combinations, results.num_valid_sets
+    );
+    println!("{:<12}{:>14}{:>14}{:>14}", "", "min", "median", "p95");
+    for (name, samples) in [
+        ("Setup", &results.setup),
+        ("Main Loop", &results.main_loop),
+        ("Total", &results.total),
+    ] {
+        println!(
+            "{name:<12}{:>14}{:>14}{:>14}",
+            millis(samples.min()),
+            millis(samples.median()),
+            millis(samples.p95())
+        );
+    }
+    println!(
+        "\nCombinations/s: {:.0} (median)\nSetup Share: {:.1}% (median)",
+        results.combinations_per_sec(),
+        results.setup_share() * 100.0
+    );
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonTimings {
+    min_ns: u64,
+    median_ns: u64,
+    p95_ns: u64,
+}
+
+impl From<&Samples> for JsonTimings {
+    fn from(samples: &Samples) -> Self {
+        Self {
+            min_ns: samples.min().as_nanos() as u64,
+            median_ns: samples.median().as_nanos() as u64,
+            p95_ns: samples.p95().as_nanos() as u64,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonBench {
+    runs: usize,
+    warmup: usize,
+    combinations: u64,
+    num_valid_sets: u32,
+    setup: JsonTimings,
+    main_loop: JsonTimings,
+    total: JsonTimings,
+    combinations_per_sec: f64,
+    setup_share: f64,
+}
+
+pub fn write_json(results: &BenchResults, mut out: impl io::Write) -> io::Result<()> {
+    let json = JsonBench {
+        runs: results.runs,
+        warmup: results.warmup,
+        combinations: results.combinations,
+        num_valid_sets: results.num_valid_sets,
+        setup: (&results.setup).into(),
+        main_loop: (&results.main_loop).into(),
+        total: (&results.total).into(),
+        combinations_per_sec: results.combinations_per_sec(),
+        setup_share: results.setup_share(),
+    };
+    serde_json::to_writer_pretty(&mut out, &json)?;
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(millis: impl IntoIterator<Item = u64>) -> Samples {
+        Samples::new(millis.into_iter().map(Duration::from_millis).collect())
+    }
+
+    #[test]
+    fn percentiles() {
+        // Out of order, like measured
+        let s = samples([7, 3, 9, 1, 5, 2, 8, 10, 4, 6]);
+        assert_eq!(s.min(), Duration::from_millis(1));
+        assert_eq!(s.median(), Duration::from_millis(5));
+        assert_eq!(s.p95(), Duration::from_millis(10));
+        assert_eq!(s.percentile(0), Duration::from_millis(1));
+        assert_eq!(s.percentile(10), Duration::from_millis(1));
+        assert_eq!(s.percentile(11), Duration::from_millis(2));
+        assert_eq!(s.percentile(100), Duration::from_millis(10));
+
+        let s = samples(1..=20);
+        assert_eq!(s.median(), Duration::from_millis(10));
+        assert_eq!(s.p95(), Duration::from_millis(19));
+
+        // A single run is every percentile
+        let s = samples([4]);
+        assert_eq!((s.min(), s.median(), s.p95()), (s.0[0], s.0[0], s.0[0]));
+    }
+}
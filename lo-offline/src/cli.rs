@@ -0,0 +1,732 @@
+//! Command line parsing, including overrides for the settings of the run.
+
+use std::{mem, num::NonZeroU32};
+
+use dim_lo_core::{
+    error::{ModList, MAX_ENERGY},
+    generate::GenerateOptions,
+    input::ProcessInput,
+    types::{EnergyType, ProcessMod, NUM_ITEM_BUCKETS, NUM_STATS},
+};
+
+use crate::{
+    minimize::Predicate,
+    output::{ExportRefs, STAT_NAMES},
+};
+
+pub const USAGE: &str = "\
+usage: lo-offline [options] <export.json | packed input | dump>
+       lo-offline bench [--runs N] [--warmup N] [options] <input>
+       lo-offline generate [generate options] [output.json]
+       lo-offline minimize --predicate PREDICATE [--output FILE] [options] <input>
+       lo-offline anonymize [--salt TEXT | --random-key] [--output FILE] [options] <input>
+       lo-offline regression [--name NAME] [--top N] [--output FILE] [options] <input>
+       lo-offline golden [--update] <directory>
+
+options:
+  --format table|json          print a table (default) or the full results as JSON
+  --top N                      number of sets to print (default 10 for tables, all for JSON)
+  --min STAT=N, --max STAT=N   override a stat's bounds, in tiers or points like the export
+  --ignore STAT                ignore a stat, i.e. allow any value
+  --any-exotic yes|no          whether sets must have an exotic
+  --auto-mods N                number of auto stat mods LO may add, 0-5 (default 5)
+  --lock-mod LIST:HASH:COST[:TAG]
+                               lock a mod in the general, combat or activity list
+  --unlock-mod HASH            remove a mod locked in the input
+
+bench options:
+  --runs N                     number of measured runs (default 20)
+  --warmup N                   number of runs before measuring (default 3)
+
+generate options (writes a synthetic export, to stdout without an output path):
+  --seed N                     seed of the generator (default 0)
+  --items N|H,G,C,L,CL         items per bucket (default 20)
+  --exotics PERCENT            chance of an item being exotic (default 10)
+  --stat-total MIN-MAX         range of an item's total base stats (default 52-68)
+  --energy MIN-MAX             range of item energy capacities (default 7-10)
+  --elements yes|no            whether items and mods have elements (default no)
+  --mod-tags N                 number of mod tags for items and activity mods (default 0)
+  --locked-mods G,C,A          number of locked general, combat and activity mods (default 0)
+
+minimize options (writes the smallest export found, to stdout without --output):
+  --predicate missing:ID,ID,ID,ID,ID
+                               the set with these item ids is valid by the oracle,
+                               but not in the results
+  --predicate oracle           LO and the oracle disagree on whether some set is valid
+                               (only feasible for small exports)
+  --predicate panics           LO panics
+  --output FILE                where to write the minimized export
+
+anonymize options (writes the anonymized export if it gives the same results):
+  --salt TEXT                  key the pseudonyms with this secret instead of a fixed key, so
+                               that only those who know it can link them to the item ids
+  --random-key                 key the pseudonyms with a random key for each run
+  --output FILE                where to write it instead of stdout
+
+regression options (writes a lo-core test module pinning the current results):
+  --name NAME                  the test is called check_NAME (default: the input's file name)
+  --top N                      number of sets to check in detail (default 10)
+  --output FILE                where to write it instead of stdout
+
+golden options (runs every NAME.json in the directory and compares with NAME.expected.json):
+  --update                     rewrite the expected results instead, if a change is intended
+
+STAT is a stat name (mob, res, rec, dis, int, str) or index (0-5).";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+}
+
+pub enum Command {
+    /// Run once and print the results.
+    Run,
+    /// Time `runs` runs after `warmup` runs, see `bench`.
+    Bench { runs: usize, warmup: usize },
+    /// Write a synthetic export.
+    Generate(GenerateOptions),
+    /// Shrink the input while `predicate` holds, see `minimize`.
+    Minimize {
+        predicate: Option<Predicate>,
+        output: Option<String>,
+    },
+    /// Replace item ids and names by pseudonyms, see `anonymize`.
+    Anonymize {
+        output: Option<String>,
+        /// Keys the pseudonyms instead of the fixed key, see `anonymize::PseudonymKey`.
+        salt: Option<String>,
+        /// Keys the pseudonyms with a random key instead of the fixed key.
+        random_key: bool,
+    },
+    /// Write a regression test for lo-core, see `regression`.
+    Regression {
+        name: Option<String>,
+        output: Option<String>,
+    },
+    /// Compare the results of every export in a directory with the expected ones,
+    /// see `golden`.
+    Golden { update: bool },
+}
+
+pub struct Options {
+    pub command: Command,
+    /// The input, the output of `generate`, which is the only command without one,
+    /// or the directory for `golden`.
+    pub path: Option<String>,
+    pub format: Format,
+    /// How many sets to print, all by default for JSON.
+    pub top: Option<usize>,
+    pub overrides: Overrides,
+}
+
+/// Changes to the settings of the input, applied in the order of the fields.
+#[derive(Default)]
+pub struct Overrides {
+    min: Vec<(usize, u16)>,
+    max: Vec<(usize, u16)>,
+    ignore: Vec<usize>,
+    any_exotic: Option<bool>,
+    auto_mods: Option<u8>,
+    unlock_mods: Vec<NonZeroU32>,
+    lock_mods: Vec<LockMod>,
+}
+
+struct LockMod {
+    list: ModList,
+    hash: NonZeroU32,
+    cost: u8,
+    /// A mod tag (e.g. a mod season) from the export.
+    tag: Option<String>,
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, what: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid {what} {value}"))
+}
+
+fn parse_stat(value: &str) -> Result<usize, String> {
+    STAT_NAMES
+        .iter()
+        .position(|n| n.eq_ignore_ascii_case(value))
+        .or_else(|| value.parse().ok().filter(|&idx| idx < NUM_STATS))
+        .ok_or(format!("unknown stat {value}"))
+}
+
+/// Parses `STAT=N`.
+fn parse_bound(value: &str) -> Result<(usize, u16), String> {
+    let (stat, bound) = value
+        .split_once('=')
+        .ok_or(format!("expected STAT=N, got {value}"))?;
+    Ok((parse_stat(stat)?, parse_number(bound, "bound")?))
+}
+
+/// Parses `LIST:HASH:COST[:TAG]`.
+fn parse_lock_mod(value: &str) -> Result<LockMod, String> {
+    let mut parts = value.splitn(4, ':');
+    let list = match parts.next().unwrap() {
+        "general" => ModList::General,
+        "combat" => ModList::Combat,
+        "activity" => ModList::Activity,
+        other => return Err(format!("unknown mod list {other}")),
+    };
+    let (Some(hash), Some(cost)) = (parts.next(), parts.next()) else {
+        return Err(format!("expected LIST:HASH:COST[:TAG], got {value}"));
+    };
+    Ok(LockMod {
+        list,
+        hash: parse_number(hash, "mod hash")?,
+        cost: parse_number(cost, "mod cost")?,
+        tag: parts.next().map(str::to_owned),
+    })
+}
+
+fn parse_yes_no(value: &str) -> Result<bool, String> {
+    match value {
+        "yes" => Ok(true),
+        "no" => Ok(false),
+        other => Err(format!("expected yes or no, got {other}")),
+    }
+}
+
+/// Parses `MIN-MAX` or just `N`.
+fn parse_range<T: std::str::FromStr + Copy + PartialOrd>(
+    value: &str,
+    what: &str,
+) -> Result<(T, T), String> {
+    let (min, max) = value.split_once('-').unwrap_or((value, value));
+    let range = (parse_number(min, what)?, parse_number(max, what)?);
+    if range.0 > range.1 {
+        return Err(format!("invalid {what} range {value}"));
+    }
+    Ok(range)
+}
+
+/// Parses `N` for all `N` values, or `N` comma-separated values.
+fn parse_list<T: std::str::FromStr + Copy, const N: usize>(
+    value: &str,
+    what: &str,
+) -> Result<[T; N], String> {
+    let values = value
+        .split(',')
+        .map(|v| parse_number(v, what))
+        .collect::<Result<Vec<T>, _>>()?;
+    match values[..] {
+        [one] => Ok([one; N]),
+        _ => values
+            .try_into()
+            .map_err(|_| format!("expected 1 or {N} values, got {value}")),
+    }
+}
+
+/// Parses `missing:ID,ID,ID,ID,ID`, `oracle` or `panics`.
+fn parse_predicate(value: &str) -> Result<Predicate, String> {
+    match value.split_once(':') {
+        Some(("missing", ids)) => {
+            let ids = ids.split(',').map(str::to_owned).collect::<Vec<_>>();
+            let ids = ids
+                .try_into()
+                .map_err(|_| format!("expected {NUM_ITEM_BUCKETS} item ids, got {value}"))?;
+            Ok(Predicate::Missing(ids))
+        }
+        None if value == "oracle" => Ok(Predicate::Oracle),
+        None if value == "panics" => Ok(Predicate::Panics),
+        _ => Err(format!("unknown predicate {value}")),
+    }
+}
+
+/// Handles the options of `generate`, which has none of the others.
+fn parse_generate_option(
+    options: &mut GenerateOptions,
+    arg: &str,
+    value: String,
+) -> Result<(), String> {
+    match arg {
+        "--seed" => options.seed = parse_number(&value, "seed")?,
+        "--items" => {
+            options.items_per_bucket = parse_list(&value, "number of items")?;
+            // Items are numbered across buckets
+            if options
+                .items_per_bucket
+                .iter()
+                .map(|&n| n as u32)
+                .sum::<u32>()
+                > 1 << 16
+            {
+                return Err("at most 65536 items in total".to_owned());
+            }
+        }
+        "--exotics" => {
+            options.exotic_percent = parse_number(&value, "exotic chance")?;
+            if options.exotic_percent > 100 {
+                return Err(format!("invalid exotic chance {value}"));
+            }
+        }
+        "--stat-total" => options.stat_total = parse_range(&value, "stat total")?,
+        "--energy" => {
+            options.energy_cap = parse_range(&value, "energy")?;
+            if options.energy_cap.1 > MAX_ENERGY {
+                return Err(format!("energy can't be more than {MAX_ENERGY}"));
+            }
+        }
+        "--elements" => options.elements = parse_yes_no(&value)?,
+        "--mod-tags" => {
+            options.mod_tags = parse_number(&value, "number of mod tags")?;
+            if options.mod_tags > 32 {
+                return Err("at most 32 mod tags".to_owned());
+            }
+        }
+        "--locked-mods" => {
+            options.locked_mods = parse_list(&value, "number of mods")?;
+            if options
+                .locked_mods
+                .iter()
+                .any(|&n| n as usize > NUM_ITEM_BUCKETS)
+            {
+                return Err(format!("at most {NUM_ITEM_BUCKETS} mods per list"));
+            }
+        }
+        _ => return Err(format!("unknown option {arg} for generate")),
+    }
+    Ok(())
+}
+
+pub fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut args = args.peekable();
+    let mut command = match args.peek().map(String::as_str) {
+        Some("bench") => {
+            args.next();
+            Command::Bench {
+                runs: 20,
+                warmup: 3,
+            }
+        }
+        Some("generate") => {
+            args.next();
+            Command::Generate(GenerateOptions::default())
+        }
+        Some("minimize") => {
+            args.next();
+            Command::Minimize {
+                predicate: None,
+                output: None,
+            }
+        }
+        Some("anonymize") => {
+            args.next();
+            Command::Anonymize {
+                output: None,
+                salt: None,
+                random_key: false,
+            }
+        }
+        Some("regression") => {
+            args.next();
+            Command::Regression {
+                name: None,
+                output: None,
+            }
+        }
+        Some("golden") => {
+            args.next();
+            Command::Golden { update: false }
+        }
+        _ => Command::Run,
+    };
+    let mut path = None;
+    let mut format = Format::Table;
+    let mut top = None;
+    let mut overrides = Overrides::default();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+        if let Command::Generate(options) = &mut command {
+            if arg.starts_with("--") {
+                parse_generate_option(options, &arg, value()?)?;
+                continue;
+            }
+        }
+        match arg.as_str() {
+            "--format" | "--top" | "--min" | "--max" | "--ignore" | "--any-exotic"
+            | "--auto-mods" | "--lock-mod" | "--unlock-mod"
+                if matches!(command, Command::Golden { .. }) =>
+            {
+                return Err(format!(
+                    "{arg} is not supported by golden, its exports hold their own settings"
+                ));
+            }
+            "--format" => {
+                format = match value()?.as_str() {
+                    "table" => Format::Table,
+                    "json" => Format::Json,
+                    other => return Err(format!("unknown format {other}")),
+                }
+            }
+            "--runs" | "--warmup" => {
+                let Command::Bench { runs, warmup } = &mut command else {
+                    return Err(format!("{arg} is only supported by bench"));
+                };
+                let n = parse_number(&value()?, "number of runs")?;
+                match arg.as_str() {
+                    "--runs" if n == 0 => return Err("bench needs at least one run".to_owned()),
+                    "--runs" => *runs = n,
+                    _ => *warmup = n,
+                }
+            }
+            "--predicate" => {
+                let Command::Minimize { predicate, .. } = &mut command else {
+                    return Err(format!("{arg} is only supported by minimize"));
+                };
+                *predicate = Some(parse_predicate(&value()?)?);
+            }
+            "--name" => {
+                let Command::Regression { name, .. } = &mut command else {
+                    return Err(format!("{arg} is only supported by regression"));
+                };
+                *name = Some(value()?);
+            }
+            "--salt" => {
+                let Command::Anonymize { salt, .. } = &mut command else {
+                    return Err(format!("{arg} is only supported by anonymize"));
+                };
+                *salt = Some(value()?);
+            }
+            "--random-key" => {
+                let Command::Anonymize { random_key, .. } = &mut command else {
+                    return Err(format!("{arg} is only supported by anonymize"));
+                };
+                *random_key = true;
+            }
+            "--update" => {
+                let Command::Golden { update } = &mut command else {
+                    return Err(format!("{arg} is only supported by golden"));
+                };
+                *update = true;
+            }
+            "--output" => match &mut command {
+                Command::Minimize { output, .. }
+                | Command::Anonymize { output, .. }
+                | Command::Regression { output, .. } => *output = Some(value()?),
+                _ => {
+                    return Err(format!(
+                        "{arg} is only supported by minimize, anonymize and regression"
+                    ))
+                }
+            },
+            "--top" => top = Some(parse_number(&value()?, "number of sets")?),
+            "--min" => overrides.min.push(parse_bound(&value()?)?),
+            "--max" => overrides.max.push(parse_bound(&value()?)?),
+            "--ignore" => overrides.ignore.push(parse_stat(&value()?)?),
+            "--any-exotic" => overrides.any_exotic = Some(parse_yes_no(&value()?)?),
+            "--auto-mods" => {
+                let auto_mods = parse_number(&value()?, "number of auto mods")?;
+                if auto_mods as usize > NUM_ITEM_BUCKETS {
+                    return Err(format!("at most {NUM_ITEM_BUCKETS} auto mods"));
+                }
+                overrides.auto_mods = Some(auto_mods);
+            }
+            "--lock-mod" => overrides.lock_mods.push(parse_lock_mod(&value()?)?),
+            "--unlock-mod" => overrides
+                .unlock_mods
+                .push(parse_number(&value()?, "mod hash")?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+            _ if path.is_none() => path = Some(arg),
+            _ => return Err(format!("unexpected argument {arg}")),
+        }
+    }
+    if path.is_none() && !matches!(command, Command::Generate(_)) {
+        return Err("missing input path".to_owned());
+    }
+    if let Command::Minimize {
+        predicate: None, ..
+    } = command
+    {
+        return Err("minimize needs a --predicate".to_owned());
+    }
+    if let Command::Anonymize {
+        salt: Some(_),
+        random_key: true,
+        ..
+    } = command
+    {
+        return Err("--salt and --random-key can't be combined".to_owned());
+    }
+    Ok(Options {
+        command,
+        path,
+        format,
+        top,
+        overrides,
+    })
+}
+
+fn mod_list(input: &mut ProcessInput, list: ModList) -> &mut [ProcessMod; NUM_ITEM_BUCKETS] {
+    match list {
+        ModList::General => &mut input.general_mods,
+        ModList::Combat => &mut input.combat_mods,
+        ModList::Activity => &mut input.activity_mods,
+    }
+}
+
+/// Removes every mod with `hash` from `mods`, keeping the others packed at the start.
+/// Returns whether there was one.
+fn unlock(mods: &mut [ProcessMod; NUM_ITEM_BUCKETS], hash: NonZeroU32) -> bool {
+    let mut found = false;
+    let mut kept = 0;
+    for idx in 0..mods.len() {
+        let m = mem::replace(&mut mods[idx], crate::export::EMPTY_MOD);
+        if m.hash == Some(hash) {
+            found = true;
+        } else if m.hash.is_some() {
+            mods[kept] = m;
+            kept += 1;
+        }
+    }
+    found
+}
+
+impl Overrides {
+    pub fn apply(&self, input: &mut ProcessInput, refs: &ExportRefs) -> Result<(), String> {
+        let args = &mut input.args;
+        for &(stat, min) in &self.min {
+            args.bounds.lower_bounds[stat] = min;
+        }
+        for &(stat, max) in &self.max {
+            args.bounds.upper_bounds[stat] = max;
+        }
+        let caps = args.effective_stat_caps();
+        for &stat in &self.ignore {
+            args.bounds.lower_bounds[stat] = 0;
+            args.bounds.upper_bounds[stat] = caps[stat] / args.points_per_unit();
+        }
+        if let Some(any_exotic) = self.any_exotic {
+            args.any_exotic = any_exotic;
+        }
+        if let Some(auto_mods) = self.auto_mods {
+            args.auto_mods = auto_mods;
+        }
+
+        for &hash in &self.unlock_mods {
+            let mut found = false;
+            for list in [ModList::General, ModList::Combat, ModList::Activity] {
+                found |= unlock(mod_list(input, list), hash);
+            }
+            if !found {
+                return Err(format!("mod {hash} isn't locked"));
+            }
+        }
+
+        for m in &self.lock_mods {
+            let mod_tag = match &m.tag {
+                Some(tag) => {
+                    let idx = refs
+                        .tags
+                        .iter()
+                        .position(|t| t == tag)
+                        .ok_or(format!("unknown mod tag {tag}"))?;
+                    NonZeroU32::new(1 << idx)
+                }
+                None => None,
+            };
+            let slot = mod_list(input, m.list)
+                .iter_mut()
+                .find(|slot| slot.hash.is_none())
+                .ok_or(format!("no free {} mod slot for mod {}", m.list, m.hash))?;
+            *slot = ProcessMod {
+                hash: Some(m.hash),
+                mod_tag,
+                energy_type: EnergyType::Any,
+                energy_val: m.cost,
+            };
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dim_lo_core::types::StatRuleset;
+
+    use super::*;
+    use crate::export::EMPTY_MOD;
+
+    fn parse(args: &str) -> Result<Options, String> {
+        parse_args(args.split_whitespace().map(str::to_owned))
+    }
+
+    fn parse_err(args: &str) -> String {
+        parse(args).err().expect("the arguments were accepted")
+    }
+
+    fn overrides(args: &str) -> Overrides {
+        parse(&format!("{args} export.json")).unwrap().overrides
+    }
+
+    /// An input without items, with general mods 1, 2 and 3 locked.
+    fn sample_input() -> ProcessInput {
+        let mut input = ProcessInput {
+            items: Default::default(),
+            general_mods: [EMPTY_MOD; NUM_ITEM_BUCKETS],
+            combat_mods: [EMPTY_MOD; NUM_ITEM_BUCKETS],
+            activity_mods: [EMPTY_MOD; NUM_ITEM_BUCKETS],
+            auto_mods: vec![],
+            args: Default::default(),
+        };
+        for (idx, slot) in input.general_mods.iter_mut().take(3).enumerate() {
+            *slot = ProcessMod {
+                hash: NonZeroU32::new(idx as u32 + 1),
+                energy_val: 1,
+                ..EMPTY_MOD
+            };
+        }
+        input
+    }
+
+    fn hashes(mods: &[ProcessMod; NUM_ITEM_BUCKETS]) -> [u32; NUM_ITEM_BUCKETS] {
+        mods.each_ref().map(|m| m.hash.map_or(0, NonZeroU32::get))
+    }
+
+    #[test]
+    fn run_options() {
+        assert_eq!(parse_err("--top"), "missing value for --top");
+        assert_eq!(parse_err("--top 3"), "missing input path");
+        assert_eq!(parse_err("--format csv x"), "unknown format csv");
+        assert_eq!(parse_err("--frobnicate x"), "unknown option --frobnicate");
+        assert_eq!(parse_err("x y"), "unexpected argument y");
+    }
+
+    #[test]
+    fn subcommand_options() {
+        let command = parse("bench --runs 5 --warmup 0 export.json")
+            .unwrap()
+            .command;
+        assert!(matches!(command, Command::Bench { runs: 5, warmup: 0 }));
+        assert_eq!(
+            parse_err("bench --runs 0 x"),
+            "bench needs at least one run"
+        );
+        assert_eq!(parse_err("--runs 5 x"), "--runs is only supported by bench");
+        assert_eq!(
+            parse_err("generate --predicate oracle"),
+            "unknown option --predicate for generate"
+        );
+        assert_eq!(parse_err("minimize x"), "minimize needs a --predicate");
+        assert_eq!(
+            parse_err("minimize --salt s x"),
+            "--salt is only supported by anonymize"
+        );
+        assert_eq!(
+            parse_err("anonymize --salt s --random-key x"),
+            "--salt and --random-key can't be combined"
+        );
+        assert_eq!(
+            parse_err("bench --output out.json x"),
+            "--output is only supported by minimize, anonymize and regression"
+        );
+        assert_eq!(
+            parse_err("golden --min mob=3 goldens"),
+            "--min is not supported by golden, its exports hold their own settings"
+        );
+    }
+
+    #[test]
+    fn auto_mods_range() {
+        assert_eq!(overrides("--auto-mods 0").auto_mods, Some(0));
+        assert_eq!(overrides("--auto-mods 5").auto_mods, Some(5));
+        assert_eq!(parse_err("--auto-mods 6 x"), "at most 5 auto mods");
+        assert_eq!(
+            parse_err("--auto-mods -1 x"),
+            "invalid number of auto mods -1"
+        );
+    }
+
+    #[test]
+    fn lists_and_ranges() {
+        assert_eq!(parse_list::<u16, 5>("3", "n"), Ok([3; 5]));
+        assert_eq!(parse_list::<u16, 5>("1,2,3,4,5", "n"), Ok([1, 2, 3, 4, 5]));
+        assert_eq!(
+            parse_list::<u16, 5>("1,2", "n"),
+            Err("expected 1 or 5 values, got 1,2".to_owned())
+        );
+        assert_eq!(
+            parse_list::<u16, 5>("1,x", "n"),
+            Err("invalid n x".to_owned())
+        );
+
+        assert_eq!(parse_range::<u8>("52-68", "r"), Ok((52, 68)));
+        assert_eq!(parse_range::<u8>("7", "r"), Ok((7, 7)));
+        assert_eq!(
+            parse_range::<u8>("9-7", "r"),
+            Err("invalid r range 9-7".to_owned())
+        );
+        assert_eq!(parse_range::<u8>("1-", "r"), Err("invalid r ".to_owned()));
+    }
+
+    #[test]
+    fn unlock_repacks_mods() {
+        let mut input = sample_input();
+        overrides("--unlock-mod 2")
+            .apply(&mut input, &ExportRefs::default())
+            .unwrap();
+        assert_eq!(hashes(&input.general_mods), [1, 3, 0, 0, 0]);
+        assert_eq!(input.general_mods[2].energy_val, 0);
+
+        // Unlocking frees a slot for the mods locked after it
+        overrides("--unlock-mod 1 --lock-mod general:4:2")
+            .apply(&mut input, &ExportRefs::default())
+            .unwrap();
+        assert_eq!(hashes(&input.general_mods), [3, 4, 0, 0, 0]);
+
+        let e = overrides("--unlock-mod 9").apply(&mut input, &ExportRefs::default());
+        assert_eq!(e, Err("mod 9 isn't locked".to_owned()));
+    }
+
+    #[test]
+    fn lock_looks_up_tags() {
+        let refs = ExportRefs {
+            tags: vec!["tag0".to_owned(), "tag1".to_owned()],
+            ..Default::default()
+        };
+        let mut input = sample_input();
+        overrides("--lock-mod combat:5:3:tag1 --lock-mod activity:6:1")
+            .apply(&mut input, &refs)
+            .unwrap();
+        let (combat, activity) = (&input.combat_mods[0], &input.activity_mods[0]);
+        assert_eq!(
+            (combat.hash, combat.mod_tag),
+            (NonZeroU32::new(5), NonZeroU32::new(2))
+        );
+        assert_eq!(combat.energy_val, 3);
+        assert_eq!(
+            (activity.hash, activity.mod_tag),
+            (NonZeroU32::new(6), None)
+        );
+
+        let e = overrides("--lock-mod combat:5:3:tag2").apply(&mut sample_input(), &refs);
+        assert_eq!(e, Err("unknown mod tag tag2".to_owned()));
+        let e = overrides("--lock-mod general:7:1 --lock-mod general:8:1 --lock-mod general:9:1")
+            .apply(&mut sample_input(), &refs);
+        assert_eq!(e, Err("no free general mod slot for mod 9".to_owned()));
+    }
+
+    #[test]
+    fn ignore_uses_effective_caps() {
+        let mut input = sample_input();
+        overrides("--min mob=3 --max res=5 --ignore res --ignore 2")
+            .apply(&mut input, &ExportRefs::default())
+            .unwrap();
+        let bounds = &input.args.bounds;
+        assert_eq!(bounds.lower_bounds, [3, 0, 0, 0, 0, 0]);
+        assert_eq!(bounds.upper_bounds, [0, 10, 10, 0, 0, 0]);
+
+        // With stat points, up to the stat's cap
+        let mut input = sample_input();
+        input.args.ruleset = StatRuleset::Points;
+        input.args.stat_caps = [200, 150, 100, 100, 100, 100];
+        input.args.bounds.lower_bounds = [50; NUM_STATS];
+        overrides("--ignore mob --ignore Res")
+            .apply(&mut input, &ExportRefs::default())
+            .unwrap();
+        let bounds = &input.args.bounds;
+        assert_eq!(bounds.lower_bounds, [0, 0, 50, 50, 50, 50]);
+        assert_eq!(bounds.upper_bounds[..2], [200, 150]);
+    }
+}
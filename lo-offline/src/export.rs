@@ -0,0 +1,678 @@
+//! DIM exports. The current schema (below) is also what the rest of lo-offline works with,
+//! exports in older schemas are migrated to it when they are loaded, see `parse_export`.
+//!
+//! Schema history:
+//! * 1: Exports without a `schemaVersion`. Energy types are numbers, base stats are called
+//!   `modStatTotals` and mod tags `compatibleModSeasons`. Set bonuses, tuning, bonus stats,
+//!   exotic perks and stat points came later and may be missing.
+//! * 2: Energy types are names and optional, since armor energy no longer has elements.
+//!   `modStatTotals` is now `baseStats` and `compatibleModSeasons` is now `modTags`.
+
+mod v1;
+
+use std::{fmt, num::NonZeroU32};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use dim_lo_core::{
+    input::ProcessInput,
+    types::{
+        BonusStatMode, EnergyType, ProcessArgs, ProcessItem, ProcessMod, ProcessSetBonus,
+        ProcessStatMod, ProcessTierBounds, SignedStats, StatRuleset, Stats, NUM_ITEM_BUCKETS,
+        NUM_SET_BONUSES, NUM_STATS,
+    },
+};
+
+use crate::output::{ExportRefs, BUCKET_NAMES};
+
+/// The schema version lo-offline writes and works with.
+pub const SCHEMA_VERSION: u32 = 2;
+
+#[derive(Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DimEnergyType {
+    #[default]
+    Any,
+    Arc,
+    Solar,
+    Void,
+    Stasis,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DimItemEnergy {
+    #[serde(default)]
+    pub r#type: DimEnergyType,
+    pub capacity: u8,
+    pub val: u8,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DimModEnergy {
+    #[serde(default)]
+    pub r#type: DimEnergyType,
+    pub val: u8,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DimAutoStatMod {
+    pub hash: u32,
+    pub energy: DimModEnergy,
+    pub investment_stats: [u16; NUM_STATS],
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DimMod {
+    pub hash: u32,
+    pub energy: DimModEnergy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DimStatFilter {
+    pub min: u16,
+    pub max: u16,
+    pub ignored: bool,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DimItem {
+    pub is_exotic: bool,
+    pub power: u16,
+    pub id: String,
+    pub name: String,
+    pub stats: [u16; NUM_STATS],
+    pub energy: DimItemEnergy,
+    pub mod_tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bonus_stats: Option<DimBonusStats>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set_hash: Option<u32>,
+    /// Indices of the stats this item can be tuned into.
+    #[serde(default)]
+    pub tuning_stats: Vec<u8>,
+    /// Perk hashes of exotics with selectable perks, e.g. exotic class items.
+    #[serde(default)]
+    pub exotic_perks: Vec<u32>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DimBonusStats {
+    pub stats: [i16; NUM_STATS],
+    #[serde(default)]
+    pub conditional: bool,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DimSetBonus {
+    pub set_hash: u32,
+    pub count: u16,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DimLockedMods {
+    pub general_mods: Vec<DimMod>,
+    pub combat_mods: Vec<DimMod>,
+    pub activity_mods: Vec<DimMod>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DimExport {
+    pub schema_version: u32,
+    pub filtered_items: [Vec<DimItem>; NUM_ITEM_BUCKETS],
+    pub base_stats: [i16; NUM_STATS],
+    pub auto_stat_mods: Vec<DimAutoStatMod>,
+    pub locked_mods: DimLockedMods,
+    pub stat_filters: [DimStatFilter; NUM_STATS],
+    pub any_exotic: bool,
+    /// Present if the stat filters are in stat points instead of tiers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stat_caps: Option<[u16; NUM_STATS]>,
+    #[serde(default)]
+    pub set_bonuses: Vec<DimSetBonus>,
+    /// The exotic perks the user requires, empty if any exotic will do.
+    #[serde(default)]
+    pub required_exotic_perks: Vec<u32>,
+    /// How many auto stat mods LO may add, 5 if missing. DIM doesn't write it, lo-offline
+    /// does whenever it differs, so that minimized and anonymized exports keep it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_auto_stat_mods: Option<u8>,
+}
+
+/// Why an export couldn't be loaded, with the JSON path of the culprit.
+#[cfg_attr(test, derive(Debug))]
+pub struct ExportError {
+    /// E.g. `filteredItems[0][3].energy.type`, empty for the whole document.
+    path: String,
+    message: String,
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if matches!(self.path.as_str(), "" | "." | "?") {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "at {}: {}", self.path, self.message)
+        }
+    }
+}
+
+fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, ExportError> {
+    let mut de = serde_json::Deserializer::from_slice(bytes);
+    let value = serde_path_to_error::deserialize(&mut de).map_err(|e| ExportError {
+        path: e.path().to_string(),
+        message: e.into_inner().to_string(),
+    })?;
+    de.end().map_err(|e| ExportError {
+        path: String::new(),
+        message: e.to_string(),
+    })?;
+    Ok(value)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SchemaVersion {
+    #[serde(default)]
+    schema_version: Option<u32>,
+}
+
+/// Reads an export in any known schema version and migrates it to the current one.
+pub fn parse_export(bytes: &[u8]) -> Result<DimExport, ExportError> {
+    let SchemaVersion { schema_version } = deserialize(bytes)?;
+    match schema_version.unwrap_or(1) {
+        1 => deserialize::<v1::DimExport>(bytes).map(DimExport::from),
+        SCHEMA_VERSION => deserialize(bytes),
+        version => Err(ExportError {
+            path: "schemaVersion".to_owned(),
+            message: format!(
+                "unknown schema version {version}, expected 1 to {SCHEMA_VERSION} \
+                 (the export may be newer than lo-offline)"
+            ),
+        }),
+    }
+}
+
+fn map_energy(e: DimEnergyType) -> EnergyType {
+    match e {
+        DimEnergyType::Any => EnergyType::Any,
+        DimEnergyType::Arc => EnergyType::Arc,
+        DimEnergyType::Solar => EnergyType::Solar,
+        DimEnergyType::Void => EnergyType::Void,
+        DimEnergyType::Stasis => EnergyType::Stasis,
+    }
+}
+
+fn map_stat_mod(m: &DimAutoStatMod) -> ProcessStatMod {
+    ProcessStatMod {
+        inner_mod: ProcessMod {
+            hash: NonZeroU32::new(m.hash),
+            mod_tag: None,
+            energy_type: map_energy(m.energy.r#type),
+            energy_val: m.energy.val,
+        },
+        stats: Stats(m.investment_stats),
+    }
+}
+
+fn map_mod(m: &DimMod, get_tag: &mut dyn FnMut(&str) -> NonZeroU32) -> ProcessMod {
+    ProcessMod {
+        hash: NonZeroU32::new(m.hash),
+        mod_tag: m.tag.as_ref().map(|t| get_tag(t)),
+        energy_type: map_energy(m.energy.r#type),
+        energy_val: m.energy.val,
+    }
+}
+
+pub const EMPTY_MOD: ProcessMod = ProcessMod {
+    hash: None,
+    mod_tag: None,
+    energy_type: EnergyType::Any,
+    energy_val: 0,
+};
+
+pub fn map_export(dim_export: &DimExport) -> (ProcessInput, ExportRefs) {
+    let auto_mods = dim_export
+        .auto_stat_mods
+        .iter()
+        .map(map_stat_mod)
+        .collect::<Vec<_>>();
+
+    let mut tag_list: Vec<String> = vec![];
+    let mut get_tag = |tag: &str| {
+        let idx = match tag_list.iter().position(|x| x == tag) {
+            Some(idx) => idx,
+            None => {
+                let idx = tag_list.len();
+                tag_list.push(tag.to_owned());
+                idx
+            }
+        };
+        NonZeroU32::new(1 << idx).unwrap()
+    };
+
+    let mut map_mods = |mods: &[DimMod]| -> [ProcessMod; NUM_ITEM_BUCKETS] {
+        let mut mods = mods
+            .iter()
+            .map(|m| map_mod(m, &mut get_tag))
+            .collect::<Vec<_>>();
+        while mods.len() < 5 {
+            mods.push(EMPTY_MOD);
+        }
+        match mods.try_into() {
+            Ok(x) => x,
+            Err(_) => unreachable!(),
+        }
+    };
+    let general_mods = map_mods(&dim_export.locked_mods.general_mods);
+    let combat_mods = map_mods(&dim_export.locked_mods.combat_mods);
+    let activity_mods = map_mods(&dim_export.locked_mods.activity_mods);
+
+    let mut lower = [0; NUM_STATS];
+    let mut upper = [0; NUM_STATS];
+    for (idx, filter) in dim_export.stat_filters.iter().enumerate() {
+        if filter.ignored {
+            lower[idx] = 0;
+            upper[idx] = dim_export.stat_caps.map_or(10, |caps| caps[idx]);
+        } else {
+            lower[idx] = filter.min;
+            upper[idx] = filter.max;
+        }
+    }
+
+    // Armor set hashes are mapped to small ids, 0 means "no set".
+    let mut set_list: Vec<u32> = vec![];
+    let mut get_set_id = |hash: u32| {
+        let idx = match set_list.iter().position(|&x| x == hash) {
+            Some(idx) => idx,
+            None => {
+                set_list.push(hash);
+                set_list.len() - 1
+            }
+        };
+        (idx + 1) as u16
+    };
+
+    let mut set_bonuses = [ProcessSetBonus::default(); NUM_SET_BONUSES];
+    for (bonus, dim_bonus) in set_bonuses.iter_mut().zip(&dim_export.set_bonuses) {
+        *bonus = ProcessSetBonus {
+            set_id: get_set_id(dim_bonus.set_hash),
+            count: dim_bonus.count,
+        };
+    }
+
+    // Perk combinations are mapped to small ids too, regardless of perk order.
+    let mut perk_combos: Vec<Vec<u32>> = vec![];
+    let mut get_perk_id = |perks: &[u32]| {
+        if perks.is_empty() {
+            return 0;
+        }
+        let mut perks = perks.to_vec();
+        perks.sort_unstable();
+        let idx = match perk_combos.iter().position(|x| *x == perks) {
+            Some(idx) => idx,
+            None => {
+                perk_combos.push(perks);
+                perk_combos.len() - 1
+            }
+        };
+        (idx + 1) as u32
+    };
+    let required_exotic_perk_id = get_perk_id(&dim_export.required_exotic_perks);
+
+    let mut item_backrefs = vec![];
+    let mut track_item = |it: &DimItem| {
+        let len = item_backrefs.len();
+        item_backrefs.push((it.name.clone(), it.id.clone()));
+        len as u16
+    };
+
+    let items = dim_export.filtered_items.each_ref().map(|l| {
+        l.iter()
+            .map(|item| {
+                let idx = track_item(item);
+                ProcessItem {
+                    id: idx,
+                    power: item.power,
+                    energy_type: map_energy(item.energy.r#type),
+                    energy_val: item.energy.val,
+                    energy_cap: item.energy.capacity,
+                    exotic: item.is_exotic,
+                    mod_tags: item
+                        .mod_tags
+                        .iter()
+                        .fold(0, |acc, tag| acc | get_tag(tag).get()),
+                    stats: Stats(item.stats),
+                    bonus_stats: item
+                        .bonus_stats
+                        .as_ref()
+                        .map_or(SignedStats::default(), |b| SignedStats(b.stats)),
+                    bonus_mode: match &item.bonus_stats {
+                        Some(b) if b.conditional => BonusStatMode::Conditional,
+                        _ => BonusStatMode::Always,
+                    },
+                    tuning_stats: item.tuning_stats.iter().fold(0, |acc, s| acc | (1 << s)),
+                    set_id: item.set_hash.map_or(0, &mut get_set_id),
+                    exotic_perk_id: get_perk_id(&item.exotic_perks),
+                }
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let args = ProcessArgs {
+        base_stats: SignedStats(dim_export.base_stats),
+        bounds: ProcessTierBounds {
+            lower_bounds: lower,
+            upper_bounds: upper,
+        },
+        any_exotic: dim_export.any_exotic,
+        auto_mods: dim_export.max_auto_stat_mods.unwrap_or(5),
+        ruleset: match dim_export.stat_caps {
+            Some(_) => StatRuleset::Points,
+            None => StatRuleset::Tiers,
+        },
+        stat_caps: dim_export.stat_caps.unwrap_or_default(),
+        set_bonuses,
+        required_exotic_perk_id,
+    };
+
+    let input = ProcessInput {
+        items,
+        general_mods,
+        combat_mods,
+        activity_mods,
+        auto_mods,
+        args,
+    };
+    let refs = ExportRefs {
+        items: item_backrefs,
+        set_hashes: set_list,
+        tags: tag_list,
+    };
+    (input, refs)
+}
+
+fn dim_energy(e: EnergyType) -> DimEnergyType {
+    match e {
+        EnergyType::Any => DimEnergyType::Any,
+        EnergyType::Arc => DimEnergyType::Arc,
+        EnergyType::Solar => DimEnergyType::Solar,
+        EnergyType::Void => DimEnergyType::Void,
+        EnergyType::Stasis => DimEnergyType::Stasis,
+    }
+}
+
+/// Names of the set bits from the export, or e.g. `tag0` for bit 0.
+fn tag_names(tags: u32, refs: &ExportRefs) -> Vec<String> {
+    (0..u32::BITS)
+        .filter(|bit| tags & (1 << bit) != 0)
+        .map(|bit| match refs.tags.get(bit as usize) {
+            Some(tag) => tag.clone(),
+            None => format!("tag{bit}"),
+        })
+        .collect()
+}
+
+fn dim_mods(mods: &[ProcessMod; NUM_ITEM_BUCKETS], refs: &ExportRefs) -> Vec<DimMod> {
+    mods.iter()
+        .filter_map(|m| {
+            Some(DimMod {
+                hash: m.hash?.get(),
+                energy: DimModEnergy {
+                    r#type: dim_energy(m.energy_type),
+                    val: m.energy_val,
+                },
+                tag: m.mod_tag.map(|t| tag_names(t.get(), refs).concat()),
+            })
+        })
+        .collect()
+}
+
+/// The export of an input, e.g. a generated inventory or one changed on the command line.
+/// `map_export` gives back an equivalent input. Names, ids, mod tags and set hashes come
+/// from `refs` where it has them. Otherwise items are named after their bucket and id,
+/// mod tags are called `tag0`, `tag1` etc. and set hashes are LO's ids. Exotic perks
+/// are always LO's ids.
+pub fn from_input(input: &ProcessInput, refs: &ExportRefs) -> DimExport {
+    let args = &input.args;
+    let set_hash = |set_id: u16| {
+        refs.set_hashes
+            .get((set_id as usize).wrapping_sub(1))
+            .copied()
+            .unwrap_or(set_id.into())
+    };
+    let filtered_items = core::array::from_fn(|bucket| {
+        input.items[bucket]
+            .iter()
+            .map(|item| {
+                let (name, id) = match refs.items.get(item.id as usize) {
+                    Some((name, id)) => (name.clone(), id.clone()),
+                    None => (
+                        format!("{} {}", BUCKET_NAMES[bucket], item.id),
+                        item.id.to_string(),
+                    ),
+                };
+                DimItem {
+                    is_exotic: item.exotic,
+                    power: item.power,
+                    id,
+                    name,
+                    stats: item.stats.0,
+                    energy: DimItemEnergy {
+                        r#type: dim_energy(item.energy_type),
+                        capacity: item.energy_cap,
+                        val: item.energy_val,
+                    },
+                    mod_tags: tag_names(item.mod_tags, refs),
+                    bonus_stats: (item.bonus_stats.0 != [0; NUM_STATS]).then(|| DimBonusStats {
+                        stats: item.bonus_stats.0,
+                        conditional: item.bonus_mode == BonusStatMode::Conditional,
+                    }),
+                    set_hash: (item.set_id != 0).then(|| set_hash(item.set_id)),
+                    tuning_stats: (0..NUM_STATS as u8)
+                        .filter(|s| item.tuning_stats & (1 << s) != 0)
+                        .collect(),
+                    exotic_perks: match item.exotic_perk_id {
+                        0 => vec![],
+                        id => vec![id],
+                    },
+                }
+            })
+            .collect()
+    });
+
+    DimExport {
+        schema_version: SCHEMA_VERSION,
+        filtered_items,
+        base_stats: args.base_stats.0,
+        auto_stat_mods: input
+            .auto_mods
+            .iter()
+            .map(|m| DimAutoStatMod {
+                hash: m.inner_mod.hash.map_or(0, NonZeroU32::get),
+                energy: DimModEnergy {
+                    r#type: dim_energy(m.inner_mod.energy_type),
+                    val: m.inner_mod.energy_val,
+                },
+                investment_stats: m.stats.0,
+            })
+            .collect(),
+        locked_mods: DimLockedMods {
+            general_mods: dim_mods(&input.general_mods, refs),
+            combat_mods: dim_mods(&input.combat_mods, refs),
+            activity_mods: dim_mods(&input.activity_mods, refs),
+        },
+        stat_filters: core::array::from_fn(|stat| DimStatFilter {
+            min: args.bounds.lower_bounds[stat],
+            max: args.bounds.upper_bounds[stat],
+            ignored: false,
+        }),
+        any_exotic: args.any_exotic,
+        stat_caps: match args.ruleset {
+            StatRuleset::Tiers => None,
+            StatRuleset::Points => Some(args.stat_caps),
+        },
+        set_bonuses: args
+            .set_bonuses
+            .iter()
+            .filter(|b| b.set_id != 0)
+            .map(|b| DimSetBonus {
+                set_hash: set_hash(b.set_id),
+                count: b.count,
+            })
+            .collect(),
+        required_exotic_perks: match args.required_exotic_perk_id {
+            0 => vec![],
+            id => vec![id],
+        },
+        max_auto_stat_mods: (args.auto_mods != 5).then_some(args.auto_mods),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A schema 1 export with one item and a mod of each kind, without the fields
+    /// that came later.
+    const V1_EXPORT: &str = r#"{
+        "filteredItems": [
+            [{
+                "isExotic": false,
+                "power": 1800,
+                "id": "6917529000000000001",
+                "name": "Helmet",
+                "stats": [2, 10, 20, 2, 10, 24],
+                "energy": { "type": 3, "capacity": 10, "val": 2 },
+                "compatibleModSeasons": ["deepstonecrypt"]
+            }],
+            [], [], [], []
+        ],
+        "modStatTotals": [10, 0, -10, 0, 0, 0],
+        "autoStatMods": [
+            { "hash": 7, "energy": { "type": 0, "val": 3 }, "investmentStats": [10, 0, 0, 0, 0, 0] }
+        ],
+        "lockedMods": {
+            "generalMods": [],
+            "combatMods": [{ "hash": 8, "energy": { "type": 2, "val": 4 }, "tag": "deepstonecrypt" }],
+            "activityMods": []
+        },
+        "statFilters": [
+            { "min": 0, "max": 10, "ignored": false },
+            { "min": 3, "max": 10, "ignored": false },
+            { "min": 0, "max": 10, "ignored": true },
+            { "min": 0, "max": 10, "ignored": true },
+            { "min": 0, "max": 10, "ignored": true },
+            { "min": 0, "max": 10, "ignored": true }
+        ],
+        "anyExotic": true
+    }"#;
+
+    fn parse_json(value: &serde_json::Value) -> Result<DimExport, ExportError> {
+        parse_export(value.to_string().as_bytes())
+    }
+
+    fn parse_error(value: &serde_json::Value) -> ExportError {
+        parse_json(value).err().expect("the export was accepted")
+    }
+
+    #[test]
+    fn migrates_v1() {
+        let dim_export = parse_export(V1_EXPORT.as_bytes()).unwrap();
+        assert_eq!(dim_export.schema_version, SCHEMA_VERSION);
+        assert_eq!(dim_export.base_stats, [10, 0, -10, 0, 0, 0]);
+        assert!(dim_export.set_bonuses.is_empty());
+        assert!(dim_export.required_exotic_perks.is_empty());
+        assert_eq!(dim_export.max_auto_stat_mods, None);
+
+        let item = &dim_export.filtered_items[0][0];
+        assert!(matches!(item.energy.r#type, DimEnergyType::Void));
+        assert_eq!(item.mod_tags, ["deepstonecrypt"]);
+        assert!(item.bonus_stats.is_none());
+        assert_eq!(item.set_hash, None);
+        assert!(item.tuning_stats.is_empty());
+        assert!(item.exotic_perks.is_empty());
+        let combat_mod = &dim_export.locked_mods.combat_mods[0];
+        assert!(matches!(combat_mod.energy.r#type, DimEnergyType::Solar));
+        assert_eq!(combat_mod.tag.as_deref(), Some("deepstonecrypt"));
+        let auto_mod = &dim_export.auto_stat_mods[0];
+        assert!(matches!(auto_mod.energy.r#type, DimEnergyType::Any));
+
+        // The item can slot the combat mod, since they share the tag
+        let (input, refs) = map_export(&dim_export);
+        let item = &input.items[0][0];
+        assert!(item.energy_type == EnergyType::Void && item.tuning_stats == 0);
+        assert_eq!(
+            Some(item.mod_tags),
+            input.combat_mods[0].mod_tag.map(|t| t.get())
+        );
+        assert_eq!(refs.tags, ["deepstonecrypt"]);
+        assert_eq!(input.args.base_stats.0, [10, 0, -10, 0, 0, 0]);
+
+        // Written out, it's a current export with the same content
+        let written = serde_json::to_value(&dim_export).unwrap();
+        assert_eq!(written["schemaVersion"], SCHEMA_VERSION);
+        assert_eq!(written["filteredItems"][0][0]["energy"]["type"], "void");
+        assert_eq!(
+            written["baseStats"],
+            serde_json::json!([10, 0, -10, 0, 0, 0])
+        );
+        assert_eq!(parse_json(&written).unwrap().filtered_items[0].len(), 1);
+    }
+
+    #[test]
+    fn rejects_unknown_schema_version() {
+        let mut dim_export: serde_json::Value = serde_json::from_str(V1_EXPORT).unwrap();
+        dim_export["schemaVersion"] = (SCHEMA_VERSION + 1).into();
+        let e = parse_error(&dim_export);
+        assert_eq!(e.path, "schemaVersion");
+        assert!(e.to_string().starts_with(&format!(
+            "at schemaVersion: unknown schema version {}, expected 1 to {SCHEMA_VERSION}",
+            SCHEMA_VERSION + 1
+        )));
+    }
+
+    #[test]
+    fn reports_bad_field() {
+        let mut dim_export: serde_json::Value = serde_json::from_str(V1_EXPORT).unwrap();
+        dim_export["filteredItems"][0][0]["energy"]["type"] = 5.into();
+        let e = parse_error(&dim_export);
+        assert!(
+            e.to_string().starts_with(
+                "at filteredItems[0][0].energy.type: unknown energy type 5, expected 0 (any), \
+             1 (arc), 2 (solar), 3 (void) or 4 (stasis) at line 1"
+            ),
+            "{e}"
+        );
+
+        // The same in the current schema, where energy types are names
+        let mut dim_export =
+            serde_json::to_value(parse_export(V1_EXPORT.as_bytes()).unwrap()).unwrap();
+        dim_export["lockedMods"]["combatMods"][0]["energy"]["type"] = "plasma".into();
+        let e = parse_error(&dim_export);
+        assert!(
+            e.to_string().starts_with(
+                "at lockedMods.combatMods[0].energy.type: unknown variant `plasma`, \
+             expected one of `any`, `arc`, `solar`, `void`, `stasis` at line 1"
+            ),
+            "{e}"
+        );
+
+        dim_export["lockedMods"]["combatMods"][0]["energy"]["type"] = "arc".into();
+        dim_export["statFilters"][1]["min"] = (-1).into();
+        let e = parse_error(&dim_export);
+        assert_eq!(e.path, "statFilters[1].min");
+        assert!(e.message.contains("expected u16"), "{e}");
+    }
+}
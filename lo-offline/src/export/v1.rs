@@ -0,0 +1,186 @@
+//! Schema 1, exports from before DIM versioned them.
+
+use serde::Deserialize;
+
+use dim_lo_core::types::{NUM_ITEM_BUCKETS, NUM_STATS};
+
+use super::{self as current, DimBonusStats, DimSetBonus, DimStatFilter, SCHEMA_VERSION};
+
+/// Energy types were numbers.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(try_from = "u8")]
+struct DimEnergyType(current::DimEnergyType);
+
+impl TryFrom<u8> for DimEnergyType {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, String> {
+        use current::DimEnergyType::*;
+        Ok(Self(match value {
+            0 => Any,
+            1 => Arc,
+            2 => Solar,
+            3 => Void,
+            4 => Stasis,
+            _ => {
+                return Err(format!(
+                    "unknown energy type {value}, expected 0 (any), 1 (arc), 2 (solar), \
+                     3 (void) or 4 (stasis)"
+                ))
+            }
+        }))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DimItemEnergy {
+    r#type: DimEnergyType,
+    capacity: u8,
+    val: u8,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DimModEnergy {
+    r#type: DimEnergyType,
+    val: u8,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DimAutoStatMod {
+    hash: u32,
+    energy: DimModEnergy,
+    investment_stats: [u16; NUM_STATS],
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DimMod {
+    hash: u32,
+    energy: DimModEnergy,
+    tag: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DimItem {
+    is_exotic: bool,
+    power: u16,
+    id: String,
+    name: String,
+    stats: [u16; NUM_STATS],
+    energy: DimItemEnergy,
+    compatible_mod_seasons: Vec<String>,
+    #[serde(default)]
+    bonus_stats: Option<DimBonusStats>,
+    #[serde(default)]
+    set_hash: Option<u32>,
+    #[serde(default)]
+    tuning_stats: Vec<u8>,
+    #[serde(default)]
+    exotic_perks: Vec<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DimLockedMods {
+    general_mods: Vec<DimMod>,
+    combat_mods: Vec<DimMod>,
+    activity_mods: Vec<DimMod>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DimExport {
+    filtered_items: [Vec<DimItem>; NUM_ITEM_BUCKETS],
+    mod_stat_totals: [i16; NUM_STATS],
+    auto_stat_mods: Vec<DimAutoStatMod>,
+    locked_mods: DimLockedMods,
+    stat_filters: [DimStatFilter; NUM_STATS],
+    any_exotic: bool,
+    #[serde(default)]
+    stat_caps: Option<[u16; NUM_STATS]>,
+    #[serde(default)]
+    set_bonuses: Vec<DimSetBonus>,
+    #[serde(default)]
+    required_exotic_perks: Vec<u32>,
+}
+
+impl From<DimModEnergy> for current::DimModEnergy {
+    fn from(e: DimModEnergy) -> Self {
+        Self {
+            r#type: e.r#type.0,
+            val: e.val,
+        }
+    }
+}
+
+impl From<DimMod> for current::DimMod {
+    fn from(m: DimMod) -> Self {
+        Self {
+            hash: m.hash,
+            energy: m.energy.into(),
+            tag: m.tag,
+        }
+    }
+}
+
+impl From<DimItem> for current::DimItem {
+    fn from(item: DimItem) -> Self {
+        Self {
+            is_exotic: item.is_exotic,
+            power: item.power,
+            id: item.id,
+            name: item.name,
+            stats: item.stats,
+            energy: current::DimItemEnergy {
+                r#type: item.energy.r#type.0,
+                capacity: item.energy.capacity,
+                val: item.energy.val,
+            },
+            mod_tags: item.compatible_mod_seasons,
+            bonus_stats: item.bonus_stats,
+            set_hash: item.set_hash,
+            tuning_stats: item.tuning_stats,
+            exotic_perks: item.exotic_perks,
+        }
+    }
+}
+
+fn migrate_mods(mods: Vec<DimMod>) -> Vec<current::DimMod> {
+    mods.into_iter().map(Into::into).collect()
+}
+
+impl From<DimExport> for current::DimExport {
+    fn from(export: DimExport) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            filtered_items: export
+                .filtered_items
+                .map(|items| items.into_iter().map(Into::into).collect()),
+            base_stats: export.mod_stat_totals,
+            auto_stat_mods: export
+                .auto_stat_mods
+                .into_iter()
+                .map(|m| current::DimAutoStatMod {
+                    hash: m.hash,
+                    energy: m.energy.into(),
+                    investment_stats: m.investment_stats,
+                })
+                .collect(),
+            locked_mods: current::DimLockedMods {
+                general_mods: migrate_mods(export.locked_mods.general_mods),
+                combat_mods: migrate_mods(export.locked_mods.combat_mods),
+                activity_mods: migrate_mods(export.locked_mods.activity_mods),
+            },
+            stat_filters: export.stat_filters,
+            any_exotic: export.any_exotic,
+            stat_caps: export.stat_caps,
+            set_bonuses: export.set_bonuses,
+            required_exotic_perks: export.required_exotic_perks,
+            max_auto_stat_mods: None,
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! `lo-offline golden`: runs every export in a directory and compares all results, i.e.
+//! the `ProcessStats`, min/max stats and every set in order, with the expected ones stored
+//! next to it, `NAME.expected.json` for `NAME.json`. With `--update` it rewrites those
+//! instead, for when a change in behavior is intended.
+
+use std::{fs, io, path::Path};
+
+use serde_json::Value;
+
+use crate::output;
+
+const EXPECTED_SUFFIX: &str = ".expected.json";
+
+/// How many differences to print per export, the first ones are usually enough.
+const MAX_DIFFERENCES: usize = 10;
+
+/// Adds a line for every difference between `expected` and `actual` to `out`,
+/// naming where it is by its JSON path.
+fn diff(path: &str, expected: &Value, actual: &Value, out: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Object(e), Value::Object(a)) => {
+            let added = a.keys().filter(|key| !e.contains_key(*key));
+            for key in e.keys().chain(added) {
+                let field = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                let missing = Value::Null;
+                let e = e.get(key).unwrap_or(&missing);
+                let a = a.get(key).unwrap_or(&missing);
+                diff(&field, e, a, out);
+            }
+        }
+        (Value::Array(e), Value::Array(a)) => {
+            for (idx, (e, a)) in e.iter().zip(a).enumerate() {
+                diff(&format!("{path}[{idx}]"), e, a, out);
+            }
+            if e.len() != a.len() {
+                out.push(format!(
+                    "{path}: expected {} elements, got {}",
+                    e.len(),
+                    a.len()
+                ));
+            }
+        }
+        _ if expected != actual => out.push(format!("{path}: expected {expected}, got {actual}")),
+        _ => {}
+    }
+}
+
+/// Writes results with one field and one set per line, so that they stay small and their
+/// diffs readable.
+fn expected_json(results: &Value) -> String {
+    let Value::Object(fields) = results else {
+        return format!("{results}\n");
+    };
+    let fields = fields.iter().map(|(key, value)| {
+        let value = match value {
+            Value::Array(elements)
+                if !elements.is_empty() && elements.iter().all(Value::is_object) =>
+            {
+                let elements = elements
+                    .iter()
+                    .map(|e| format!("    {e}"))
+                    .collect::<Vec<_>>();
+                format!("[\n{}\n  ]", elements.join(",\n"))
+            }
+            _ => value.to_string(),
+        };
+        format!("  {}: {value}", Value::from(key.as_str()))
+    });
+    format!("{{\n{}\n}}\n", fields.collect::<Vec<_>>().join(",\n"))
+}
+
+/// The differences between the results of the export at `path` and its expected ones,
+/// or rewrites the expected ones with `update`.
+fn check(path: &Path, expected_path: &Path, update: bool) -> Result<Vec<String>, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let (input, refs) = crate::load_input(&bytes).map_err(|e| e.to_string())?;
+    let actual = output::results_json(&input, &refs)?;
+    let actual = serde_json::from_str(&actual).expect("output::results_json writes JSON");
+    if update {
+        fs::write(expected_path, expected_json(&actual)).map_err(|e| e.to_string())?;
+        return Ok(vec![]);
+    }
+
+    let expected = match fs::read_to_string(expected_path) {
+        Ok(expected) => expected,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err("no expected results, run with --update to write them".to_owned())
+        }
+        Err(e) => return Err(e.to_string()),
+    };
+    let expected = serde_json::from_str(&expected).map_err(|e| e.to_string())?;
+    let mut differences = vec![];
+    diff("", &expected, &actual, &mut differences);
+    Ok(differences)
+}
+
+/// Checks (or updates) every export in `dir`, printing what differs.
+/// Returns whether all of them gave their expected results, and false if there are none,
+/// since that is most likely a wrong directory.
+pub fn run(dir: &Path, update: bool) -> io::Result<bool> {
+    let mut exports = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    exports.retain(|path| {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        name.ends_with(".json") && !name.ends_with(EXPECTED_SUFFIX)
+    });
+    exports.sort();
+    if exports.is_empty() {
+        println!("No exports in {}", dir.display());
+        return Ok(false);
+    }
+
+    let mut passed = 0;
+    for path in &exports {
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        let expected_path = path.with_file_name(format!("{name}{EXPECTED_SUFFIX}"));
+        match check(path, &expected_path, update) {
+            Ok(differences) if differences.is_empty() => passed += 1,
+            Ok(differences) => {
+                println!("{name}: {} differences", differences.len());
+                for difference in differences.iter().take(MAX_DIFFERENCES) {
+                    println!("  {difference}");
+                }
+                if differences.len() > MAX_DIFFERENCES {
+                    println!("  ...");
+                }
+            }
+            Err(e) => println!("{name}: {e}"),
+        }
+    }
+
+    let verb = if update { "Updated" } else { "Checked" };
+    println!(
+        "{verb} {} exports in {}, {passed} passed",
+        exports.len(),
+        dir.display()
+    );
+    Ok(passed == exports.len())
+}
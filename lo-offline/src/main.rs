@@ -1,261 +1,185 @@
-#![feature(array_methods)]
+use std::{env, fmt, fs, io, path::Path, process};
 
-use std::{env, fs::File, io, num::NonZeroU32};
-
-use serde::Deserialize;
-use serde_repr::Deserialize_repr;
+mod anonymize;
+mod bench;
+mod cli;
+mod export;
+mod golden;
+mod minimize;
+mod output;
+mod regression;
 
 use dim_lo_core::{
-    dim_lo_process,
-    types::{
-        EnergyType, ProcessArgs, ProcessItem, ProcessMod, ProcessStatMod, ProcessStats,
-        ProcessTierBounds, Stats, NUM_ITEM_BUCKETS, NUM_STATS,
-    },
+    generate,
+    input::{ProcessInput, DUMP_MAGIC, PACKED_MAGIC},
 };
 
-#[repr(u8)]
-#[derive(Clone, Copy, Deserialize_repr)]
-enum DimEnergyType {
-    Any = 0,
-    Arc = 1,
-    Solar = 2,
-    Void = 3,
-    Stasis = 4,
-}
-
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct DimItemEnergy {
-    r#type: DimEnergyType,
-    capacity: u8,
-    val: u8,
-}
-
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct DimModEnergy {
-    r#type: DimEnergyType,
-    val: u8,
-}
-
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct DimAutoStatMod {
-    hash: u32,
-    energy: DimModEnergy,
-    investment_stats: [u16; NUM_STATS],
-}
+use cli::{Command, Format, USAGE};
+use export::DimExport;
+use output::{ExportRefs, Results};
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct DimMod {
-    hash: u32,
-    energy: DimModEnergy,
-    tag: Option<String>,
+fn invalid_input(e: impl fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid input: {e}"))
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct DimStatFilter {
-    min: u8,
-    max: u8,
-    ignored: bool,
+/// Packed input and dumps (see `dim_lo_core::input`) are recognized by their magic bytes,
+/// anything else is read as a JSON export.
+fn load_input(bytes: &[u8]) -> Result<(ProcessInput, ExportRefs), io::Error> {
+    Ok(if bytes.starts_with(&PACKED_MAGIC) {
+        let input = ProcessInput::from_packed(bytes).map_err(invalid_input)?;
+        (input, ExportRefs::default())
+    } else if bytes.starts_with(&DUMP_MAGIC) {
+        let input = ProcessInput::from_dump(bytes).map_err(invalid_input)?;
+        (input, ExportRefs::default())
+    } else {
+        let dim_export = export::parse_export(bytes).map_err(invalid_input)?;
+        export::map_export(&dim_export)
+    })
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct DimItem {
-    is_exotic: bool,
-    power: u16,
-    id: String,
-    name: String,
-    stats: [u16; NUM_STATS],
-    energy: DimItemEnergy,
-    compatible_mod_seasons: Vec<String>,
+fn write_export(dim_export: &DimExport, mut out: impl io::Write) -> Result<(), io::Error> {
+    serde_json::to_writer_pretty(&mut out, dim_export)?;
+    writeln!(out)
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct DimLockedMods {
-    general_mods: Vec<DimMod>,
-    combat_mods: Vec<DimMod>,
-    activity_mods: Vec<DimMod>,
+/// Writes to `path`, or to stdout without one.
+fn write_export_to(dim_export: &DimExport, path: Option<&str>) -> Result<(), io::Error> {
+    match path {
+        Some(path) => write_export(dim_export, fs::File::create(path)?),
+        None => write_export(dim_export, io::stdout().lock()),
+    }
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct DimExport {
-    filtered_items: [Vec<DimItem>; NUM_ITEM_BUCKETS],
-    mod_stat_totals: [u16; NUM_STATS],
-    auto_stat_mods: Vec<DimAutoStatMod>,
-    locked_mods: DimLockedMods,
-    stat_filters: [DimStatFilter; NUM_STATS],
-    any_exotic: bool,
-}
+fn main() -> Result<(), io::Error> {
+    let options = match cli::parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}\n{USAGE}");
+            process::exit(2);
+        }
+    };
 
-fn map_energy(e: DimEnergyType) -> EnergyType {
-    match e {
-        DimEnergyType::Any => EnergyType::Any,
-        DimEnergyType::Arc => EnergyType::Arc,
-        DimEnergyType::Solar => EnergyType::Solar,
-        DimEnergyType::Void => EnergyType::Void,
-        DimEnergyType::Stasis => EnergyType::Stasis,
+    if let Command::Generate(generate_options) = &options.command {
+        let input = generate::generate(generate_options);
+        let dim_export = export::from_input(&input, &ExportRefs::default());
+        return write_export_to(&dim_export, options.path.as_deref());
     }
-}
 
-fn map_stat_mod(m: &DimAutoStatMod) -> ProcessStatMod {
-    ProcessStatMod {
-        inner_mod: ProcessMod {
-            hash: NonZeroU32::new(m.hash),
-            mod_tag: None,
-            energy_type: map_energy(m.energy.r#type),
-            energy_val: m.energy.val,
-        },
-        stats: Stats(m.investment_stats),
+    if let Command::Golden { update } = options.command {
+        let dir = options
+            .path
+            .as_deref()
+            .expect("parse_args checks for an input");
+        if !golden::run(Path::new(dir), update)? {
+            process::exit(1);
+        }
+        return Ok(());
     }
-}
 
-fn map_mod(m: &DimMod, get_tag: &mut dyn FnMut(&str) -> NonZeroU32) -> ProcessMod {
-    ProcessMod {
-        hash: NonZeroU32::new(m.hash),
-        mod_tag: m.tag.as_ref().map(|t| get_tag(t)),
-        energy_type: map_energy(m.energy.r#type),
-        energy_val: m.energy.val,
+    let path = options
+        .path
+        .as_deref()
+        .expect("parse_args checks for an input");
+    let bytes = fs::read(path)?;
+    let (mut input, refs) = load_input(&bytes)?;
+    if let Err(e) = options.overrides.apply(&mut input, &refs) {
+        eprintln!("{e}");
+        process::exit(2);
     }
-}
-
-const EMPTY_MOD: ProcessMod = ProcessMod {
-    hash: None,
-    mod_tag: None,
-    energy_type: EnergyType::Any,
-    energy_val: 0,
-};
-
-fn main() -> Result<(), io::Error> {
-    let path = env::args()
-        .nth(1)
-        .expect("provide path to exported json on command line");
 
-    let dim_export: DimExport = serde_json::from_reader(io::BufReader::new(File::open(path)?))?;
+    if let Command::Minimize {
+        predicate: Some(predicate),
+        output,
+    } = &options.command
+    {
+        let dim_export = export::from_input(&input, &refs);
+        let Some(minimized) = minimize::minimize(dim_export, predicate) else {
+            eprintln!("the predicate doesn't hold for {path}");
+            process::exit(1);
+        };
+        eprintln!(
+            "Minimized to {} after {} tests",
+            minimize::describe(&minimized.export),
+            minimized.tests
+        );
+        return write_export_to(&minimized.export, output.as_deref());
+    }
 
-    let auto_mods = dim_export
-        .auto_stat_mods
-        .iter()
-        .map(map_stat_mod)
-        .collect::<Vec<_>>();
+    if let Command::Anonymize {
+        output,
+        salt,
+        random_key,
+    } = &options.command
+    {
+        let key = match salt {
+            Some(salt) => anonymize::PseudonymKey::from_salt(salt),
+            None if *random_key => anonymize::PseudonymKey::random(),
+            None => anonymize::PseudonymKey::fixed(),
+        };
+        let dim_export = anonymize::anonymize(&input, &refs, &key);
+        if let Err(e) = anonymize::verify(&input, &refs, &key, &dim_export) {
+            eprintln!("the anonymized export doesn't give the same results: {e}");
+            process::exit(1);
+        }
+        eprintln!("Anonymized {path}, the results are the same");
+        return write_export_to(&dim_export, output.as_deref());
+    }
 
-    let mut tag_list: Vec<String> = vec![];
-    let mut get_tag = |tag: &str| {
-        let idx = match tag_list.iter().position(|x| x == tag) {
-            Some(idx) => idx,
-            None => {
-                let idx = tag_list.len();
-                tag_list.push(tag.to_owned());
-                idx
-            }
+    if let Command::Regression { name, output } = &options.command {
+        // Just the file name, the path may well say something about the user
+        let file_name = Path::new(path)
+            .file_name()
+            .map_or(path.into(), |n| n.to_string_lossy());
+        let name = match name {
+            Some(name) => name.as_str(),
+            None => file_name.split('.').next().unwrap_or_default(),
         };
-        NonZeroU32::new(1 << idx).unwrap()
-    };
+        let test = regression::write_test(
+            &input,
+            &refs,
+            &regression::test_name(name),
+            &file_name,
+            options.top.unwrap_or(10),
+        )
+        .map_err(invalid_input)?;
+        return match output {
+            Some(path) => fs::write(path, test),
+            None => io::Write::write_all(&mut io::stdout().lock(), test.as_bytes()),
+        };
+    }
 
-    let mut map_mods = |mods: &[DimMod]| -> [ProcessMod; NUM_ITEM_BUCKETS] {
-        let mut mods = mods
-            .iter()
-            .map(|m| map_mod(m, &mut get_tag))
-            .collect::<Vec<_>>();
-        while mods.len() < 5 {
-            mods.push(EMPTY_MOD);
-        }
-        match mods.try_into() {
-            Ok(x) => x,
-            Err(_) => unreachable!(),
-        }
-    };
-    let general_mods = map_mods(&dim_export.locked_mods.general_mods);
-    let combat_mods = map_mods(&dim_export.locked_mods.combat_mods);
-    let activity_mods = map_mods(&dim_export.locked_mods.activity_mods);
+    // Keep stdout parseable for JSON
+    match options.format {
+        Format::Table => output::print_config(&input, &refs, io::stdout().lock())?,
+        Format::Json => output::print_config(&input, &refs, io::stderr().lock())?,
+    }
 
-    let mut lower = [0; NUM_STATS];
-    let mut upper = [0; NUM_STATS];
-    for (idx, filter) in dim_export.stat_filters.iter().enumerate() {
-        if filter.ignored {
-            lower[idx] = 0;
-            lower[idx] = 10;
-        } else {
-            lower[idx] = filter.min;
-            upper[idx] = filter.max;
+    if let Command::Bench { runs, warmup } = options.command {
+        let results = bench::bench(&input, runs, warmup).map_err(invalid_input)?;
+        match options.format {
+            Format::Table => bench::print_table(&results),
+            Format::Json => bench::write_json(&results, io::stdout().lock())?,
         }
+        return Ok(());
     }
 
-    let mut item_backrefs = vec![];
-    let mut track_item = |it: &DimItem| {
-        let len = item_backrefs.len();
-        item_backrefs.push((it.name.clone(), it.id.clone()));
-        len as u16
-    };
-
-    let items = dim_export.filtered_items.each_ref().map(|l| {
-        l.iter()
-            .map(|item| {
-                let idx = track_item(item);
-                ProcessItem {
-                    id: idx,
-                    power: item.power,
-                    energy_type: map_energy(item.energy.r#type),
-                    energy_val: item.energy.val,
-                    energy_cap: item.energy.capacity,
-                    exotic: item.is_exotic,
-                    mod_tags: item
-                        .compatible_mod_seasons
-                        .iter()
-                        .fold(0, |acc, season| acc | get_tag(season).get()),
-                    stats: Stats(item.stats),
-                }
-            })
-            .collect::<Vec<_>>()
-    });
-    let sliced = items.each_ref().map(|x| &**x);
+    let (info, sets, min_max) = input.process().map_err(invalid_input)?;
 
-    let args = ProcessArgs {
-        base_stats: Stats(dim_export.mod_stat_totals),
-        bounds: ProcessTierBounds {
-            lower_bounds: lower,
-            upper_bounds: upper,
-        },
-        any_exotic: dim_export.any_exotic,
-        auto_mods: 5,
+    let results = Results {
+        info: &info,
+        sets: &sets,
+        min_max: &min_max,
+        args: &input.args,
+        refs: &refs,
     };
-
-    let (info, results, min_max) = dim_lo_process(
-        sliced,
-        &general_mods,
-        &combat_mods,
-        &activity_mods,
-        &auto_mods,
-        &args,
-    );
-
-    let ProcessStats {
-        num_valid_sets,
-        skipped_low_tier,
-        skipped_stat_range,
-        skipped_mods_unfit,
-        skipped_double_exotic,
-        skipped_no_exotic,
-    } = info;
-
-    println!(
-        r#"Completed LO Run.
-Num Valid Sets: {num_valid_sets}
-Skipped Low Tier: {skipped_low_tier}
-Skipped Stat Range: {skipped_stat_range}
-Skipped Mods Didn't Fit: {skipped_mods_unfit}
-Skipped Double Exotic: {skipped_double_exotic}
-Skipped No Exotic: {skipped_no_exotic}
-"#
-    );
-
-    println!("MinMax: {:?} - {:?}", min_max.min, min_max.max);
-    println!("Num Results: {}", results.len());
+    match options.format {
+        Format::Table => output::print_table(&results, options.top.unwrap_or(10)),
+        Format::Json => output::write_json(
+            &results,
+            options.top.unwrap_or(usize::MAX),
+            io::stdout().lock(),
+        )?,
+    }
 
     Ok(())
 }
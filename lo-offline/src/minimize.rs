@@ -0,0 +1,362 @@
+//! `lo-offline minimize`: shrinks an export that shows a bug down to one that still does,
+//! by delta debugging its items, locked and auto mods, set requirements and stat bounds,
+//! then loosening the bounds that are left.
+
+use std::{
+    panic::{self, AssertUnwindSafe},
+    slice,
+};
+
+use dim_lo_core::{
+    input::ProcessInput,
+    oracle, try_dim_lo_process,
+    types::{ProcessItem, NUM_ITEM_BUCKETS, NUM_STATS},
+};
+
+use crate::export::{self, DimExport};
+
+/// What makes an export interesting, i.e. what has to keep happening while it shrinks.
+/// Exports LO rejects as invalid are never interesting.
+pub enum Predicate {
+    /// The set with these item ids (DIM ids, or LO ids for packed input and dumps)
+    /// isn't in the results, even though the oracle takes it.
+    Missing([String; NUM_ITEM_BUCKETS]),
+    /// LO and the oracle disagree on whether some set is valid, see `dim_lo_core::oracle`.
+    /// Both look at every set on its own, so this is only feasible for small exports.
+    Oracle,
+    /// LO panics.
+    Panics,
+}
+
+impl Predicate {
+    fn holds(&self, dim_export: &DimExport) -> bool {
+        let (input, refs) = export::map_export(dim_export);
+        match self {
+            Predicate::Missing(ids) => {
+                let find = |bucket: usize| {
+                    input.items[bucket].iter().find(|item| {
+                        refs.items
+                            .get(item.id as usize)
+                            .is_some_and(|(_, id)| *id == ids[bucket])
+                    })
+                };
+                let [Some(helmet), Some(gauntlets), Some(chest), Some(legs), Some(class_item)] =
+                    std::array::from_fn(find)
+                else {
+                    return false;
+                };
+                let set = [helmet, gauntlets, chest, legs, class_item];
+                if !oracle::accepts_set(&input, set) {
+                    return false;
+                }
+                let ids = set.map(|i| i.id);
+                match input.process() {
+                    Ok((_, sets, _)) => !sets.iter().any(|s| s.items == ids),
+                    Err(_) => false,
+                }
+            }
+            Predicate::Oracle => {
+                if input.process().is_err() {
+                    return false;
+                }
+                let mut differs = false;
+                oracle::for_each_set(&input.items, |set| {
+                    differs =
+                        differs || oracle::accepts_set(&input, set) != lo_accepts(&input, set);
+                });
+                differs
+            }
+            Predicate::Panics => panic::catch_unwind(AssertUnwindSafe(|| input.process())).is_err(),
+        }
+    }
+}
+
+/// Whether LO takes `set` when it is the only one. Comparing the valid set counts of a full
+/// run instead doesn't work, LO doesn't check the sets it skips as too low tier.
+fn lo_accepts(input: &ProcessInput, set: [&ProcessItem; NUM_ITEM_BUCKETS]) -> bool {
+    try_dim_lo_process(
+        set.map(slice::from_ref),
+        &input.general_mods,
+        &input.combat_mods,
+        &input.activity_mods,
+        &input.auto_mods,
+        &input.args,
+    )
+    .is_ok_and(|(info, _, _)| info.num_valid_sets == 1)
+}
+
+/// Finds a small subset of `0..len` that `test` holds for, given that it holds for all
+/// of it. Tries dropping chunks of halving size, down to single indices, so no single
+/// index of the result can be dropped on its own.
+fn ddmin(len: usize, mut test: impl FnMut(&[usize]) -> bool) -> Vec<usize> {
+    let mut kept = (0..len).collect::<Vec<_>>();
+    let mut chunk = len;
+    while chunk > 0 {
+        let mut start = 0;
+        while start < kept.len() {
+            let end = (start + chunk).min(kept.len());
+            let candidate = [&kept[..start], &kept[end..]].concat();
+            if test(&candidate) {
+                kept = candidate;
+            } else {
+                start = end;
+            }
+        }
+        chunk /= 2;
+    }
+    kept
+}
+
+/// Keeps the elements of `list` whose running index `next` is in `keep` (sorted).
+fn retain<T>(list: &mut Vec<T>, keep: &[usize], next: &mut usize) {
+    list.retain(|_| {
+        *next += 1;
+        keep.binary_search(&(*next - 1)).is_ok()
+    });
+}
+
+/// A way of shrinking an export: `units` counts the parts it could drop,
+/// `keep` makes a copy with only the given parts.
+struct Pass {
+    units: fn(&DimExport) -> usize,
+    keep: fn(&DimExport, &[usize]) -> DimExport,
+}
+
+const PASSES: [Pass; 5] = [
+    // Items, in bucket order
+    Pass {
+        units: |e| e.filtered_items.iter().map(Vec::len).sum(),
+        keep: |e, keep| {
+            let mut e = e.clone();
+            let mut next = 0;
+            for bucket in &mut e.filtered_items {
+                retain(bucket, keep, &mut next);
+            }
+            e
+        },
+    },
+    // Locked general, combat and activity mods
+    Pass {
+        units: |e| {
+            let mods = &e.locked_mods;
+            mods.general_mods.len() + mods.combat_mods.len() + mods.activity_mods.len()
+        },
+        keep: |e, keep| {
+            let mut e = e.clone();
+            let mut next = 0;
+            let mods = &mut e.locked_mods;
+            retain(&mut mods.general_mods, keep, &mut next);
+            retain(&mut mods.combat_mods, keep, &mut next);
+            retain(&mut mods.activity_mods, keep, &mut next);
+            e
+        },
+    },
+    // Auto stat mods
+    Pass {
+        units: |e| e.auto_stat_mods.len(),
+        keep: |e, keep| {
+            let mut e = e.clone();
+            retain(&mut e.auto_stat_mods, keep, &mut 0);
+            e
+        },
+    },
+    // Required set bonuses, then exotic perks
+    Pass {
+        units: |e| e.set_bonuses.len() + e.required_exotic_perks.len(),
+        keep: |e, keep| {
+            let mut e = e.clone();
+            let mut next = 0;
+            retain(&mut e.set_bonuses, keep, &mut next);
+            retain(&mut e.required_exotic_perks, keep, &mut next);
+            e
+        },
+    },
+    // Stats that aren't ignored, and requiring an exotic
+    Pass {
+        units: |e| e.stat_filters.iter().filter(|f| !f.ignored).count() + e.any_exotic as usize,
+        keep: |e, keep| {
+            let mut e = e.clone();
+            let mut next = 0;
+            for filter in e.stat_filters.iter_mut().filter(|f| !f.ignored) {
+                filter.ignored = keep.binary_search(&next).is_err();
+                next += 1;
+            }
+            e.any_exotic &= keep.binary_search(&next).is_ok();
+            e
+        },
+    },
+];
+
+/// The highest bound a stat can have, in tiers or points like the filters.
+fn top_bound(dim_export: &DimExport, stat: usize) -> u16 {
+    dim_export.stat_caps.map_or(10, |caps| caps[stat])
+}
+
+/// Lowers the minimums and raises the maximums of the stats that aren't ignored as far
+/// as `test` still holds, by bisection, so that only the bounds that matter stay tight.
+fn loosen_bounds(dim_export: &mut DimExport, mut test: impl FnMut(&DimExport) -> bool) {
+    for stat in 0..NUM_STATS {
+        let filter = &dim_export.stat_filters[stat];
+        if filter.ignored {
+            continue;
+        }
+        let (min, max, top) = (filter.min, filter.max, top_bound(dim_export, stat));
+        let mut holds_with = |min, max| {
+            let mut e = dim_export.clone();
+            e.stat_filters[stat].min = min;
+            e.stat_filters[stat].max = max;
+            test(&e)
+        };
+
+        let (mut low, mut high) = (0, min);
+        while low < high {
+            let mid = (low + high) / 2;
+            if holds_with(mid, max) {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        let min = high;
+
+        let (mut low, mut high) = (max, top.max(max));
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if holds_with(min, mid) {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        dim_export.stat_filters[stat].min = min;
+        dim_export.stat_filters[stat].max = low;
+    }
+}
+
+/// How far the bounds that aren't ignored are from allowing any value.
+fn bound_units(dim_export: &DimExport) -> usize {
+    (0..NUM_STATS)
+        .filter(|&stat| !dim_export.stat_filters[stat].ignored)
+        .map(|stat| {
+            let filter = &dim_export.stat_filters[stat];
+            let top = top_bound(dim_export, stat);
+            filter.min as usize + top.saturating_sub(filter.max) as usize
+        })
+        .sum()
+}
+
+fn size(dim_export: &DimExport) -> usize {
+    PASSES
+        .iter()
+        .map(|pass| (pass.units)(dim_export))
+        .sum::<usize>()
+        + bound_units(dim_export)
+}
+
+pub struct Minimized {
+    pub export: DimExport,
+    /// How often the predicate was checked.
+    pub tests: usize,
+}
+
+/// Runs every pass until none of them shrinks the export any further.
+/// Returns None if the predicate doesn't hold for the export to begin with.
+pub fn minimize(dim_export: DimExport, predicate: &Predicate) -> Option<Minimized> {
+    // Panics are expected, don't print every one of them
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let minimized = shrink(dim_export, predicate);
+    panic::set_hook(hook);
+    minimized
+}
+
+fn shrink(mut dim_export: DimExport, predicate: &Predicate) -> Option<Minimized> {
+    let mut tests = 1;
+    if !predicate.holds(&dim_export) {
+        return None;
+    }
+    loop {
+        let before = size(&dim_export);
+        for pass in &PASSES {
+            let keep = ddmin((pass.units)(&dim_export), |keep| {
+                tests += 1;
+                predicate.holds(&(pass.keep)(&dim_export, keep))
+            });
+            dim_export = (pass.keep)(&dim_export, &keep);
+        }
+        loosen_bounds(&mut dim_export, |e| {
+            tests += 1;
+            predicate.holds(e)
+        });
+        if size(&dim_export) == before {
+            return Some(Minimized {
+                export: dim_export,
+                tests,
+            });
+        }
+    }
+}
+
+/// What's left, for the summary on stderr.
+pub fn describe(dim_export: &DimExport) -> String {
+    let mods = &dim_export.locked_mods;
+    let constrained = dim_export
+        .stat_filters
+        .iter()
+        .filter(|f| !f.ignored)
+        .count();
+    format!(
+        "{} items, {} locked mods, {} auto mods, {} of {NUM_STATS} stats with bounds",
+        dim_export
+            .filtered_items
+            .iter()
+            .map(Vec::len)
+            .sum::<usize>(),
+        mods.general_mods.len() + mods.combat_mods.len() + mods.activity_mods.len(),
+        dim_export.auto_stat_mods.len(),
+        constrained,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ddmin_finds_minimal_subset() {
+        let mut tests = 0;
+        let keep = ddmin(16, |keep| {
+            tests += 1;
+            [3, 7, 11].iter().all(|i| keep.contains(i))
+        });
+        assert_eq!(keep, [3, 7, 11]);
+        assert!(tests < 16 * 16, "{tests} tests");
+
+        // Nothing to drop
+        assert_eq!(ddmin(3, |keep| keep.len() == 3), [0, 1, 2]);
+        assert!(ddmin(0, |_| true).is_empty());
+    }
+
+    #[test]
+    fn loosen_bounds_keeps_what_matters() {
+        let mut dim_export = export::from_input(
+            &dim_lo_core::generate::generate(&Default::default()),
+            &Default::default(),
+        );
+        for filter in &mut dim_export.stat_filters {
+            filter.min = 7;
+            filter.max = 8;
+            filter.ignored = false;
+        }
+        dim_export.stat_filters[5].ignored = true;
+
+        // Needs a minimum of at least 3 in stat 1 and a maximum of at most 9 in stat 2
+        loosen_bounds(&mut dim_export, |e| {
+            let filters = &e.stat_filters;
+            filters[1].min >= 3 && filters[2].max <= 9
+        });
+        let bounds = dim_export.stat_filters.each_ref().map(|f| (f.min, f.max));
+        assert_eq!(bounds, [(0, 10), (3, 10), (0, 9), (0, 10), (0, 10), (7, 8)]);
+        assert_eq!(bound_units(&dim_export), 3 + 1);
+    }
+}
@@ -0,0 +1,426 @@
+//! Prints results, either as a table for humans or as JSON for diffing and other tools.
+
+use std::io;
+
+use serde::Serialize;
+
+use dim_lo_core::{
+    input::ProcessInput,
+    types::{
+        ProcessArgs, ProcessArmorSet, ProcessMinMaxStats, ProcessMod, ProcessStats, StatRuleset,
+        NUM_ITEM_BUCKETS, NUM_STATS,
+    },
+};
+
+pub const STAT_NAMES: [&str; NUM_STATS] = ["Mob", "Res", "Rec", "Dis", "Int", "Str"];
+pub const BUCKET_NAMES: [&str; NUM_ITEM_BUCKETS] =
+    ["Helmet", "Gauntlets", "Chest", "Legs", "Class"];
+
+/// What a JSON export says about items and sets before they are mapped to LO's small ids.
+/// Empty for packed input and dumps, which only have the ids.
+#[derive(Default)]
+pub struct ExportRefs {
+    /// Name and DIM id of every item, by `ProcessItem::id`.
+    pub items: Vec<(String, String)>,
+    /// DIM set hashes, by `ProcessSetBonus::set_id` - 1.
+    pub set_hashes: Vec<u32>,
+    /// Mod tags (e.g. mod seasons), by bit index in `ProcessMod::mod_tag`.
+    pub tags: Vec<String>,
+}
+
+impl ExportRefs {
+    fn item_name(&self, id: u16) -> String {
+        match self.items.get(id as usize) {
+            Some((name, _)) => name.clone(),
+            None => format!("Item {id}"),
+        }
+    }
+
+    /// The DIM id, or the LO id if there is none.
+    pub fn item_id(&self, id: u16) -> String {
+        match self.items.get(id as usize) {
+            Some((_, dim_id)) => dim_id.clone(),
+            None => id.to_string(),
+        }
+    }
+
+    /// The DIM set hash, or the LO set id if there is none.
+    fn set_hash(&self, set_id: u16) -> u32 {
+        self.set_hashes
+            .get((set_id as usize).wrapping_sub(1))
+            .copied()
+            .unwrap_or(set_id.into())
+    }
+
+    /// The tag name, or the tag bits if there is none.
+    fn mod_tag(&self, m: &ProcessMod) -> Option<String> {
+        let bits = m.mod_tag?.get();
+        let idx = bits.trailing_zeros() as usize;
+        Some(match self.tags.get(idx) {
+            Some(tag) if bits.count_ones() == 1 => tag.clone(),
+            _ => format!("{bits:#x}"),
+        })
+    }
+}
+
+/// Prints the settings of the run, after command line overrides.
+pub fn print_config(
+    input: &ProcessInput,
+    refs: &ExportRefs,
+    mut out: impl io::Write,
+) -> io::Result<()> {
+    let args = &input.args;
+    let ruleset = match args.ruleset {
+        StatRuleset::Tiers => "tiers",
+        StatRuleset::Points => "points",
+    };
+    writeln!(out, "Ruleset: {ruleset}")?;
+    for (idx, name) in STAT_NAMES.iter().enumerate() {
+        writeln!(
+            out,
+            "{name}: {}-{}",
+            args.bounds.lower_bounds[idx], args.bounds.upper_bounds[idx]
+        )?;
+    }
+    writeln!(
+        out,
+        "Any Exotic: {}",
+        if args.any_exotic { "yes" } else { "no" }
+    )?;
+    if args.required_exotic_perk_id != 0 {
+        writeln!(
+            out,
+            "Required Exotic Perk Id: {}",
+            args.required_exotic_perk_id
+        )?;
+    }
+    writeln!(
+        out,
+        "Auto Mods: {} ({} available)",
+        args.auto_mods,
+        input.auto_mods.len()
+    )?;
+    for bonus in args.set_bonuses.iter().filter(|b| b.set_id != 0) {
+        writeln!(
+            out,
+            "Set Bonus: {} x{}",
+            refs.set_hash(bonus.set_id),
+            bonus.count
+        )?;
+    }
+    for (list, mods) in [
+        ("General", &input.general_mods),
+        ("Combat", &input.combat_mods),
+        ("Activity", &input.activity_mods),
+    ] {
+        let mods = mods
+            .iter()
+            .filter_map(|m| {
+                let hash = m.hash?;
+                Some(match refs.mod_tag(m) {
+                    Some(tag) => format!("{hash} ({}, {tag})", m.energy_val),
+                    None => format!("{hash} ({})", m.energy_val),
+                })
+            })
+            .collect::<Vec<_>>();
+        if !mods.is_empty() {
+            writeln!(out, "{list} Mods: {}", mods.join(", "))?;
+        }
+    }
+    let num_items = input.items.each_ref().map(Vec::len);
+    writeln!(out, "Items: {num_items:?}\n")
+}
+
+pub struct Results<'a> {
+    pub info: &'a ProcessStats,
+    /// Best first.
+    pub sets: &'a [ProcessArmorSet],
+    pub min_max: &'a ProcessMinMaxStats,
+    pub args: &'a ProcessArgs,
+    pub refs: &'a ExportRefs,
+}
+
+pub fn print_table(results: &Results, top: usize) {
+    let ProcessStats {
+        num_valid_sets,
+        skipped_low_tier,
+        skipped_stat_range,
+        skipped_mods_unfit,
+        skipped_double_exotic,
+        skipped_no_exotic,
+        skipped_set_bonus,
+    } = *results.info;
+
+    println!(
+        r#"Completed LO Run.
+Num Valid Sets: {num_valid_sets}
+Skipped Low Tier: {skipped_low_tier}
+Skipped Stat Range: {skipped_stat_range}
+Skipped Mods Didn't Fit: {skipped_mods_unfit}
+Skipped Double Exotic: {skipped_double_exotic}
+Skipped No Exotic: {skipped_no_exotic}
+Skipped Set Bonus: {skipped_set_bonus}
+"#
+    );
+
+    println!(
+        "MinMax: {:?} - {:?}",
+        results.min_max.min, results.min_max.max
+    );
+    println!("Num Results: {}", results.sets.len());
+
+    let total = match results.args.ruleset {
+        StatRuleset::Tiers => "Tier",
+        StatRuleset::Points => "Points",
+    };
+    for (rank, set) in results.sets.iter().take(top).enumerate() {
+        println!(
+            "\n#{} - {total} {} - Power {}",
+            rank + 1,
+            set.total_tier,
+            set.power
+        );
+        let header = STAT_NAMES.map(|n| format!("{n:>9}")).concat();
+        println!("  {:<10}{header}", "");
+        let row = |stats: [u16; NUM_STATS]| {
+            stats
+                .map(|s| match results.args.ruleset {
+                    StatRuleset::Tiers => format!("{:>9}", format!("{s} T{}", s.min(100) / 10)),
+                    StatRuleset::Points => format!("{s:>9}"),
+                })
+                .concat()
+        };
+        println!("  {:<10}{}", "Stats", row(set.stats.0));
+        println!("  {:<10}{}", "w/ Bonus", row(set.stats_with_bonus.0));
+        for (bucket, &id) in BUCKET_NAMES.iter().zip(&set.items) {
+            println!(
+                "  {bucket:<10}{} ({})",
+                results.refs.item_name(id),
+                results.refs.item_id(id)
+            );
+        }
+        let mods = set
+            .extra_stat_mods
+            .iter()
+            .flatten()
+            .map(|hash| hash.to_string())
+            .collect::<Vec<_>>();
+        if !mods.is_empty() {
+            println!("  {:<10}{}", "Stat Mods", mods.join(", "));
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonResults {
+    num_valid_sets: u32,
+    skipped_low_tier: u32,
+    skipped_stat_range: u32,
+    skipped_mods_unfit: u32,
+    skipped_double_exotic: u32,
+    skipped_no_exotic: u32,
+    skipped_set_bonus: u32,
+    min_stats: [u16; NUM_STATS],
+    max_stats: [u16; NUM_STATS],
+    num_results: usize,
+    sets: Vec<JsonSet>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonSet {
+    /// DIM item ids in bucket order.
+    items: [String; NUM_ITEM_BUCKETS],
+    power: u16,
+    total_tier: u16,
+    stats: [u16; NUM_STATS],
+    stats_with_bonus: [u16; NUM_STATS],
+    /// Hashes of the auto stat mods.
+    stat_mods: Vec<u32>,
+    set_bonuses: Vec<JsonSetBonus>,
+    tunings: Vec<JsonTuning>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonSetBonus {
+    set_hash: u32,
+    count: u16,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonTuning {
+    /// The index of the tuned item in `items`.
+    item: usize,
+    stat_plus: u8,
+    stat_minus: u8,
+}
+
+pub fn write_json(results: &Results, top: usize, mut out: impl io::Write) -> io::Result<()> {
+    let info = results.info;
+    let refs = results.refs;
+    let sets = results
+        .sets
+        .iter()
+        .take(top)
+        .map(|set| JsonSet {
+            items: set.items.map(|id| refs.item_id(id)),
+            power: set.power,
+            total_tier: set.total_tier,
+            stats: set.stats.0,
+            stats_with_bonus: set.stats_with_bonus.0,
+            stat_mods: set
+                .extra_stat_mods
+                .iter()
+                .flatten()
+                .map(|h| h.get())
+                .collect(),
+            set_bonuses: set
+                .set_bonuses
+                .iter()
+                .filter(|b| b.set_id != 0)
+                .map(|b| JsonSetBonus {
+                    set_hash: refs.set_hash(b.set_id),
+                    count: b.count,
+                })
+                .collect(),
+            tunings: set
+                .tunings
+                .iter()
+                .enumerate()
+                .filter(|(_, t)| t.stat_plus != t.stat_minus)
+                .map(|(item, t)| JsonTuning {
+                    item,
+                    stat_plus: t.stat_plus,
+                    stat_minus: t.stat_minus,
+                })
+                .collect(),
+        })
+        .collect();
+
+    let json = JsonResults {
+        num_valid_sets: info.num_valid_sets,
+        skipped_low_tier: info.skipped_low_tier,
+        skipped_stat_range: info.skipped_stat_range,
+        skipped_mods_unfit: info.skipped_mods_unfit,
+        skipped_double_exotic: info.skipped_double_exotic,
+        skipped_no_exotic: info.skipped_no_exotic,
+        skipped_set_bonus: info.skipped_set_bonus,
+        min_stats: results.min_max.min,
+        max_stats: results.min_max.max,
+        num_results: results.sets.len(),
+        sets,
+    };
+    serde_json::to_writer_pretty(&mut out, &json)?;
+    writeln!(out)
+}
+
+/// All results of `input` as JSON, like `write_json` writes them, with `refs` for item ids.
+pub fn results_json(input: &ProcessInput, refs: &ExportRefs) -> Result<String, String> {
+    let (info, sets, min_max) = input.process().map_err(|e| e.to_string())?;
+    let results = Results {
+        info: &info,
+        sets: &sets,
+        min_max: &min_max,
+        args: &input.args,
+        refs,
+    };
+    let mut json = vec![];
+    write_json(&results, usize::MAX, &mut json).map_err(|e| e.to_string())?;
+    Ok(String::from_utf8(json).expect("serde_json writes UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use dim_lo_core::types::{ProcessSetBonus, ProcessTuning, Stats, NUM_SET_BONUSES};
+
+    use super::*;
+
+    #[test]
+    fn json_schema() {
+        let refs = ExportRefs {
+            items: (0..5)
+                .map(|id| (format!("Item {id}"), format!("69175290000000000{id:02}")))
+                .collect(),
+            set_hashes: vec![1234],
+            tags: vec![],
+        };
+        let mut set_bonuses = [ProcessSetBonus::default(); NUM_SET_BONUSES];
+        set_bonuses[0] = ProcessSetBonus {
+            set_id: 1,
+            count: 2,
+        };
+        let mut tunings = [ProcessTuning::default(); NUM_ITEM_BUCKETS];
+        tunings[3] = ProcessTuning {
+            stat_plus: 1,
+            stat_minus: 4,
+        };
+        let set = ProcessArmorSet {
+            stats: Stats([100, 50, 30, 20, 10, 0]),
+            // Item 5 has no reference, so it keeps its LO id
+            items: [0, 1, 2, 3, 5],
+            power: 1810,
+            total_tier: 20,
+            extra_stat_mods: [NonZeroU32::new(7), None, NonZeroU32::new(8), None, None],
+            stats_with_bonus: Stats([100, 60, 30, 20, 10, 0]),
+            set_bonuses,
+            tunings,
+        };
+        let info = ProcessStats {
+            num_valid_sets: 2,
+            skipped_mods_unfit: 3,
+            ..Default::default()
+        };
+        let min_max = ProcessMinMaxStats {
+            min: [90, 50, 30, 20, 10, 0],
+            max: [100, 50, 30, 20, 10, 0],
+        };
+        let args = ProcessArgs::default();
+        let results = Results {
+            info: &info,
+            sets: &[set, set],
+            min_max: &min_max,
+            args: &args,
+            refs: &refs,
+        };
+
+        let mut json = vec![];
+        write_json(&results, 1, &mut json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "numValidSets": 2,
+                "skippedLowTier": 0,
+                "skippedStatRange": 0,
+                "skippedModsUnfit": 3,
+                "skippedDoubleExotic": 0,
+                "skippedNoExotic": 0,
+                "skippedSetBonus": 0,
+                "minStats": [90, 50, 30, 20, 10, 0],
+                "maxStats": [100, 50, 30, 20, 10, 0],
+                "numResults": 2,
+                "sets": [{
+                    "items": [
+                        "6917529000000000000",
+                        "6917529000000000001",
+                        "6917529000000000002",
+                        "6917529000000000003",
+                        "5"
+                    ],
+                    "power": 1810,
+                    "totalTier": 20,
+                    "stats": [100, 50, 30, 20, 10, 0],
+                    "statsWithBonus": [100, 60, 30, 20, 10, 0],
+                    "statMods": [7, 8],
+                    "setBonuses": [{ "setHash": 1234, "count": 2 }],
+                    "tunings": [{ "item": 3, "statPlus": 1, "statMinus": 4 }]
+                }]
+            })
+        );
+    }
+}
@@ -0,0 +1,322 @@
+//! `lo-offline regression`: writes a lo-core test module that runs an input and pins the
+//! results LO gives for it now, for turning a (minimized) bug report into a regression test.
+
+use std::fmt::{self, Write};
+
+use dim_lo_core::{
+    input::ProcessInput,
+    types::{
+        BonusStatMode, EnergyType, ProcessArmorSet, ProcessMinMaxStats, ProcessMod, ProcessStats,
+        StatRuleset, NUM_ITEM_BUCKETS, NUM_STATS,
+    },
+};
+
+use crate::output::{ExportRefs, BUCKET_NAMES};
+
+/// A test function name from e.g. a file name: `check_` and the name in snake case.
+pub fn test_name(name: &str) -> String {
+    let mut test = "check_".to_owned();
+    for c in name.chars() {
+        match c {
+            'a'..='z' | '0'..='9' => test.push(c),
+            'A'..='Z' => test.push(c.to_ascii_lowercase()),
+            _ if !test.ends_with('_') => test.push('_'),
+            _ => {}
+        }
+    }
+    test.trim_end_matches('_').to_owned()
+}
+
+fn energy_type(e: EnergyType) -> &'static str {
+    match e {
+        EnergyType::Any => "EnergyType::Any",
+        EnergyType::Arc => "EnergyType::Arc",
+        EnergyType::Solar => "EnergyType::Solar",
+        EnergyType::Void => "EnergyType::Void",
+        EnergyType::Stasis => "EnergyType::Stasis",
+    }
+}
+
+fn non_zero(value: Option<std::num::NonZeroU32>) -> String {
+    match value {
+        Some(v) => format!("NonZeroU32::new({v})"),
+        None => "None".to_owned(),
+    }
+}
+
+/// A `ProcessMod` literal, with its lines after the first indented by `indent`.
+fn mod_literal(m: &ProcessMod, indent: &str) -> String {
+    format!(
+        "ProcessMod {{\n\
+         {indent}    hash: {},\n\
+         {indent}    mod_tag: {},\n\
+         {indent}    energy_type: {},\n\
+         {indent}    energy_val: {},\n\
+         {indent}}}",
+        non_zero(m.hash),
+        non_zero(m.mod_tag),
+        energy_type(m.energy_type),
+        m.energy_val
+    )
+}
+
+fn write_mods(out: &mut String, name: &str, mods: &[ProcessMod; NUM_ITEM_BUCKETS]) -> fmt::Result {
+    if mods.iter().all(|m| m.hash.is_none()) {
+        return writeln!(out, "    let {name} = [NO_MOD; NUM_ITEM_BUCKETS];");
+    }
+    writeln!(out, "    let {name} = [")?;
+    for m in mods {
+        match m.hash {
+            Some(_) => writeln!(out, "        {},", mod_literal(m, "        "))?,
+            None => writeln!(out, "        NO_MOD,")?,
+        }
+    }
+    writeln!(out, "    ];")
+}
+
+fn write_items(out: &mut String, input: &ProcessInput, refs: &ExportRefs) -> fmt::Result {
+    writeln!(out, "    let items: [&[_]; NUM_ITEM_BUCKETS] = [")?;
+    for (bucket, items) in input.items.iter().enumerate() {
+        if items.is_empty() {
+            writeln!(out, "        &[],")?;
+            continue;
+        }
+        writeln!(out, "        &[")?;
+        for item in items {
+            match refs.items.get(item.id as usize) {
+                Some((name, id)) => writeln!(out, "            // {name} ({id})")?,
+                None => writeln!(out, "            // {} {}", BUCKET_NAMES[bucket], item.id)?,
+            }
+            writeln!(out, "            ProcessItem {{")?;
+            writeln!(out, "                id: {},", item.id)?;
+            writeln!(out, "                power: {},", item.power)?;
+            writeln!(
+                out,
+                "                energy_type: {},",
+                energy_type(item.energy_type)
+            )?;
+            writeln!(out, "                energy_val: {},", item.energy_val)?;
+            writeln!(out, "                energy_cap: {},", item.energy_cap)?;
+            writeln!(out, "                exotic: {},", item.exotic)?;
+            writeln!(out, "                mod_tags: {:#x},", item.mod_tags)?;
+            writeln!(out, "                stats: Stats({:?}),", item.stats.0)?;
+            // The rest only if it isn't the default
+            if item.bonus_stats.0 != [0; NUM_STATS] {
+                let mode = match item.bonus_mode {
+                    BonusStatMode::Always => "BonusStatMode::Always",
+                    BonusStatMode::Conditional => "BonusStatMode::Conditional",
+                };
+                let bonus_stats = item.bonus_stats.0;
+                writeln!(
+                    out,
+                    "                bonus_stats: SignedStats({bonus_stats:?}),"
+                )?;
+                writeln!(out, "                bonus_mode: {mode},")?;
+            }
+            if item.tuning_stats != 0 {
+                writeln!(
+                    out,
+                    "                tuning_stats: {:#08b},",
+                    item.tuning_stats
+                )?;
+            }
+            if item.set_id != 0 {
+                writeln!(out, "                set_id: {},", item.set_id)?;
+            }
+            if item.exotic_perk_id != 0 {
+                writeln!(
+                    out,
+                    "                exotic_perk_id: {},",
+                    item.exotic_perk_id
+                )?;
+            }
+            if item.bonus_stats.0 == [0; NUM_STATS]
+                || item.tuning_stats == 0
+                || item.set_id == 0
+                || item.exotic_perk_id == 0
+            {
+                writeln!(out, "                ..Default::default()")?;
+            }
+            writeln!(out, "            }},")?;
+        }
+        writeln!(out, "        ],")?;
+    }
+    writeln!(out, "    ];")
+}
+
+fn write_args(out: &mut String, input: &ProcessInput) -> fmt::Result {
+    let args = &input.args;
+    let ruleset = match args.ruleset {
+        StatRuleset::Tiers => "StatRuleset::Tiers",
+        StatRuleset::Points => "StatRuleset::Points",
+    };
+    writeln!(out, "    let args = ProcessArgs {{")?;
+    writeln!(
+        out,
+        "        base_stats: SignedStats({:?}),",
+        args.base_stats.0
+    )?;
+    writeln!(out, "        bounds: ProcessTierBounds {{")?;
+    writeln!(
+        out,
+        "            lower_bounds: {:?},",
+        args.bounds.lower_bounds
+    )?;
+    writeln!(
+        out,
+        "            upper_bounds: {:?},",
+        args.bounds.upper_bounds
+    )?;
+    writeln!(out, "        }},")?;
+    writeln!(out, "        any_exotic: {},", args.any_exotic)?;
+    writeln!(out, "        auto_mods: {},", args.auto_mods)?;
+    writeln!(out, "        ruleset: {ruleset},")?;
+    writeln!(out, "        stat_caps: {:?},", args.stat_caps)?;
+    writeln!(out, "        set_bonuses: [")?;
+    for bonus in &args.set_bonuses {
+        writeln!(out, "            ProcessSetBonus {{")?;
+        writeln!(out, "                set_id: {},", bonus.set_id)?;
+        writeln!(out, "                count: {},", bonus.count)?;
+        writeln!(out, "            }},")?;
+    }
+    writeln!(out, "        ],")?;
+    writeln!(
+        out,
+        "        required_exotic_perk_id: {},",
+        args.required_exotic_perk_id
+    )?;
+    writeln!(out, "    }};")
+}
+
+const RUN: &str = "
+    let (info, sets, min_max) = dim_lo_process(
+        items,
+        &general_mods,
+        &combat_mods,
+        &activity_mods,
+        &auto_mods,
+        &args,
+    );
+
+";
+
+const CHECK_BEST: &str = "    ];
+    for (set, (items, total_tier, stats, mods)) in sets.iter().zip(best) {
+        assert_eq!(set.items, items);
+        assert_eq!(set.total_tier, total_tier);
+        assert_eq!(set.stats, stats);
+        assert_eq!(
+            set.extra_stat_mods.map(|m| m.map_or(0, NonZeroU32::get)),
+            mods
+        );
+    }
+";
+
+/// Everything `dim_lo_process` takes.
+fn write_setup(out: &mut String, input: &ProcessInput, refs: &ExportRefs) -> fmt::Result {
+    write_items(out, input, refs)?;
+    write_mods(out, "general_mods", &input.general_mods)?;
+    write_mods(out, "combat_mods", &input.combat_mods)?;
+    write_mods(out, "activity_mods", &input.activity_mods)?;
+    if input.auto_mods.is_empty() {
+        writeln!(out, "    let auto_mods: [ProcessStatMod; 0] = [];")?;
+    } else {
+        writeln!(out, "    let auto_mods = [")?;
+        for m in &input.auto_mods {
+            let inner_mod = mod_literal(&m.inner_mod, "            ");
+            writeln!(out, "        ProcessStatMod {{")?;
+            writeln!(out, "            inner_mod: {inner_mod},")?;
+            writeln!(out, "            stats: Stats({:?}),", m.stats.0)?;
+            writeln!(out, "        }},")?;
+        }
+        writeln!(out, "    ];")?;
+    }
+    write_args(out, input)
+}
+
+/// Assertions on everything `dim_lo_process` returns, but only `top` of the sets.
+fn write_assertions(
+    out: &mut String,
+    (info, sets, min_max): &(ProcessStats, Vec<ProcessArmorSet>, ProcessMinMaxStats),
+    top: usize,
+) -> fmt::Result {
+    let ProcessStats {
+        num_valid_sets,
+        skipped_low_tier,
+        skipped_stat_range,
+        skipped_mods_unfit,
+        skipped_double_exotic,
+        skipped_no_exotic,
+        skipped_set_bonus,
+    } = *info;
+    for (field, value) in [
+        ("num_valid_sets", num_valid_sets),
+        ("skipped_low_tier", skipped_low_tier),
+        ("skipped_stat_range", skipped_stat_range),
+        ("skipped_mods_unfit", skipped_mods_unfit),
+        ("skipped_double_exotic", skipped_double_exotic),
+        ("skipped_no_exotic", skipped_no_exotic),
+        ("skipped_set_bonus", skipped_set_bonus),
+    ] {
+        writeln!(out, "    assert_eq!(info.{field}, {value});")?;
+    }
+    writeln!(out, "    assert_eq!(min_max.min, {:?});", min_max.min)?;
+    writeln!(out, "    assert_eq!(min_max.max, {:?});", min_max.max)?;
+    writeln!(out, "    assert_eq!(sets.len(), {});", sets.len())?;
+
+    let best = &sets[..top.min(sets.len())];
+    if best.is_empty() {
+        return Ok(());
+    }
+    writeln!(
+        out,
+        "\n    // The best sets: items, total tier, stats and auto stat mods"
+    )?;
+    writeln!(out, "    let best = [")?;
+    for set in best {
+        let mods = set.extra_stat_mods.map(|m| m.map_or(0, |m| m.get()));
+        writeln!(out, "        (")?;
+        writeln!(out, "            {:?},", set.items)?;
+        writeln!(out, "            {},", set.total_tier)?;
+        writeln!(out, "            Stats({:?}),", set.stats.0)?;
+        writeln!(out, "            {mods:?},")?;
+        writeln!(out, "        ),")?;
+    }
+    out.push_str(CHECK_BEST);
+    Ok(())
+}
+
+/// A test module named `name` that runs `input` and checks the results against what LO
+/// gives now, `top` of the best sets in detail. It mentions `source` in its docs.
+/// The code is close to, but not always exactly, what rustfmt makes of it.
+pub fn write_test(
+    input: &ProcessInput,
+    refs: &ExportRefs,
+    name: &str,
+    source: &str,
+    top: usize,
+) -> Result<String, String> {
+    let results = input.process().map_err(|e| e.to_string())?;
+    let mut body = format!("#[test]\nfn {name}() {{\n");
+    write_setup(&mut body, input, refs)
+        .and_then(|()| {
+            body.push_str(RUN);
+            write_assertions(&mut body, &results, top)
+        })
+        .expect("writing to a String can't fail");
+    body.push_str("}\n");
+
+    let mut out = format!(
+        "//! Regression test generated by `lo-offline regression` from `{source}`.\n\
+         //! The assertions pin the results LO gave then, regenerate it if a change in\n\
+         //! behavior is intended.\n\n"
+    );
+    if body.contains("NonZeroU32") {
+        out.push_str("use core::num::NonZeroU32;\n\n");
+    }
+    out.push_str("use crate::{dim_lo_process, types::*};\n\n");
+    let no_mod = mod_literal(&crate::export::EMPTY_MOD, "");
+    out.push_str(&format!("const NO_MOD: ProcessMod = {no_mod};\n\n"));
+    out.push_str(&body);
+    Ok(out)
+}
@@ -0,0 +1,31 @@
+//! Runs `lo-offline golden` over the exports in `goldens`, which fails if any of them
+//! doesn't give its expected results. If the change is intended, rewrite them with
+//! `cargo run -p lo-offline -- golden --update lo-offline/goldens`.
+
+use std::process::Command;
+
+#[test]
+fn goldens() {
+    let output = Command::new(env!("CARGO_BIN_EXE_lo-offline"))
+        .arg("golden")
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/goldens"))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "results differ from the goldens:\n{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn no_exports_fail() {
+    let dir = concat!(env!("CARGO_TARGET_TMPDIR"), "/no_goldens");
+    std::fs::create_dir_all(dir).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_lo-offline"))
+        .args(["golden", dir])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
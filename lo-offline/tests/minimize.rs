@@ -0,0 +1,84 @@
+//! Runs `lo-offline minimize` on generated exports, where LO and the oracle agree.
+
+use std::{fs, process::Command};
+
+use serde_json::{json, Value};
+
+fn lo_offline(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_lo-offline"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn oracle_predicate_does_not_hold() {
+    let path = concat!(env!("CARGO_TARGET_TMPDIR"), "/generated_seed_3.json");
+    let output = lo_offline(&["generate", "--seed", "3", "--items", "3", path]);
+    assert!(output.status.success());
+
+    // Fewer auto mods than the default, the oracle is slow with all 5
+    let output = lo_offline(&[
+        "minimize",
+        "--predicate",
+        "oracle",
+        "--auto-mods",
+        "2",
+        path,
+    ]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        !output.status.success() && stderr.contains("the predicate doesn't hold"),
+        "minimized an export without a bug:\n{stderr}"
+    );
+}
+
+#[test]
+fn missing_set_minimized() {
+    let path = concat!(env!("CARGO_TARGET_TMPDIR"), "/generated_missing.json");
+    let output = lo_offline(&["generate", "--items", "4", "--exotics", "0", path]);
+    assert!(output.status.success());
+
+    // The first item of every bucket without stats makes the uniquely worst of the 1024
+    // sets, so it's valid but not among the 200 results until only 200 sets are left
+    let mut dim_export: Value = serde_json::from_slice(&fs::read(path).unwrap()).unwrap();
+    let mut ids = vec![];
+    for bucket in dim_export["filteredItems"].as_array_mut().unwrap() {
+        bucket[0]["stats"] = json!([0, 0, 0, 0, 0, 0]);
+        ids.push(bucket[0]["id"].as_str().unwrap().to_owned());
+    }
+    // A bound that doesn't matter, which should end up as loose as it gets
+    dim_export["statFilters"][3]["max"] = json!(9);
+    fs::write(path, dim_export.to_string()).unwrap();
+
+    let predicate = format!("missing:{}", ids.join(","));
+    let output = lo_offline(&[
+        "minimize",
+        "--predicate",
+        &predicate,
+        "--auto-mods",
+        "0",
+        path,
+    ]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    let minimized: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    // More than 200 sets are left, but dropping any item would leave 200 or fewer
+    let buckets = minimized["filteredItems"].as_array().unwrap();
+    let sizes = buckets.iter().map(|b| b.as_array().unwrap().len());
+    let num_sets = sizes.clone().product::<usize>();
+    assert!(num_sets > 200, "{num_sets} sets");
+    for size in sizes {
+        assert!(num_sets / size * (size - 1) <= 200, "{num_sets} sets");
+    }
+    for (bucket, id) in buckets.iter().zip(&ids) {
+        assert!(bucket
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|item| item["id"] == *id));
+    }
+    let filter = &minimized["statFilters"][3];
+    assert!(filter["ignored"] == true || filter["max"] == 10, "{filter}");
+}
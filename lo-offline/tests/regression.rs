@@ -0,0 +1,27 @@
+//! Checks that the regression tests in lo-core are what `lo-offline regression` writes
+//! for the exports next to them.
+
+use std::process::Command;
+
+#[test]
+fn small_inventory_up_to_date() {
+    let export = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../lo-core/src/tests/small_inventory.json"
+    );
+    let output = Command::new(env!("CARGO_BIN_EXE_lo-offline"))
+        .args(["regression", "--top", "3", export])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        output.stdout == include_bytes!("../../lo-core/src/tests/small_inventory.rs"),
+        "lo-core/src/tests/small_inventory.rs is out of date, run `lo-offline regression \
+         --top 3 --output lo-core/src/tests/small_inventory.rs \
+         lo-core/src/tests/small_inventory.json`"
+    );
+}
@@ -0,0 +1,874 @@
+// Generated by `cargo compile` from the lo-core and lo-web sources. Do not edit.
+
+/** An address in the memory of the WASM module. */
+export type Ptr = number;
+
+function times<T>(n: number, f: (i: number) => T): T[] {
+  const result: T[] = [];
+  for (let i = 0; i < n; i++) {
+    result.push(f(i));
+  }
+  return result;
+}
+
+/** Reads `len` consecutive structs of `size` bytes each, starting at `ptr`. */
+export function readArray<T>(
+  view: DataView,
+  ptr: Ptr,
+  len: number,
+  size: number,
+  read: (view: DataView, ptr: Ptr) => T,
+): T[] {
+  return times(len, (i) => read(view, ptr + i * size));
+}
+
+/** Writes `values` as consecutive structs of `size` bytes each, starting at `ptr`. */
+export function writeArray<T>(
+  view: DataView,
+  ptr: Ptr,
+  values: readonly T[],
+  size: number,
+  write: (view: DataView, ptr: Ptr, value: T) => void,
+): void {
+  values.forEach((value, i) => write(view, ptr + i * size, value));
+}
+
+export enum EnergyType {
+  Any = 0,
+  Arc = 1,
+  Solar = 2,
+  Void = 3,
+  Stasis = 4,
+}
+
+export enum BonusStatMode {
+  Always = 0,
+  Conditional = 1,
+}
+
+export enum StatRuleset {
+  Tiers = 0,
+  Points = 1,
+}
+
+export interface ProcessItem {
+  id: number;
+  power: number;
+  energyType: EnergyType;
+  energyVal: number;
+  energyCap: number;
+  exotic: boolean;
+  modTags: number;
+  stats: number[];
+  bonusStats: number[];
+  bonusMode: BonusStatMode;
+  tuningStats: number;
+  setId: number;
+  exoticPerkId: number;
+}
+
+export const PROCESS_ITEM_SIZE = 44;
+
+export function readProcessItem(view: DataView, ptr: Ptr): ProcessItem {
+  return {
+    id: view.getUint16(ptr + 0, true),
+    power: view.getUint16(ptr + 2, true),
+    energyType: view.getUint8(ptr + 4) as EnergyType,
+    energyVal: view.getUint8(ptr + 5),
+    energyCap: view.getUint8(ptr + 6),
+    exotic: view.getUint8(ptr + 7) !== 0,
+    modTags: view.getUint32(ptr + 8, true),
+    stats: times(6, (i) => view.getUint16(ptr + 12 + 2 * i, true)),
+    bonusStats: times(6, (i) => view.getInt16(ptr + 24 + 2 * i, true)),
+    bonusMode: view.getUint8(ptr + 36) as BonusStatMode,
+    tuningStats: view.getUint8(ptr + 37),
+    setId: view.getUint16(ptr + 38, true),
+    exoticPerkId: view.getUint32(ptr + 40, true),
+  };
+}
+
+export function writeProcessItem(view: DataView, ptr: Ptr, value: ProcessItem): void {
+  view.setUint16(ptr + 0, value.id, true);
+  view.setUint16(ptr + 2, value.power, true);
+  view.setUint8(ptr + 4, value.energyType);
+  view.setUint8(ptr + 5, value.energyVal);
+  view.setUint8(ptr + 6, value.energyCap);
+  view.setUint8(ptr + 7, value.exotic ? 1 : 0);
+  view.setUint32(ptr + 8, value.modTags, true);
+  for (let i = 0; i < 6; i++) {
+    view.setUint16(ptr + 12 + 2 * i, value.stats[i], true);
+  }
+  for (let i = 0; i < 6; i++) {
+    view.setInt16(ptr + 24 + 2 * i, value.bonusStats[i], true);
+  }
+  view.setUint8(ptr + 36, value.bonusMode);
+  view.setUint8(ptr + 37, value.tuningStats);
+  view.setUint16(ptr + 38, value.setId, true);
+  view.setUint32(ptr + 40, value.exoticPerkId, true);
+}
+
+export interface ProcessMod {
+  hash: number;
+  modTag: number;
+  energyType: EnergyType;
+  energyVal: number;
+}
+
+export const PROCESS_MOD_SIZE = 12;
+
+export function readProcessMod(view: DataView, ptr: Ptr): ProcessMod {
+  return {
+    hash: view.getUint32(ptr + 0, true),
+    modTag: view.getUint32(ptr + 4, true),
+    energyType: view.getUint8(ptr + 8) as EnergyType,
+    energyVal: view.getUint8(ptr + 9),
+  };
+}
+
+export function writeProcessMod(view: DataView, ptr: Ptr, value: ProcessMod): void {
+  view.setUint32(ptr + 0, value.hash, true);
+  view.setUint32(ptr + 4, value.modTag, true);
+  view.setUint8(ptr + 8, value.energyType);
+  view.setUint8(ptr + 9, value.energyVal);
+}
+
+export interface ProcessStatMod {
+  innerMod: ProcessMod;
+  stats: number[];
+}
+
+export const PROCESS_STAT_MOD_SIZE = 24;
+
+export function readProcessStatMod(view: DataView, ptr: Ptr): ProcessStatMod {
+  return {
+    innerMod: readProcessMod(view, ptr + 0),
+    stats: times(6, (i) => view.getUint16(ptr + 12 + 2 * i, true)),
+  };
+}
+
+export function writeProcessStatMod(view: DataView, ptr: Ptr, value: ProcessStatMod): void {
+  writeProcessMod(view, ptr + 0, value.innerMod);
+  for (let i = 0; i < 6; i++) {
+    view.setUint16(ptr + 12 + 2 * i, value.stats[i], true);
+  }
+}
+
+export interface ProcessArmorSet {
+  stats: number[];
+  items: number[];
+  power: number;
+  totalTier: number;
+  extraStatMods: number[];
+  statsWithBonus: number[];
+  setBonuses: ProcessSetBonus[];
+  tunings: ProcessTuning[];
+}
+
+export const PROCESS_ARMOR_SET_SIZE = 80;
+
+export function readProcessArmorSet(view: DataView, ptr: Ptr): ProcessArmorSet {
+  return {
+    stats: times(6, (i) => view.getUint16(ptr + 0 + 2 * i, true)),
+    items: times(5, (i) => view.getUint16(ptr + 12 + 2 * i, true)),
+    power: view.getUint16(ptr + 22, true),
+    totalTier: view.getUint16(ptr + 24, true),
+    extraStatMods: times(5, (i) => view.getUint32(ptr + 28 + 4 * i, true)),
+    statsWithBonus: times(6, (i) => view.getUint16(ptr + 48 + 2 * i, true)),
+    setBonuses: times(2, (i) => readProcessSetBonus(view, ptr + 60 + 4 * i)),
+    tunings: times(5, (i) => readProcessTuning(view, ptr + 68 + 2 * i)),
+  };
+}
+
+export function writeProcessArmorSet(view: DataView, ptr: Ptr, value: ProcessArmorSet): void {
+  for (let i = 0; i < 6; i++) {
+    view.setUint16(ptr + 0 + 2 * i, value.stats[i], true);
+  }
+  for (let i = 0; i < 5; i++) {
+    view.setUint16(ptr + 12 + 2 * i, value.items[i], true);
+  }
+  view.setUint16(ptr + 22, value.power, true);
+  view.setUint16(ptr + 24, value.totalTier, true);
+  for (let i = 0; i < 5; i++) {
+    view.setUint32(ptr + 28 + 4 * i, value.extraStatMods[i], true);
+  }
+  for (let i = 0; i < 6; i++) {
+    view.setUint16(ptr + 48 + 2 * i, value.statsWithBonus[i], true);
+  }
+  for (let i = 0; i < 2; i++) {
+    writeProcessSetBonus(view, ptr + 60 + 4 * i, value.setBonuses[i]);
+  }
+  for (let i = 0; i < 5; i++) {
+    writeProcessTuning(view, ptr + 68 + 2 * i, value.tunings[i]);
+  }
+}
+
+export interface ProcessStats {
+  numValidSets: number;
+  skippedLowTier: number;
+  skippedStatRange: number;
+  skippedModsUnfit: number;
+  skippedDoubleExotic: number;
+  skippedNoExotic: number;
+  skippedSetBonus: number;
+}
+
+export const PROCESS_STATS_SIZE = 28;
+
+export function readProcessStats(view: DataView, ptr: Ptr): ProcessStats {
+  return {
+    numValidSets: view.getUint32(ptr + 0, true),
+    skippedLowTier: view.getUint32(ptr + 4, true),
+    skippedStatRange: view.getUint32(ptr + 8, true),
+    skippedModsUnfit: view.getUint32(ptr + 12, true),
+    skippedDoubleExotic: view.getUint32(ptr + 16, true),
+    skippedNoExotic: view.getUint32(ptr + 20, true),
+    skippedSetBonus: view.getUint32(ptr + 24, true),
+  };
+}
+
+export function writeProcessStats(view: DataView, ptr: Ptr, value: ProcessStats): void {
+  view.setUint32(ptr + 0, value.numValidSets, true);
+  view.setUint32(ptr + 4, value.skippedLowTier, true);
+  view.setUint32(ptr + 8, value.skippedStatRange, true);
+  view.setUint32(ptr + 12, value.skippedModsUnfit, true);
+  view.setUint32(ptr + 16, value.skippedDoubleExotic, true);
+  view.setUint32(ptr + 20, value.skippedNoExotic, true);
+  view.setUint32(ptr + 24, value.skippedSetBonus, true);
+}
+
+export interface ProcessTierBounds {
+  lowerBounds: number[];
+  upperBounds: number[];
+}
+
+export const PROCESS_TIER_BOUNDS_SIZE = 24;
+
+export function readProcessTierBounds(view: DataView, ptr: Ptr): ProcessTierBounds {
+  return {
+    lowerBounds: times(6, (i) => view.getUint16(ptr + 0 + 2 * i, true)),
+    upperBounds: times(6, (i) => view.getUint16(ptr + 12 + 2 * i, true)),
+  };
+}
+
+export function writeProcessTierBounds(view: DataView, ptr: Ptr, value: ProcessTierBounds): void {
+  for (let i = 0; i < 6; i++) {
+    view.setUint16(ptr + 0 + 2 * i, value.lowerBounds[i], true);
+  }
+  for (let i = 0; i < 6; i++) {
+    view.setUint16(ptr + 12 + 2 * i, value.upperBounds[i], true);
+  }
+}
+
+export interface ProcessMinMaxStats {
+  min: number[];
+  max: number[];
+}
+
+export const PROCESS_MIN_MAX_STATS_SIZE = 24;
+
+export function readProcessMinMaxStats(view: DataView, ptr: Ptr): ProcessMinMaxStats {
+  return {
+    min: times(6, (i) => view.getUint16(ptr + 0 + 2 * i, true)),
+    max: times(6, (i) => view.getUint16(ptr + 12 + 2 * i, true)),
+  };
+}
+
+export function writeProcessMinMaxStats(view: DataView, ptr: Ptr, value: ProcessMinMaxStats): void {
+  for (let i = 0; i < 6; i++) {
+    view.setUint16(ptr + 0 + 2 * i, value.min[i], true);
+  }
+  for (let i = 0; i < 6; i++) {
+    view.setUint16(ptr + 12 + 2 * i, value.max[i], true);
+  }
+}
+
+export interface ProcessSetBonus {
+  setId: number;
+  count: number;
+}
+
+export const PROCESS_SET_BONUS_SIZE = 4;
+
+export function readProcessSetBonus(view: DataView, ptr: Ptr): ProcessSetBonus {
+  return {
+    setId: view.getUint16(ptr + 0, true),
+    count: view.getUint16(ptr + 2, true),
+  };
+}
+
+export function writeProcessSetBonus(view: DataView, ptr: Ptr, value: ProcessSetBonus): void {
+  view.setUint16(ptr + 0, value.setId, true);
+  view.setUint16(ptr + 2, value.count, true);
+}
+
+export interface ProcessTuning {
+  statPlus: number;
+  statMinus: number;
+}
+
+export const PROCESS_TUNING_SIZE = 2;
+
+export function readProcessTuning(view: DataView, ptr: Ptr): ProcessTuning {
+  return {
+    statPlus: view.getUint8(ptr + 0),
+    statMinus: view.getUint8(ptr + 1),
+  };
+}
+
+export function writeProcessTuning(view: DataView, ptr: Ptr, value: ProcessTuning): void {
+  view.setUint8(ptr + 0, value.statPlus);
+  view.setUint8(ptr + 1, value.statMinus);
+}
+
+export interface ProcessArgs {
+  baseStats: number[];
+  bounds: ProcessTierBounds;
+  anyExotic: boolean;
+  autoMods: number;
+  ruleset: StatRuleset;
+  statCaps: number[];
+  setBonuses: ProcessSetBonus[];
+  requiredExoticPerkId: number;
+}
+
+export const PROCESS_ARGS_SIZE = 64;
+
+export function readProcessArgs(view: DataView, ptr: Ptr): ProcessArgs {
+  return {
+    baseStats: times(6, (i) => view.getInt16(ptr + 0 + 2 * i, true)),
+    bounds: readProcessTierBounds(view, ptr + 12),
+    anyExotic: view.getUint8(ptr + 36) !== 0,
+    autoMods: view.getUint8(ptr + 37),
+    ruleset: view.getUint8(ptr + 38) as StatRuleset,
+    statCaps: times(6, (i) => view.getUint16(ptr + 40 + 2 * i, true)),
+    setBonuses: times(2, (i) => readProcessSetBonus(view, ptr + 52 + 4 * i)),
+    requiredExoticPerkId: view.getUint32(ptr + 60, true),
+  };
+}
+
+export function writeProcessArgs(view: DataView, ptr: Ptr, value: ProcessArgs): void {
+  for (let i = 0; i < 6; i++) {
+    view.setInt16(ptr + 0 + 2 * i, value.baseStats[i], true);
+  }
+  writeProcessTierBounds(view, ptr + 12, value.bounds);
+  view.setUint8(ptr + 36, value.anyExotic ? 1 : 0);
+  view.setUint8(ptr + 37, value.autoMods);
+  view.setUint8(ptr + 38, value.ruleset);
+  for (let i = 0; i < 6; i++) {
+    view.setUint16(ptr + 40 + 2 * i, value.statCaps[i], true);
+  }
+  for (let i = 0; i < 2; i++) {
+    writeProcessSetBonus(view, ptr + 52 + 4 * i, value.setBonuses[i]);
+  }
+  view.setUint32(ptr + 60, value.requiredExoticPerkId, true);
+}
+
+/**
+ * Why `lo_validate` or `lo_run` rejected a context, 0 means nothing was wrong.
+ * The numbers are part of the FFI and must not change.
+ */
+export enum ValidationCode {
+  Ok = 0,
+  /**
+   * The number of items per bucket adds up to more than the item buffer holds.
+   * The index is the first bucket that doesn't fit.
+   */
+  TooManyItems = 1,
+  /**
+   * An item's `energy_type` isn't a valid `EnergyType`. The index is the item.
+   */
+  InvalidItemEnergyType = 2,
+  /**
+   * An item's `exotic` is neither 0 nor 1. The index is the item.
+   */
+  InvalidItemExotic = 3,
+  /**
+   * An item's `bonus_mode` isn't a valid `BonusStatMode`. The index is the item.
+   */
+  InvalidItemBonusMode = 4,
+  /**
+   * An item's `energy_val` is greater than its `energy_cap`. The index is the item.
+   */
+  ItemEnergyOverCapacity = 5,
+  /**
+   * A mod's `energy_type` isn't a valid `EnergyType`. The index is the mod slot (0-14).
+   */
+  InvalidModEnergyType = 6,
+  /**
+   * An empty mod slot (hash 0) isn't zeroed entirely. The index is the mod slot (0-14).
+   */
+  ModNotZeroed = 7,
+  /**
+   * An auto stat mod's `energy_type` isn't a valid `EnergyType`. The index is the mod.
+   */
+  InvalidAutoModEnergyType = 8,
+  /**
+   * A stat's lower bound is above its upper bound. The index is the stat.
+   */
+  InvertedBounds = 9,
+  /**
+   * An item has more than `MAX_ENERGY` energy. The index is the item.
+   */
+  ItemEnergyTooHigh = 10,
+  /**
+   * A mod costs more than `MAX_ENERGY` energy. The index is the mod slot (0-14).
+   */
+  ModEnergyTooHigh = 11,
+  /**
+   * An auto stat mod costs more than `MAX_ENERGY` energy. The index is the mod.
+   */
+  AutoModEnergyTooHigh = 12,
+  /**
+   * There's an empty general mod slot before a filled one. The index is the filled one.
+   */
+  GeneralModsNotPacked = 13,
+  /**
+   * A stat's lower bound is above the stat cap. The index is the stat.
+   */
+  LowerBoundAboveCap = 14,
+  /**
+   * Packed input is truncated, has trailing bytes, or doesn't start with the magic bytes.
+   * The index is the offending byte.
+   */
+  MalformedPackedInput = 15,
+  /**
+   * Packed input has an invalid enum or bool. The index is the offending byte.
+   */
+  InvalidPackedValue = 16,
+  /**
+   * Packed input has a version this module can't read. The index is the version.
+   */
+  UnsupportedPackedVersion = 17,
+  /**
+   * `auto_mods` is more than the 5 stat mod slots. The index is `auto_mods`.
+   */
+  TooManyAutoMods = 18,
+  /**
+   * An item's stat or bonus stat is beyond `MAX_STAT`. The index is the item.
+   */
+  ItemStatTooHigh = 19,
+  /**
+   * An auto stat mod's stat is above `MAX_STAT`. The index is the mod.
+   */
+  AutoModStatTooHigh = 20,
+  /**
+   * A stat's base stat or stat cap is beyond `MAX_STAT`. The index is the stat.
+   */
+  StatTooHigh = 21,
+}
+
+export interface ValidationResult {
+  code: ValidationCode;
+  /** The offending bucket, item, mod or stat, depending on the code. */
+  index: number;
+}
+
+export function readValidationResult(view: DataView, ptr: Ptr): ValidationResult {
+  return {
+    code: view.getUint32(ptr, true) as ValidationCode,
+    index: view.getUint32(ptr + 4, true),
+  };
+}
+
+/** The raw exports of lo_web.wasm. */
+export interface LoWebExports {
+  memory: WebAssembly.Memory;
+  lo_abi_version(): number;
+  lo_abi_layout_ptr(): number;
+  lo_abi_layout_len(): number;
+  lo_init(numItems: number, numAutoMods: number): number;
+  lo_setup_settings(ctx: number, anyExotic: number, allowedAutoMods: number): void;
+  lo_setup_exotic_perk(ctx: number, exoticPerkId: number): void;
+  lo_setup_ruleset(ctx: number, ruleset: number): number;
+  lo_setup_base_stats_ptr(ctx: number): number;
+  lo_setup_num_items_per_bucket_ptr(ctx: number): number;
+  lo_setup_bounds_ptr(ctx: number): number;
+  lo_setup_set_bonuses_ptr(ctx: number): number;
+  lo_setup_items_ptr(ctx: number): number;
+  lo_setup_items_reserve(ctx: number, numItems: number): number;
+  lo_setup_mods_ptr(ctx: number): number;
+  lo_setup_auto_mods_ptr(ctx: number): number;
+  lo_validate(ctx: number): number;
+  lo_validation_ptr(ctx: number): number;
+  lo_setup_validation(ctx: number, validate: number): void;
+  lo_run(ctx: number): number;
+  lo_run_start(ctx: number): number;
+  lo_run_step(ctx: number, budget: number): number;
+  lo_run_snapshot(ctx: number): number;
+  lo_run_finish(ctx: number): number;
+  lo_run_cancel(ctx: number): void;
+  lo_dump_len(ctx: number): number;
+  lo_dump_inputs(ctx: number): number;
+  lo_packed_alloc(len: number): number;
+  lo_packed_free(ptr: number, len: number): void;
+  lo_run_packed(ptr: number, len: number): number;
+  lo_packed_validation_ptr(): number;
+  lo_result_num_sets(ctx: number): number;
+  lo_result_sets_ptr(ctx: number): number;
+  lo_result_info_ptr(ctx: number): number;
+  lo_result_minmax_ptr(ctx: number): number;
+  lo_free(ctx: number, res: number): void;
+  lo_free_context(ctx: number): void;
+  lo_free_results(res: number): void;
+}
+
+export const ABI_VERSION = 1;
+
+/** The layout table these bindings were generated from, see `lo_abi_layout_ptr`. */
+export const ABI_LAYOUT: readonly number[] = [1, 10, 44, 4, 13, 0, 2, 2, 2, 4, 1, 5, 1, 6, 1, 7, 1, 8, 4, 12, 12, 24, 12, 36, 1, 37, 1, 38, 2, 40, 4, 12, 4, 4, 0, 4, 4, 4, 8, 1, 9, 1, 24, 4, 2, 0, 12, 12, 12, 80, 4, 8, 0, 12, 12, 10, 22, 2, 24, 2, 28, 20, 48, 12, 60, 8, 68, 10, 28, 4, 7, 0, 4, 4, 4, 8, 4, 12, 4, 16, 4, 20, 4, 24, 4, 24, 2, 2, 0, 12, 12, 12, 24, 2, 2, 0, 12, 12, 12, 4, 2, 2, 0, 2, 2, 2, 2, 1, 2, 0, 1, 1, 1, 64, 4, 8, 0, 12, 12, 24, 36, 1, 37, 1, 38, 1, 40, 12, 52, 8, 60, 4];
+
+/** Typed wrappers for the exports of lo_web.wasm. */
+export class LoWeb {
+  readonly exports: LoWebExports;
+
+  constructor(instance: WebAssembly.Instance) {
+    this.exports = instance.exports as unknown as LoWebExports;
+  }
+
+  /**
+   * A view of the module's memory. Get a new one after calls that may allocate,
+   * since growing the memory detaches the old buffer.
+   */
+  view(): DataView {
+    return new DataView(this.exports.memory.buffer);
+  }
+
+  /** Throws if the module was built with different struct layouts than these bindings. */
+  checkAbi(): void {
+    const version = this.abiVersion();
+    if (version !== ABI_VERSION) {
+      throw new Error(`lo_web ABI version ${version}, expected ${ABI_VERSION}`);
+    }
+    const ptr = this.abiLayoutPtr();
+    const view = this.view();
+    const layout = times(this.abiLayoutLen(), (i) => view.getUint32(ptr + 4 * i, true));
+    if (layout.length !== ABI_LAYOUT.length || layout.some((x, i) => x !== ABI_LAYOUT[i])) {
+      throw new Error('lo_web struct layouts differ from the generated bindings');
+    }
+  }
+
+  /**
+   * The version of the struct layouts this module was built with. Hosts should
+   * refuse to run if it doesn't match the version they were written against.
+   */
+  abiVersion(): number {
+    return this.exports.lo_abi_version() >>> 0;
+  }
+
+  /**
+   * A table of the size, alignment and field offsets of every FFI struct,
+   * see `dim_lo_core::abi::layout_table` for the format. `lo_abi_layout_len`
+   * is the number of `u32`s in the table.
+   */
+  abiLayoutPtr(): Ptr {
+    return this.exports.lo_abi_layout_ptr() >>> 0;
+  }
+
+  abiLayoutLen(): number {
+    return this.exports.lo_abi_layout_len() >>> 0;
+  }
+
+  /**
+   * Initialize memory for a context holding the configuration of the algorithm,
+   * `num_items` ProcessItems, and `num_auto_mods` auto stat mods.
+   */
+  init(numItems: number, numAutoMods: number): Ptr {
+    return this.exports.lo_init(numItems, numAutoMods) >>> 0;
+  }
+
+  /**
+   * Sets some LO settings. `ctx` must have been allocated via `lo_init`.
+   */
+  setupSettings(ctx: Ptr, anyExotic: number, allowedAutoMods: number): void {
+    this.exports.lo_setup_settings(ctx, anyExotic, allowedAutoMods);
+  }
+
+  /**
+   * Requires sets to include an exotic with the given perk id (as in `ProcessItem::exotic_perk_id`),
+   * 0 to allow any exotic. `ctx` must have been allocated via `lo_init`.
+   */
+  setupExoticPerk(ctx: Ptr, exoticPerkId: number): void {
+    this.exports.lo_setup_exotic_perk(ctx, exoticPerkId);
+  }
+
+  /**
+   * Selects how stats are scored: 0 for tiers, 1 for raw stat points.
+   * Returns a pointer to the buffer holding the 6 stat caps used with stat points.
+   * `ctx` must have been allocated via `lo_init`.
+   */
+  setupRuleset(ctx: Ptr, ruleset: number): Ptr {
+    return this.exports.lo_setup_ruleset(ctx, ruleset) >>> 0;
+  }
+
+  /**
+   * Gets a pointer to the buffer holding the 6 base stats. These are signed (i16)
+   * so that stat penalties from e.g. subclass fragments can be passed as they are.
+   * `ctx` must have been allocated via `lo_init`.
+   */
+  setupBaseStatsPtr(ctx: Ptr): Ptr {
+    return this.exports.lo_setup_base_stats_ptr(ctx) >>> 0;
+  }
+
+  /**
+   * Gets a pointer to the buffer holding the number of items per bucket.
+   * `ctx` must have been allocated via `lo_init`.
+   */
+  setupNumItemsPerBucketPtr(ctx: Ptr): Ptr {
+    return this.exports.lo_setup_num_items_per_bucket_ptr(ctx) >>> 0;
+  }
+
+  /**
+   * Gets a pointer to the buffer holding stat minimums and maximums,
+   * in tiers or stat points depending on the ruleset.
+   * `ctx` must have been allocated via `lo_init`.
+   */
+  setupBoundsPtr(ctx: Ptr): Ptr {
+    return this.exports.lo_setup_bounds_ptr(ctx) >>> 0;
+  }
+
+  /**
+   * Gets a pointer to the buffer holding the armor set bonus constraints.
+   * Unused entries must have set id 0.
+   * `ctx` must have been allocated via `lo_init`.
+   */
+  setupSetBonusesPtr(ctx: Ptr): Ptr {
+    return this.exports.lo_setup_set_bonuses_ptr(ctx) >>> 0;
+  }
+
+  /**
+   * Gets a pointer to the buffer allocated for `num_items` ProcessItems
+   * in `lo_init` or `lo_setup_items_reserve`. `ctx` must have been allocated via `lo_init`.
+   */
+  setupItemsPtr(ctx: Ptr): Ptr {
+    return this.exports.lo_setup_items_ptr(ctx) >>> 0;
+  }
+
+  /**
+   * Makes sure the item buffer has room for `num_items` ProcessItems, reallocating only the
+   * item buffer if it doesn't. Returns the pointer to the item buffer, which is the same as
+   * before if no reallocation was necessary. If it isn't, the old pointer is invalid and the
+   * items must be written again. `ctx` must have been allocated via `lo_init`.
+   */
+  setupItemsReserve(ctx: Ptr, numItems: number): Ptr {
+    return this.exports.lo_setup_items_reserve(ctx, numItems) >>> 0;
+  }
+
+  /**
+   * Gets a pointer to the buffer allocated for 15 ProcessMods.
+   * This buffer must be filled with 5 general mods, then 5 combat mods, then
+   * 5 activity mods. Not-filled slots must be zeroed entirely.
+   * `ctx` must have been allocated via `lo_init`.
+   */
+  setupModsPtr(ctx: Ptr): Ptr {
+    return this.exports.lo_setup_mods_ptr(ctx) >>> 0;
+  }
+
+  /**
+   * Gets a pointer to the buffer allocated for `num_auto_mods` ProcessStatMods.
+   * `ctx` must have been allocated via `lo_init`.
+   */
+  setupAutoModsPtr(ctx: Ptr): Ptr {
+    return this.exports.lo_setup_auto_mods_ptr(ctx) >>> 0;
+  }
+
+  /**
+   * Checks the current contents of the context for everything `lo_run` can't handle:
+   * item counts that don't fit the item buffer, invalid enum and bool values,
+   * and everything `dim_lo_core::error::ProcessError` covers, like items with more energy
+   * used than they have or lower bounds above upper bounds. Returns 0 if the context is fine,
+   * otherwise a `ValidationCode`. The offending index can be read via `lo_validation_ptr`.
+   * `ctx` must have been allocated via `lo_init`.
+   */
+  validate(ctx: Ptr): number {
+    return this.exports.lo_validate(ctx) >>> 0;
+  }
+
+  /**
+   * Gets a pointer to the outcome of the last validation or run (error code, then index).
+   * `ctx` must have been allocated via `lo_init`.
+   */
+  validationPtr(ctx: Ptr): Ptr {
+    return this.exports.lo_validation_ptr(ctx) >>> 0;
+  }
+
+  /**
+   * Makes `lo_run` check the enums and bools in the context too, see `lo_validate`.
+   * Off by default, since this reads every item once more.
+   * `ctx` must have been allocated via `lo_init`.
+   */
+  setupValidation(ctx: Ptr, validate: number): void {
+    this.exports.lo_setup_validation(ctx, validate);
+  }
+
+  /**
+   * Runs the algorithm with the current contents of the context.
+   * Returns null if the input is invalid, e.g. if the number of items per bucket doesn't
+   * fit the item buffer. In that case, `lo_validation_ptr` tells what's wrong.
+   * The context is left as it is, so this can be called repeatedly, and every
+   * result must be freed on its own. `ctx` must have been allocated via `lo_init`.
+   */
+  run(ctx: Ptr): Ptr {
+    return this.exports.lo_run(ctx) >>> 0;
+  }
+
+  /**
+   * Starts a run that is done in steps through `lo_run_step`, replacing any run
+   * in progress. Returns 0 on success, otherwise the same error code `lo_validate`
+   * would, and no run is started. Until the run is over, the context must not change.
+   * `ctx` must have been allocated via `lo_init`.
+   */
+  runStart(ctx: Ptr): number {
+    return this.exports.lo_run_start(ctx) >>> 0;
+  }
+
+  /**
+   * Looks at roughly `budget` more combinations of items in the run started by `lo_run_start`.
+   * Returns 1 once the run is done (or if there is no run), 0 if there's more to do.
+   * `ctx` must have been allocated via `lo_init`.
+   */
+  runStep(ctx: Ptr, budget: number): number {
+    return this.exports.lo_run_step(ctx, budget) >>> 0;
+  }
+
+  /**
+   * Gets the best sets found so far by the run started by `lo_run_start`, without
+   * ending it. The result must be freed like one of `lo_run`. Returns null if there's
+   * no run. `ctx` must have been allocated via `lo_init`.
+   */
+  runSnapshot(ctx: Ptr): Ptr {
+    return this.exports.lo_run_snapshot(ctx) >>> 0;
+  }
+
+  /**
+   * Ends the run started by `lo_run_start`, whether it's done or not, and returns its
+   * results like `lo_run` would. Returns null if there's no run.
+   * `ctx` must have been allocated via `lo_init`.
+   */
+  runFinish(ctx: Ptr): Ptr {
+    return this.exports.lo_run_finish(ctx) >>> 0;
+  }
+
+  /**
+   * Drops the run started by `lo_run_start` without returning results.
+   * Does nothing if there's no run. `ctx` must have been allocated via `lo_init`.
+   */
+  runCancel(ctx: Ptr): void {
+    this.exports.lo_run_cancel(ctx);
+  }
+
+  /**
+   * Gets the size of the buffer `lo_dump_inputs` returns for the context.
+   */
+  dumpLen(ctx: Ptr): number {
+    return this.exports.lo_dump_len(ctx) >>> 0;
+  }
+
+  /**
+   * Copies the inputs in the context into a new buffer of `lo_dump_len` bytes, exactly as
+   * the host wrote them. See `dim_lo_core::input` for the dump format, `lo-offline` replays
+   * dumps. Returns null if the items don't fit the item buffer. Free the buffer with
+   * `lo_packed_free`.
+   */
+  dumpInputs(ctx: Ptr): Ptr {
+    return this.exports.lo_dump_inputs(ctx) >>> 0;
+  }
+
+  /**
+   * Allocates a buffer of `len` bytes for packed input.
+   */
+  packedAlloc(len: number): Ptr {
+    return this.exports.lo_packed_alloc(len) >>> 0;
+  }
+
+  /**
+   * Frees a buffer allocated via `lo_packed_alloc` with the same `len`, or one returned by
+   * `lo_dump_inputs` with its `lo_dump_len`.
+   */
+  packedFree(ptr: number, len: number): void {
+    this.exports.lo_packed_free(ptr, len);
+  }
+
+  /**
+   * Runs the algorithm with packed input (see `dim_lo_core::input` for the format), without
+   * any setup context. `ptr` must point to `len` bytes, e.g. allocated via `lo_packed_alloc`.
+   * Returns null if the input is invalid, in which case `lo_packed_validation_ptr` tells
+   * what's wrong. The result must be freed like one of `lo_run`.
+   */
+  runPacked(ptr: number, len: number): Ptr {
+    return this.exports.lo_run_packed(ptr, len) >>> 0;
+  }
+
+  /**
+   * Gets a pointer to the outcome of the last `lo_run_packed` (error code, then index).
+   */
+  packedValidationPtr(): Ptr {
+    return this.exports.lo_packed_validation_ptr() >>> 0;
+  }
+
+  /**
+   * Gets how many sets were generated.
+   * `ctx` must be the result of `lo_run`.
+   */
+  resultNumSets(ctx: Ptr): number {
+    return this.exports.lo_result_num_sets(ctx) >>> 0;
+  }
+
+  /**
+   * Gets a pointer to the generated sets buffer.
+   * `ctx` must be the result of `lo_run`.
+   */
+  resultSetsPtr(ctx: Ptr): Ptr {
+    return this.exports.lo_result_sets_ptr(ctx) >>> 0;
+  }
+
+  /**
+   * Gets a pointer to the auxiliary information about generated sets.
+   * `ctx` must be the result of `lo_run`.
+   */
+  resultInfoPtr(ctx: Ptr): Ptr {
+    return this.exports.lo_result_info_ptr(ctx) >>> 0;
+  }
+
+  /**
+   * Gets a pointer to the buffer containing min/max observed stats.
+   * `ctx` must be the result of `lo_run`.
+   */
+  resultMinmaxPtr(ctx: Ptr): Ptr {
+    return this.exports.lo_result_minmax_ptr(ctx) >>> 0;
+  }
+
+  /**
+   * Free all memory allocated as part of the algorithm setup and runtime.
+   * Passing null pointers is allowed, e.g. when you decide to not call `lo_run`
+   * and instead just free the setup data.
+   */
+  free(ctx: Ptr, res: Ptr): void {
+    this.exports.lo_free(ctx, res);
+  }
+
+  /**
+   * Free a context allocated via `lo_init`. Results of `lo_run` stay valid.
+   * Passing a null pointer is allowed.
+   */
+  freeContext(ctx: Ptr): void {
+    this.exports.lo_free_context(ctx);
+  }
+
+  /**
+   * Free a single result of `lo_run`. Passing a null pointer is allowed.
+   */
+  freeResults(res: Ptr): void {
+    this.exports.lo_free_results(res);
+  }
+}
+
+/** Instantiates lo_web.wasm and checks that it matches these bindings. */
+export async function loadLoWeb(source: Response | PromiseLike<Response> | BufferSource): Promise<LoWeb> {
+  const { instance } =
+    source instanceof ArrayBuffer || ArrayBuffer.isView(source)
+      ? await WebAssembly.instantiate(source)
+      : await WebAssembly.instantiateStreaming(source);
+  const lo = new LoWeb(instance);
+  lo.checkAbi();
+  return lo;
+}
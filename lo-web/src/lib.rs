@@ -1,19 +1,79 @@
-#![feature(vec_into_raw_parts)]
+//! WASM FFI for the loadout optimizer.
+//!
+//! Lifecycle:
+//!
+//! 1. `lo_init` allocates a `ProcessSetupContext`. The host fills it through the
+//!    `lo_setup_*` functions.
+//! 2. `lo_run` runs the algorithm and returns a new `ProcessResults`. It doesn't change
+//!    the context, so the host may change some settings or items and call `lo_run` again,
+//!    as often as it likes. `lo_setup_items_reserve` grows the item buffer if a later run
+//!    needs more items than `lo_init` made room for.
+//!
+//!    Alternatively, `lo_run_start` starts a run that the host drives in steps of a given
+//!    number of combinations with `lo_run_step`, reading the best sets so far between steps
+//!    with `lo_run_snapshot`. `lo_run_finish` ends the run and returns its results,
+//!    `lo_run_cancel` just ends it. The context must not change while a run is in progress,
+//!    so every `lo_setup_*` function cancels it. Buffers must not be written through
+//!    pointers from before the run either.
+//! 3. Every `ProcessResults` is independent of the context and of other results.
+//!    Free each one with `lo_free_results` once it has been read.
+//! 4. `lo_free_context` frees the context. `lo_free` frees a context and a result at once.
+//!
+//! `lo_run` rejects some invalid input (see `dim_lo_core::error::ProcessError`), but trusts
+//! the host to write valid enums and bools. While integrating, check contexts with
+//! `lo_validate`, or have `lo_run` do it through `lo_setup_validation`.
+//!
+//! Hosts that already have their input serialized in the packed format of
+//! `dim_lo_core::input` can skip the context: copy it into a buffer from `lo_packed_alloc`
+//! and call `lo_run_packed`. Packed input is always validated.
+//!
+//! To debug marshalling, `lo_dump_inputs` copies everything the host wrote into the context
+//! into a buffer that `lo-offline` can replay natively.
 #![no_std]
 
 extern crate alloc;
 
 use alloc::{boxed::Box, vec::Vec};
+use core::{cell::UnsafeCell, mem::size_of};
 use dim_lo_core::{
-    dim_lo_process,
+    abi::{layout_table, layout_table_len, FfiKind, FfiType, ABI_VERSION, FFI_LAYOUTS},
+    input::{ProcessInput, DUMP_HEADER_LEN, DUMP_MAGIC},
+    try_dim_lo_process,
     types::{
-        ProcessArgs, ProcessArmorSet, ProcessItem, ProcessMinMaxStats, ProcessMod, ProcessStatMod,
-        ProcessStats, ProcessTierBounds, NUM_ITEM_BUCKETS, NUM_STATS,
+        ProcessArgs, ProcessArmorSet, ProcessItem, ProcessMinMaxStats, ProcessMod, ProcessSetBonus,
+        ProcessStatMod, ProcessStats, ProcessTierBounds, StatRuleset, NUM_ITEM_BUCKETS,
+        NUM_SET_BONUSES, NUM_STATS,
     },
+    ProcessRun, NUM_RESULT_SETS,
 };
-use types::{ProcessResults, ProcessSetupContext};
+use types::{ProcessResults, ProcessSetupContext, ValidationResult};
 
+#[cfg(test)]
+mod tests;
 mod types;
+mod validate;
+
+static ABI_LAYOUT: [u32; layout_table_len()] = layout_table();
+
+/// The version of the struct layouts this module was built with. Hosts should
+/// refuse to run if it doesn't match the version they were written against.
+#[no_mangle]
+fn lo_abi_version() -> u32 {
+    ABI_VERSION
+}
+
+/// A table of the size, alignment and field offsets of every FFI struct,
+/// see `dim_lo_core::abi::layout_table` for the format. `lo_abi_layout_len`
+/// is the number of `u32`s in the table.
+#[no_mangle]
+fn lo_abi_layout_ptr() -> *const u32 {
+    ABI_LAYOUT.as_ptr()
+}
+
+#[no_mangle]
+fn lo_abi_layout_len() -> usize {
+    ABI_LAYOUT.len()
+}
 
 /// Initialize memory for a context holding the configuration of the algorithm,
 /// `num_items` ProcessItems, and `num_auto_mods` auto stat mods.
@@ -29,23 +89,55 @@ fn lo_init(num_items: usize, num_auto_mods: usize) -> *mut ProcessSetupContext {
         items: items.into_raw_parts(),
         mods: mods.into_raw_parts(),
         auto_mods: auto_mods.into_raw_parts(),
+        validate: false,
+        validation: ValidationResult::default(),
+        run: core::ptr::null_mut(),
     });
     Box::into_raw(ctx)
 }
 
+/// Gets the context for a `lo_setup_*` function. A run borrows the buffers of the context,
+/// so changing any of them ends the run: this cancels any run in progress first.
+unsafe fn setup_context<'a>(ctx: *mut ProcessSetupContext) -> &'a mut ProcessSetupContext {
+    lo_run_cancel(ctx);
+    &mut *ctx
+}
+
 /// Sets some LO settings. `ctx` must have been allocated via `lo_init`.
 #[no_mangle]
 fn lo_setup_settings(ctx: *mut ProcessSetupContext, any_exotic: usize, allowed_auto_mods: usize) {
-    let ctx = unsafe { &mut *ctx };
+    let ctx = unsafe { setup_context(ctx) };
     ctx.args.auto_mods = allowed_auto_mods as u8;
     ctx.args.any_exotic = any_exotic != 0;
 }
 
-/// Gets a pointer to the buffer holding the 6 base stats.
+/// Requires sets to include an exotic with the given perk id (as in `ProcessItem::exotic_perk_id`),
+/// 0 to allow any exotic. `ctx` must have been allocated via `lo_init`.
+#[no_mangle]
+fn lo_setup_exotic_perk(ctx: *mut ProcessSetupContext, exotic_perk_id: u32) {
+    let ctx = unsafe { setup_context(ctx) };
+    ctx.args.required_exotic_perk_id = exotic_perk_id;
+}
+
+/// Selects how stats are scored: 0 for tiers, 1 for raw stat points.
+/// Returns a pointer to the buffer holding the 6 stat caps used with stat points.
 /// `ctx` must have been allocated via `lo_init`.
 #[no_mangle]
-fn lo_setup_base_stats_ptr(ctx: *mut ProcessSetupContext) -> *mut [u16; NUM_STATS] {
-    unsafe { &mut (*ctx).args.base_stats.0 }
+fn lo_setup_ruleset(ctx: *mut ProcessSetupContext, ruleset: usize) -> *mut [u16; NUM_STATS] {
+    let ctx = unsafe { setup_context(ctx) };
+    ctx.args.ruleset = match ruleset {
+        1 => StatRuleset::Points,
+        _ => StatRuleset::Tiers,
+    };
+    &mut ctx.args.stat_caps
+}
+
+/// Gets a pointer to the buffer holding the 6 base stats. These are signed (i16)
+/// so that stat penalties from e.g. subclass fragments can be passed as they are.
+/// `ctx` must have been allocated via `lo_init`.
+#[no_mangle]
+fn lo_setup_base_stats_ptr(ctx: *mut ProcessSetupContext) -> *mut [i16; NUM_STATS] {
+    unsafe { &mut setup_context(ctx).args.base_stats.0 }
 }
 
 /// Gets a pointer to the buffer holding the number of items per bucket.
@@ -54,21 +146,47 @@ fn lo_setup_base_stats_ptr(ctx: *mut ProcessSetupContext) -> *mut [u16; NUM_STAT
 fn lo_setup_num_items_per_bucket_ptr(
     ctx: *mut ProcessSetupContext,
 ) -> *mut [u16; NUM_ITEM_BUCKETS] {
-    unsafe { &mut (*ctx).num_items }
+    unsafe { &mut setup_context(ctx).num_items }
 }
 
-/// Gets a pointer to the buffer holding stat minimums and maximums.
+/// Gets a pointer to the buffer holding stat minimums and maximums,
+/// in tiers or stat points depending on the ruleset.
 /// `ctx` must have been allocated via `lo_init`.
 #[no_mangle]
 fn lo_setup_bounds_ptr(ctx: *mut ProcessSetupContext) -> *mut ProcessTierBounds {
-    unsafe { &mut (*ctx).args.bounds }
+    unsafe { &mut setup_context(ctx).args.bounds }
+}
+
+/// Gets a pointer to the buffer holding the armor set bonus constraints.
+/// Unused entries must have set id 0.
+/// `ctx` must have been allocated via `lo_init`.
+#[no_mangle]
+fn lo_setup_set_bonuses_ptr(
+    ctx: *mut ProcessSetupContext,
+) -> *mut [ProcessSetBonus; NUM_SET_BONUSES] {
+    unsafe { &mut setup_context(ctx).args.set_bonuses }
 }
 
 /// Gets a pointer to the buffer allocated for `num_items` ProcessItems
-/// in `lo_init`. `ctx` must have been allocated via `lo_init`.
+/// in `lo_init` or `lo_setup_items_reserve`. `ctx` must have been allocated via `lo_init`.
 #[no_mangle]
 fn lo_setup_items_ptr(ctx: *mut ProcessSetupContext) -> *mut ProcessItem {
-    unsafe { (*ctx).items.0 }
+    unsafe { setup_context(ctx).items.0 }
+}
+
+/// Makes sure the item buffer has room for `num_items` ProcessItems, reallocating only the
+/// item buffer if it doesn't. Returns the pointer to the item buffer, which is the same as
+/// before if no reallocation was necessary. If it isn't, the old pointer is invalid and the
+/// items must be written again. `ctx` must have been allocated via `lo_init`.
+#[no_mangle]
+fn lo_setup_items_reserve(ctx: *mut ProcessSetupContext, num_items: usize) -> *mut ProcessItem {
+    let ctx = unsafe { setup_context(ctx) };
+    if ctx.items.2 < num_items {
+        let mut items = unsafe { Vec::from_raw_parts(ctx.items.0, ctx.items.1, ctx.items.2) };
+        items.reserve_exact(num_items);
+        ctx.items = items.into_raw_parts();
+    }
+    ctx.items.0
 }
 
 /// Gets a pointer to the buffer allocated for 15 ProcessMods.
@@ -77,44 +195,70 @@ fn lo_setup_items_ptr(ctx: *mut ProcessSetupContext) -> *mut ProcessItem {
 /// `ctx` must have been allocated via `lo_init`.
 #[no_mangle]
 fn lo_setup_mods_ptr(ctx: *mut ProcessSetupContext) -> *mut ProcessMod {
-    unsafe { (*ctx).mods.0 }
+    unsafe { setup_context(ctx).mods.0 }
 }
 
 /// Gets a pointer to the buffer allocated for `num_auto_mods` ProcessStatMods.
 /// `ctx` must have been allocated via `lo_init`.
 #[no_mangle]
 fn lo_setup_auto_mods_ptr(ctx: *mut ProcessSetupContext) -> *mut ProcessStatMod {
-    unsafe { (*ctx).auto_mods.0 }
+    unsafe { setup_context(ctx).auto_mods.0 }
 }
 
+/// Checks the current contents of the context for everything `lo_run` can't handle:
+/// item counts that don't fit the item buffer, invalid enum and bool values,
+/// and everything `dim_lo_core::error::ProcessError` covers, like items with more energy
+/// used than they have or lower bounds above upper bounds. Returns 0 if the context is fine,
+/// otherwise a `ValidationCode`. The offending index can be read via `lo_validation_ptr`.
+/// `ctx` must have been allocated via `lo_init`.
 #[no_mangle]
-fn lo_run(ctx: *mut ProcessSetupContext) -> *mut ProcessResults {
-    let ctx = unsafe { &*ctx };
+fn lo_validate(ctx: *mut ProcessSetupContext) -> u32 {
+    let ctx = unsafe { &mut *ctx };
+    ctx.validation = match validate::validate(ctx) {
+        Ok(()) => ValidationResult::default(),
+        Err(e) => e,
+    };
+    ctx.validation.code
+}
 
-    let mut lists: [&[ProcessItem]; NUM_ITEM_BUCKETS] = [&[]; NUM_ITEM_BUCKETS];
-    let mut running_offset = 0;
-    for (list, len) in lists.iter_mut().zip(ctx.num_items) {
-        *list = unsafe {
-            core::slice::from_raw_parts(ctx.items.0.offset(running_offset), len as usize)
-        };
-        running_offset += len as isize;
-    }
+/// Gets a pointer to the outcome of the last validation or run (error code, then index).
+/// `ctx` must have been allocated via `lo_init`.
+#[no_mangle]
+fn lo_validation_ptr(ctx: *mut ProcessSetupContext) -> *const ValidationResult {
+    unsafe { &(*ctx).validation }
+}
 
-    let general_mods = unsafe { &*(ctx.mods.0 as *const [ProcessMod; 5]) };
-    let combat_mods = unsafe { &*(ctx.mods.0.offset(5) as *const [ProcessMod; 5]) };
-    let activity_mods = unsafe { &*(ctx.mods.0.offset(10) as *const [ProcessMod; 5]) };
-    let auto_mods = unsafe { core::slice::from_raw_parts(ctx.auto_mods.0, ctx.num_auto_mods) };
+/// Makes `lo_run` check the enums and bools in the context too, see `lo_validate`.
+/// Off by default, since this reads every item once more.
+/// `ctx` must have been allocated via `lo_init`.
+#[no_mangle]
+fn lo_setup_validation(ctx: *mut ProcessSetupContext, validate: usize) {
+    unsafe { setup_context(ctx).validate = validate != 0 };
+}
 
-    let (stats, results, min_max) = dim_lo_process(
-        lists,
-        general_mods,
-        combat_mods,
-        activity_mods,
-        auto_mods,
-        &ctx.args,
-    );
+/// Checks what needs to be checked before the buffers of the context can be read at all,
+/// storing the outcome for `lo_validation_ptr` if something's wrong.
+fn check_buffers(ctx: &mut ProcessSetupContext) -> bool {
+    let check = if ctx.validate {
+        validate::validate_raw(ctx)
+    } else {
+        validate::check_item_capacity(ctx).map(|_| ())
+    };
+    match check {
+        Ok(()) => true,
+        Err(e) => {
+            ctx.validation = e;
+            false
+        }
+    }
+}
 
-    let parts = results.into_raw_parts();
+fn into_raw_results(
+    stats: ProcessStats,
+    sets: Vec<ProcessArmorSet>,
+    min_max: ProcessMinMaxStats,
+) -> *mut ProcessResults {
+    let parts = sets.into_raw_parts();
 
     let ret = Box::new(ProcessResults {
         ptr: parts.0,
@@ -127,6 +271,270 @@ fn lo_run(ctx: *mut ProcessSetupContext) -> *mut ProcessResults {
     Box::into_raw(ret)
 }
 
+/// Runs the algorithm with the current contents of the context.
+/// Returns null if the input is invalid, e.g. if the number of items per bucket doesn't
+/// fit the item buffer. In that case, `lo_validation_ptr` tells what's wrong.
+/// The context is left as it is, so this can be called repeatedly, and every
+/// result must be freed on its own. `ctx` must have been allocated via `lo_init`.
+#[no_mangle]
+fn lo_run(ctx: *mut ProcessSetupContext) -> *mut ProcessResults {
+    let ctx = unsafe { &mut *ctx };
+    if !check_buffers(ctx) {
+        return core::ptr::null_mut();
+    }
+
+    let lists = unsafe { ctx.item_lists() };
+    let (general_mods, combat_mods, activity_mods) = unsafe { ctx.mod_lists() };
+    let auto_mods = unsafe { ctx.auto_mod_list() };
+
+    let result = try_dim_lo_process(
+        lists,
+        general_mods,
+        combat_mods,
+        activity_mods,
+        auto_mods,
+        &ctx.args,
+    );
+    match result {
+        Ok((stats, sets, min_max)) => {
+            ctx.validation = ValidationResult::default();
+            into_raw_results(stats, sets, min_max)
+        }
+        Err(e) => {
+            ctx.validation = validate::map_process_error(&ctx.num_items, e);
+            core::ptr::null_mut()
+        }
+    }
+}
+
+/// Starts a run that is done in steps through `lo_run_step`, replacing any run
+/// in progress. Returns 0 on success, otherwise the same error code `lo_validate`
+/// would, and no run is started. Until the run is over, the context must not change.
+/// `ctx` must have been allocated via `lo_init`.
+#[no_mangle]
+fn lo_run_start(ctx: *mut ProcessSetupContext) -> u32 {
+    lo_run_cancel(ctx);
+    let ctx = unsafe { &mut *ctx };
+    if !check_buffers(ctx) {
+        return ctx.validation.code;
+    }
+
+    // The run borrows the buffers the context points to, which stay where they are until
+    // the run is over because every `lo_setup_*` function cancels it. It copies the args,
+    // so it doesn't borrow the context itself.
+    let (general_mods, combat_mods, activity_mods) = unsafe { ctx.mod_lists() };
+    let run = ProcessRun::new(
+        unsafe { ctx.item_lists() },
+        general_mods,
+        combat_mods,
+        activity_mods,
+        unsafe { ctx.auto_mod_list() },
+        &ctx.args,
+    );
+
+    match run {
+        Ok(run) => {
+            ctx.validation = ValidationResult::default();
+            ctx.run = Box::into_raw(Box::new(run));
+        }
+        Err(e) => ctx.validation = validate::map_process_error(&ctx.num_items, e),
+    }
+    ctx.validation.code
+}
+
+/// Looks at roughly `budget` more combinations of items in the run started by `lo_run_start`.
+/// Returns 1 once the run is done (or if there is no run), 0 if there's more to do.
+/// `ctx` must have been allocated via `lo_init`.
+#[no_mangle]
+fn lo_run_step(ctx: *mut ProcessSetupContext, budget: u32) -> u32 {
+    let run = unsafe { (*ctx).run };
+    if run.is_null() {
+        return 1;
+    }
+    unsafe { (*run).step(budget) as u32 }
+}
+
+/// Gets the best sets found so far by the run started by `lo_run_start`, without
+/// ending it. The result must be freed like one of `lo_run`. Returns null if there's
+/// no run. `ctx` must have been allocated via `lo_init`.
+#[no_mangle]
+fn lo_run_snapshot(ctx: *mut ProcessSetupContext) -> *mut ProcessResults {
+    let run = unsafe { (*ctx).run };
+    if run.is_null() {
+        return core::ptr::null_mut();
+    }
+    let run = unsafe { &*run };
+    let sets = run.best_sets().take(NUM_RESULT_SETS).copied().collect();
+    into_raw_results(*run.info(), sets, run.min_max())
+}
+
+/// Ends the run started by `lo_run_start`, whether it's done or not, and returns its
+/// results like `lo_run` would. Returns null if there's no run.
+/// `ctx` must have been allocated via `lo_init`.
+#[no_mangle]
+fn lo_run_finish(ctx: *mut ProcessSetupContext) -> *mut ProcessResults {
+    let run = unsafe { (*ctx).run };
+    if run.is_null() {
+        return core::ptr::null_mut();
+    }
+    unsafe { (*ctx).run = core::ptr::null_mut() };
+    let run = unsafe { Box::from_raw(run) };
+    let (stats, sets, min_max) = run.into_results();
+    into_raw_results(stats, sets, min_max)
+}
+
+/// Drops the run started by `lo_run_start` without returning results.
+/// Does nothing if there's no run. `ctx` must have been allocated via `lo_init`.
+#[no_mangle]
+fn lo_run_cancel(ctx: *mut ProcessSetupContext) {
+    let run = unsafe { (*ctx).run };
+    if !run.is_null() {
+        unsafe { (*ctx).run = core::ptr::null_mut() };
+        let _run = unsafe { Box::from_raw(run) };
+    }
+}
+
+/// Copies the fields of the `T` at `src` byte for byte to the start of `dst`, leaving
+/// padding as it is in `dst`.
+unsafe fn copy_fields<T: FfiType>(src: *const T, dst: &mut [u8]) {
+    let FfiKind::Struct(name) = T::KIND else {
+        unreachable!()
+    };
+    copy_layout(name, src as *const u8, dst);
+}
+
+unsafe fn copy_layout(name: &str, src: *const u8, dst: &mut [u8]) {
+    let layout = FFI_LAYOUTS.iter().find(|l| l.name == name).unwrap();
+    for field in layout.fields {
+        match field.kind {
+            FfiKind::Struct(inner) => {
+                let stride = field.size / field.len;
+                for i in 0..field.len {
+                    let offset = field.offset + i * stride;
+                    copy_layout(inner, src.add(offset), &mut dst[offset..]);
+                }
+            }
+            _ => core::ptr::copy_nonoverlapping(
+                src.add(field.offset),
+                dst[field.offset..].as_mut_ptr(),
+                field.size,
+            ),
+        }
+    }
+}
+
+/// Gets the size of the buffer `lo_dump_inputs` returns for the context.
+#[no_mangle]
+fn lo_dump_len(ctx: *const ProcessSetupContext) -> usize {
+    let ctx = unsafe { &*ctx };
+    let num_items = ctx.num_items.iter().map(|&n| n as usize).sum::<usize>();
+    DUMP_HEADER_LEN
+        + size_of::<ProcessArgs>()
+        + num_items * size_of::<ProcessItem>()
+        + 3 * NUM_ITEM_BUCKETS * size_of::<ProcessMod>()
+        + ctx.num_auto_mods * size_of::<ProcessStatMod>()
+}
+
+/// Copies the inputs in the context into a new buffer of `lo_dump_len` bytes, exactly as
+/// the host wrote them. See `dim_lo_core::input` for the dump format, `lo-offline` replays
+/// dumps. Returns null if the items don't fit the item buffer. Free the buffer with
+/// `lo_packed_free`.
+#[no_mangle]
+fn lo_dump_inputs(ctx: *mut ProcessSetupContext) -> *mut u8 {
+    let len = lo_dump_len(ctx);
+    let ctx = unsafe { &mut *ctx };
+    if let Err(e) = validate::check_item_capacity(ctx) {
+        ctx.validation = e;
+        return core::ptr::null_mut();
+    }
+
+    // Exactly `len` bytes, so that `lo_packed_free` can free it
+    let mut out = Vec::with_capacity(len);
+    out.extend(DUMP_MAGIC);
+    out.extend(ABI_VERSION.to_le_bytes());
+    for n in ctx.num_items {
+        out.extend(n.to_le_bytes());
+    }
+    out.extend((ctx.num_auto_mods as u32).to_le_bytes());
+    out.resize(len, 0);
+
+    unsafe {
+        let mut offset = DUMP_HEADER_LEN;
+        copy_fields(&ctx.args, &mut out[offset..]);
+        offset += size_of::<ProcessArgs>();
+        for list in ctx.item_lists() {
+            for item in list {
+                copy_fields(item, &mut out[offset..]);
+                offset += size_of::<ProcessItem>();
+            }
+        }
+        for i in 0..3 * NUM_ITEM_BUCKETS {
+            copy_fields(ctx.mods.0.add(i), &mut out[offset..]);
+            offset += size_of::<ProcessMod>();
+        }
+        for m in ctx.auto_mod_list() {
+            copy_fields(m, &mut out[offset..]);
+            offset += size_of::<ProcessStatMod>();
+        }
+    }
+
+    out.into_raw_parts().0
+}
+
+/// The outcome of the last `lo_run_packed`. There's no context to keep it in,
+/// and WASM is single-threaded, so a static does.
+struct PackedValidation(UnsafeCell<ValidationResult>);
+unsafe impl Sync for PackedValidation {}
+
+static PACKED_VALIDATION: PackedValidation =
+    PackedValidation(UnsafeCell::new(ValidationResult { code: 0, index: 0 }));
+
+/// Allocates a buffer of `len` bytes for packed input.
+#[no_mangle]
+fn lo_packed_alloc(len: usize) -> *mut u8 {
+    Vec::<u8>::with_capacity(len).into_raw_parts().0
+}
+
+/// Frees a buffer allocated via `lo_packed_alloc` with the same `len`, or one returned by
+/// `lo_dump_inputs` with its `lo_dump_len`.
+#[no_mangle]
+fn lo_packed_free(ptr: *mut u8, len: usize) {
+    let _buf = unsafe { Vec::from_raw_parts(ptr, 0, len) };
+}
+
+/// Runs the algorithm with packed input (see `dim_lo_core::input` for the format), without
+/// any setup context. `ptr` must point to `len` bytes, e.g. allocated via `lo_packed_alloc`.
+/// Returns null if the input is invalid, in which case `lo_packed_validation_ptr` tells
+/// what's wrong. The result must be freed like one of `lo_run`.
+#[no_mangle]
+fn lo_run_packed(ptr: *const u8, len: usize) -> *mut ProcessResults {
+    let bytes = unsafe { core::slice::from_raw_parts(ptr, len) };
+    let validation = unsafe { &mut *PACKED_VALIDATION.0.get() };
+    let input = match ProcessInput::from_packed(bytes) {
+        Ok(input) => input,
+        Err(e) => {
+            *validation = validate::map_packed_error(e);
+            return core::ptr::null_mut();
+        }
+    };
+    match input.process() {
+        Ok((stats, sets, min_max)) => {
+            *validation = ValidationResult::default();
+            into_raw_results(stats, sets, min_max)
+        }
+        Err(e) => {
+            *validation = validate::map_process_error(&input.num_items(), e);
+            core::ptr::null_mut()
+        }
+    }
+}
+
+/// Gets a pointer to the outcome of the last `lo_run_packed` (error code, then index).
+#[no_mangle]
+fn lo_packed_validation_ptr() -> *const ValidationResult {
+    PACKED_VALIDATION.0.get()
+}
+
 /// Gets how many sets were generated.
 /// `ctx` must be the result of `lo_run`.
 #[no_mangle]
@@ -160,15 +568,28 @@ fn lo_result_minmax_ptr(ctx: *mut ProcessResults) -> *mut ProcessMinMaxStats {
 /// and instead just free the setup data.
 #[no_mangle]
 fn lo_free(ctx: *mut ProcessSetupContext, res: *mut ProcessResults) {
+    lo_free_context(ctx);
+    lo_free_results(res);
+}
+
+/// Free a context allocated via `lo_init`. Results of `lo_run` stay valid.
+/// Passing a null pointer is allowed.
+#[no_mangle]
+fn lo_free_context(ctx: *mut ProcessSetupContext) {
     // Restore the types used to allocate, this will deallocate upon dropping.
     if !ctx.is_null() {
+        lo_run_cancel(ctx);
         let ctx = unsafe { Box::from_raw(ctx) };
         let _items = unsafe { Vec::from_raw_parts(ctx.items.0, ctx.items.1, ctx.items.2) };
         let _mods = unsafe { Vec::from_raw_parts(ctx.mods.0, ctx.mods.1, ctx.mods.2) };
         let _auto_mods =
             unsafe { Vec::from_raw_parts(ctx.auto_mods.0, ctx.auto_mods.1, ctx.auto_mods.2) };
     }
+}
 
+/// Free a single result of `lo_run`. Passing a null pointer is allowed.
+#[no_mangle]
+fn lo_free_results(res: *mut ProcessResults) {
     if !res.is_null() {
         let res = unsafe { Box::from_raw(res) };
         let _sets = unsafe { Vec::from_raw_parts(res.ptr, res.len, res.cap) };
@@ -0,0 +1,339 @@
+use core::{mem::offset_of, num::NonZeroU32};
+
+use dim_lo_core::{
+    error::MAX_STAT,
+    types::{EnergyType, ProcessItem, ProcessMod, ProcessStatMod, Stats, NUM_ITEM_BUCKETS},
+};
+
+use crate::{validate::ValidationCode, *};
+
+const NO_MOD: ProcessMod = ProcessMod {
+    hash: None,
+    mod_tag: None,
+    energy_type: EnergyType::Any,
+    energy_val: 0,
+};
+
+/// A context with one item per bucket, no mods and `num_auto_mods` empty auto stat mods,
+/// like a host would set it up.
+fn sample_context(num_auto_mods: usize) -> *mut ProcessSetupContext {
+    let ctx = lo_init(NUM_ITEM_BUCKETS, num_auto_mods);
+    unsafe {
+        *lo_setup_num_items_per_bucket_ptr(ctx) = [1; NUM_ITEM_BUCKETS];
+        let items = lo_setup_items_ptr(ctx);
+        for id in 0..NUM_ITEM_BUCKETS {
+            items.add(id).write(ProcessItem {
+                id: id as u16,
+                energy_cap: 10,
+                ..Default::default()
+            });
+        }
+        let mods = lo_setup_mods_ptr(ctx);
+        for idx in 0..3 * NUM_ITEM_BUCKETS {
+            mods.add(idx).write(NO_MOD);
+        }
+        let auto_mods = lo_setup_auto_mods_ptr(ctx);
+        for idx in 0..num_auto_mods {
+            auto_mods.add(idx).write(ProcessStatMod {
+                inner_mod: NO_MOD,
+                stats: Stats::default(),
+            });
+        }
+        *lo_setup_bounds_ptr(ctx) = ProcessTierBounds {
+            lower_bounds: [0; NUM_STATS],
+            upper_bounds: [10; NUM_STATS],
+        };
+    }
+    ctx
+}
+
+/// Overwrites the byte at `offset` in the `idx`th `T` at `base`, to get values
+/// into the context that the real types can't hold.
+unsafe fn write_byte<T>(base: *mut T, idx: usize, offset: usize, value: u8) {
+    *(base as *mut u8).add(idx * size_of::<T>() + offset) = value;
+}
+
+/// Breaks a fresh sample context with `setup`, then checks that both `lo_validate` and
+/// `lo_run` with validation report `code` and `index`.
+fn check_rejected(setup: impl FnOnce(*mut ProcessSetupContext), code: ValidationCode, index: u32) {
+    let ctx = sample_context(2);
+    // Some items in a later bucket, so that item indices differ from bucket indices
+    unsafe { *lo_setup_num_items_per_bucket_ptr(ctx) = [0, 2, 1, 1, 1] };
+    setup(ctx);
+
+    assert_eq!(lo_validate(ctx), code as u32);
+    let validation = unsafe { &*lo_validation_ptr(ctx) };
+    assert_eq!((validation.code, validation.index), (code as u32, index));
+
+    lo_setup_validation(ctx, 1);
+    assert!(lo_run(ctx).is_null());
+    let validation = unsafe { &*lo_validation_ptr(ctx) };
+    assert_eq!((validation.code, validation.index), (code as u32, index));
+    lo_free_context(ctx);
+}
+
+#[test]
+fn check_setup_cancels_run() {
+    let ctx = sample_context(0);
+
+    assert_eq!(lo_run_start(ctx), 0);
+    assert!(!unsafe { (*ctx).run }.is_null());
+    // Growing the item buffer moves it, the run must not outlive that
+    lo_setup_items_reserve(ctx, 1000);
+    assert!(unsafe { (*ctx).run }.is_null());
+    assert_eq!(lo_run_step(ctx, 1), 1);
+    assert!(lo_run_finish(ctx).is_null());
+
+    // The same for the setters that don't reallocate
+    assert_eq!(lo_run_start(ctx), 0);
+    lo_setup_settings(ctx, 0, 0);
+    assert!(unsafe { (*ctx).run }.is_null());
+    assert_eq!(lo_run_start(ctx), 0);
+    lo_setup_bounds_ptr(ctx);
+    assert!(unsafe { (*ctx).run }.is_null());
+
+    // Without changes in between, the run goes on
+    assert_eq!(lo_run_start(ctx), 0);
+    assert_eq!(lo_run_step(ctx, u32::MAX), 1);
+    let res = lo_run_finish(ctx);
+    assert_eq!(lo_result_num_sets(res), 1);
+    lo_free(ctx, res);
+}
+
+#[test]
+fn check_validate_sample_context() {
+    let ctx = sample_context(2);
+    assert_eq!(lo_validate(ctx), 0);
+    lo_setup_validation(ctx, 1);
+    let res = lo_run(ctx);
+    assert_eq!(lo_result_num_sets(res), 1);
+    assert_eq!(unsafe { (*lo_validation_ptr(ctx)).code }, 0);
+    lo_free(ctx, res);
+}
+
+#[test]
+fn check_validate_too_many_items() {
+    let ctx = sample_context(0);
+    // Bucket 3 is the first that doesn't fit the 5 items
+    unsafe { *lo_setup_num_items_per_bucket_ptr(ctx) = [1, 1, 1, 3, 0] };
+    assert_eq!(lo_validate(ctx), ValidationCode::TooManyItems as u32);
+    assert_eq!(unsafe { (*lo_validation_ptr(ctx)).index }, 3);
+
+    // Checked even without validation, and before a stepped run borrows the buffers
+    assert!(lo_run(ctx).is_null());
+    assert_eq!(lo_run_start(ctx), ValidationCode::TooManyItems as u32);
+    assert!(unsafe { (*ctx).run }.is_null());
+
+    // The same with a dump, which reads the items just like a run
+    unsafe { (*ctx).validation = ValidationResult::default() };
+    assert!(lo_dump_inputs(ctx).is_null());
+    assert_eq!(
+        unsafe { (*lo_validation_ptr(ctx)).code },
+        ValidationCode::TooManyItems as u32
+    );
+    lo_free_context(ctx);
+}
+
+#[test]
+fn check_validate_raw_values() {
+    let item_byte = |offset: usize, value: u8| {
+        move |ctx| unsafe { write_byte(lo_setup_items_ptr(ctx), 2, offset, value) }
+    };
+    check_rejected(
+        item_byte(offset_of!(ProcessItem, energy_type), 5),
+        ValidationCode::InvalidItemEnergyType,
+        2,
+    );
+    check_rejected(
+        item_byte(offset_of!(ProcessItem, exotic), 2),
+        ValidationCode::InvalidItemExotic,
+        2,
+    );
+    check_rejected(
+        item_byte(offset_of!(ProcessItem, bonus_mode), 2),
+        ValidationCode::InvalidItemBonusMode,
+        2,
+    );
+    check_rejected(
+        |ctx| unsafe {
+            let offset = offset_of!(ProcessMod, energy_type);
+            write_byte(lo_setup_mods_ptr(ctx), 7, offset, 0xff);
+        },
+        ValidationCode::InvalidModEnergyType,
+        7,
+    );
+    check_rejected(
+        |ctx| unsafe {
+            let offset =
+                offset_of!(ProcessStatMod, inner_mod) + offset_of!(ProcessMod, energy_type);
+            write_byte(lo_setup_auto_mods_ptr(ctx), 1, offset, 5);
+        },
+        ValidationCode::InvalidAutoModEnergyType,
+        1,
+    );
+}
+
+#[test]
+fn check_validate_items() {
+    check_rejected(
+        |ctx| unsafe { (*lo_setup_items_ptr(ctx).add(2)).energy_val = 11 },
+        ValidationCode::ItemEnergyOverCapacity,
+        2,
+    );
+    check_rejected(
+        |ctx| unsafe {
+            let item = &mut *lo_setup_items_ptr(ctx).add(4);
+            item.energy_cap = 11;
+        },
+        ValidationCode::ItemEnergyTooHigh,
+        4,
+    );
+    check_rejected(
+        |ctx| unsafe { (*lo_setup_items_ptr(ctx).add(1)).stats.0[3] = MAX_STAT + 1 },
+        ValidationCode::ItemStatTooHigh,
+        1,
+    );
+}
+
+#[test]
+fn check_validate_mods() {
+    let hash = NonZeroU32::new(1);
+    check_rejected(
+        // Combat mod 1
+        |ctx| unsafe {
+            lo_setup_mods_ptr(ctx).add(6).write(ProcessMod {
+                hash,
+                energy_val: 11,
+                ..NO_MOD
+            })
+        },
+        ValidationCode::ModEnergyTooHigh,
+        6,
+    );
+    check_rejected(
+        // Activity mod 0
+        |ctx| unsafe { (*lo_setup_mods_ptr(ctx).add(10)).energy_val = 1 },
+        ValidationCode::ModNotZeroed,
+        10,
+    );
+    check_rejected(
+        |ctx| unsafe {
+            lo_setup_mods_ptr(ctx)
+                .add(1)
+                .write(ProcessMod { hash, ..NO_MOD })
+        },
+        ValidationCode::GeneralModsNotPacked,
+        1,
+    );
+    check_rejected(
+        |ctx| unsafe { (*lo_setup_auto_mods_ptr(ctx).add(1)).inner_mod.energy_val = 11 },
+        ValidationCode::AutoModEnergyTooHigh,
+        1,
+    );
+    check_rejected(
+        |ctx| unsafe { (*lo_setup_auto_mods_ptr(ctx).add(1)).stats.0[0] = MAX_STAT + 1 },
+        ValidationCode::AutoModStatTooHigh,
+        1,
+    );
+    check_rejected(
+        |ctx| lo_setup_settings(ctx, 0, 6),
+        ValidationCode::TooManyAutoMods,
+        6,
+    );
+}
+
+#[test]
+fn check_validate_stats() {
+    check_rejected(
+        |ctx| unsafe { (*lo_setup_bounds_ptr(ctx)).lower_bounds[3] = 11 },
+        ValidationCode::InvertedBounds,
+        3,
+    );
+    check_rejected(
+        |ctx| unsafe {
+            *lo_setup_ruleset(ctx, 1) = [100; NUM_STATS];
+            let bounds = &mut *lo_setup_bounds_ptr(ctx);
+            bounds.lower_bounds[4] = 101;
+            bounds.upper_bounds[4] = 200;
+        },
+        ValidationCode::LowerBoundAboveCap,
+        4,
+    );
+    check_rejected(
+        |ctx| unsafe { (*lo_setup_base_stats_ptr(ctx))[5] = -(MAX_STAT as i16) - 1 },
+        ValidationCode::StatTooHigh,
+        5,
+    );
+}
+
+#[test]
+fn check_validate_packed() {
+    let check = |bytes: &[u8], code: ValidationCode, index: u32| {
+        assert!(lo_run_packed(bytes.as_ptr(), bytes.len()).is_null());
+        let validation = unsafe { &*lo_packed_validation_ptr() };
+        assert_eq!((validation.code, validation.index), (code as u32, index));
+    };
+    check(b"DLOX\x01\x00", ValidationCode::MalformedPackedInput, 0);
+    check(b"DLOP\x07\x00", ValidationCode::UnsupportedPackedVersion, 7);
+    // Ends before `any_exotic`, then `any_exotic` is 2
+    check(b"DLOP\x01\x00", ValidationCode::MalformedPackedInput, 6);
+    check(b"DLOP\x01\x00\x02", ValidationCode::InvalidPackedValue, 6);
+}
+
+#[test]
+fn check_context_reuse() {
+    let ctx = sample_context(0);
+    let res = lo_run(ctx);
+    assert_eq!(lo_result_num_sets(res), 1);
+    lo_free_results(res);
+
+    // Two items per bucket, the odd ids have better stats
+    let items = lo_setup_items_reserve(ctx, 2 * NUM_ITEM_BUCKETS);
+    unsafe {
+        *lo_setup_num_items_per_bucket_ptr(ctx) = [2; NUM_ITEM_BUCKETS];
+        for id in 0..2 * NUM_ITEM_BUCKETS {
+            items.add(id).write(ProcessItem {
+                id: id as u16,
+                energy_cap: 10,
+                stats: Stats([20 * (id as u16 % 2), 0, 0, 0, 0, 0]),
+                ..Default::default()
+            });
+        }
+    }
+    let more_items = lo_run(ctx);
+    assert_eq!(lo_result_num_sets(more_items), 32);
+    let best = unsafe { &*lo_result_sets_ptr(more_items) };
+    assert_eq!(best.items, [1, 3, 5, 7, 9]);
+    assert_eq!(best.stats.0[0], 100);
+
+    // Five combat mods that need all of an item's energy, which item 0 has used half of
+    unsafe {
+        (*items).energy_val = 5;
+        let mods = lo_setup_mods_ptr(ctx);
+        for idx in NUM_ITEM_BUCKETS..2 * NUM_ITEM_BUCKETS {
+            mods.add(idx).write(ProcessMod {
+                hash: NonZeroU32::new(idx as u32),
+                energy_val: 10,
+                ..NO_MOD
+            });
+        }
+    }
+    let more_mods = lo_run(ctx);
+    assert_eq!(lo_result_num_sets(more_mods), 16);
+    assert_eq!(
+        unsafe { (*lo_result_info_ptr(more_mods)).skipped_mods_unfit },
+        16
+    );
+    let sets = unsafe { core::slice::from_raw_parts(lo_result_sets_ptr(more_mods), 16) };
+    assert!(sets.iter().all(|set| set.items[0] == 1));
+
+    // Results don't depend on the context or each other
+    lo_free_context(ctx);
+    assert_eq!(lo_result_num_sets(more_items), 32);
+    lo_free_results(more_items);
+    assert_eq!(
+        unsafe { (*lo_result_sets_ptr(more_mods)).items },
+        [1, 3, 5, 7, 9]
+    );
+    lo_free_results(more_mods);
+}
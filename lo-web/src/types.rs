@@ -1,8 +1,13 @@
-use dim_lo_core::types::{
-    ProcessArgs, ProcessArmorSet, ProcessItem, ProcessMinMaxStats, ProcessMod, ProcessStatMod,
-    ProcessStats, NUM_ITEM_BUCKETS,
+use dim_lo_core::{
+    types::{
+        ProcessArgs, ProcessArmorSet, ProcessItem, ProcessMinMaxStats, ProcessMod, ProcessStatMod,
+        ProcessStats, NUM_ITEM_BUCKETS,
+    },
+    ProcessRun,
 };
 
+type Mods<'a> = &'a [ProcessMod; NUM_ITEM_BUCKETS];
+
 #[repr(C)]
 pub struct ProcessResults {
     pub ptr: *mut ProcessArmorSet,
@@ -12,6 +17,15 @@ pub struct ProcessResults {
     pub min_max: ProcessMinMaxStats,
 }
 
+/// The outcome of the last validation, see `ValidationCode` for the codes.
+#[repr(C)]
+#[derive(Default)]
+pub struct ValidationResult {
+    pub code: u32,
+    /// The offending bucket, item, mod or stat, depending on the code.
+    pub index: u32,
+}
+
 #[repr(C)]
 pub struct ProcessSetupContext {
     pub args: ProcessArgs,
@@ -20,4 +34,41 @@ pub struct ProcessSetupContext {
     pub items: (*mut ProcessItem, usize, usize),
     pub mods: (*mut ProcessMod, usize, usize),
     pub auto_mods: (*mut ProcessStatMod, usize, usize),
+    /// Whether `lo_run` validates the context before running.
+    pub validate: bool,
+    pub validation: ValidationResult,
+    /// The run started by `lo_run_start`, null if there is none. It borrows the buffers
+    /// this context points to, but nothing in the context itself since it copies the args,
+    /// so the context may still be borrowed while it exists. The buffers must stay put and
+    /// unchanged though: the `lo_setup_*` functions cancel it before touching the context.
+    pub run: *mut ProcessRun<'static>,
+}
+
+impl ProcessSetupContext {
+    /// The items per bucket. The number of items per bucket must fit the item buffer.
+    /// The lists borrow the item buffer, not the context, so they may outlive the borrow
+    /// of `self` as long as the buffer isn't changed or freed.
+    pub unsafe fn item_lists<'b>(&self) -> [&'b [ProcessItem]; NUM_ITEM_BUCKETS] {
+        let mut lists: [&[ProcessItem]; NUM_ITEM_BUCKETS] = [&[]; NUM_ITEM_BUCKETS];
+        let mut running_offset = 0;
+        for (list, len) in lists.iter_mut().zip(self.num_items) {
+            *list = core::slice::from_raw_parts(self.items.0.offset(running_offset), len as usize);
+            running_offset += len as isize;
+        }
+        lists
+    }
+
+    /// The general, combat and activity mods, borrowing the mod buffer like `item_lists`.
+    pub unsafe fn mod_lists<'b>(&self) -> (Mods<'b>, Mods<'b>, Mods<'b>) {
+        (
+            &*(self.mods.0 as *const [ProcessMod; 5]),
+            &*(self.mods.0.offset(5) as *const [ProcessMod; 5]),
+            &*(self.mods.0.offset(10) as *const [ProcessMod; 5]),
+        )
+    }
+
+    /// The auto stat mods, borrowing their buffer like `item_lists`.
+    pub unsafe fn auto_mod_list<'b>(&self) -> &'b [ProcessStatMod] {
+        core::slice::from_raw_parts(self.auto_mods.0, self.num_auto_mods)
+    }
 }
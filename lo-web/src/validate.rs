@@ -0,0 +1,214 @@
+use core::mem::{offset_of, size_of};
+
+use dim_lo_core::{
+    error::{validate_inputs, ModList, ProcessError},
+    input::PackedError,
+    types::{ProcessItem, ProcessMod, ProcessStatMod, NUM_ITEM_BUCKETS},
+};
+
+use crate::types::{ProcessSetupContext, ValidationResult};
+
+/// Why `lo_validate` or `lo_run` rejected a context, 0 means nothing was wrong.
+/// The numbers are part of the FFI and must not change.
+#[repr(u32)]
+#[derive(Clone, Copy)]
+pub enum ValidationCode {
+    /// The number of items per bucket adds up to more than the item buffer holds.
+    /// The index is the first bucket that doesn't fit.
+    TooManyItems = 1,
+    /// An item's `energy_type` isn't a valid `EnergyType`. The index is the item.
+    InvalidItemEnergyType = 2,
+    /// An item's `exotic` is neither 0 nor 1. The index is the item.
+    InvalidItemExotic = 3,
+    /// An item's `bonus_mode` isn't a valid `BonusStatMode`. The index is the item.
+    InvalidItemBonusMode = 4,
+    /// An item's `energy_val` is greater than its `energy_cap`. The index is the item.
+    ItemEnergyOverCapacity = 5,
+    /// A mod's `energy_type` isn't a valid `EnergyType`. The index is the mod slot (0-14).
+    InvalidModEnergyType = 6,
+    /// An empty mod slot (hash 0) isn't zeroed entirely. The index is the mod slot (0-14).
+    ModNotZeroed = 7,
+    /// An auto stat mod's `energy_type` isn't a valid `EnergyType`. The index is the mod.
+    InvalidAutoModEnergyType = 8,
+    /// A stat's lower bound is above its upper bound. The index is the stat.
+    InvertedBounds = 9,
+    /// An item has more than `MAX_ENERGY` energy. The index is the item.
+    ItemEnergyTooHigh = 10,
+    /// A mod costs more than `MAX_ENERGY` energy. The index is the mod slot (0-14).
+    ModEnergyTooHigh = 11,
+    /// An auto stat mod costs more than `MAX_ENERGY` energy. The index is the mod.
+    AutoModEnergyTooHigh = 12,
+    /// There's an empty general mod slot before a filled one. The index is the filled one.
+    GeneralModsNotPacked = 13,
+    /// A stat's lower bound is above the stat cap. The index is the stat.
+    LowerBoundAboveCap = 14,
+    /// Packed input is truncated, has trailing bytes, or doesn't start with the magic bytes.
+    /// The index is the offending byte.
+    MalformedPackedInput = 15,
+    /// Packed input has an invalid enum or bool. The index is the offending byte.
+    InvalidPackedValue = 16,
+    /// Packed input has a version this module can't read. The index is the version.
+    UnsupportedPackedVersion = 17,
+    /// `auto_mods` is more than the 5 stat mod slots. The index is `auto_mods`.
+    TooManyAutoMods = 18,
+    /// An item's stat or bonus stat is beyond `MAX_STAT`. The index is the item.
+    ItemStatTooHigh = 19,
+    /// An auto stat mod's stat is above `MAX_STAT`. The index is the mod.
+    AutoModStatTooHigh = 20,
+    /// A stat's base stat or stat cap is beyond `MAX_STAT`. The index is the stat.
+    StatTooHigh = 21,
+}
+
+fn result(code: ValidationCode, index: usize) -> ValidationResult {
+    ValidationResult {
+        code: code as u32,
+        index: index as u32,
+    }
+}
+
+/// The highest valid `EnergyType` discriminant.
+const MAX_ENERGY_TYPE: u8 = 4;
+/// The highest valid `BonusStatMode` discriminant.
+const MAX_BONUS_MODE: u8 = 1;
+
+/// Reads the byte at `offset` in the `idx`th element of `base`.
+/// Enums and bools must be read like this, because reading an invalid
+/// value through the real type is already undefined behavior.
+unsafe fn raw_byte<T>(base: *const T, idx: usize, offset: usize) -> u8 {
+    *(base as *const u8).add(idx * size_of::<T>() + offset)
+}
+
+/// Checks everything `lo_run` relies on, see `lo_validate`.
+pub fn validate(ctx: &ProcessSetupContext) -> Result<(), ValidationResult> {
+    validate_raw(ctx)?;
+    let (general_mods, combat_mods, activity_mods) = unsafe { ctx.mod_lists() };
+    validate_inputs(
+        &unsafe { ctx.item_lists() },
+        general_mods,
+        combat_mods,
+        activity_mods,
+        unsafe { ctx.auto_mod_list() },
+        &ctx.args,
+    )
+    .map_err(|e| map_process_error(&ctx.num_items, e))
+}
+
+/// Checks that the number of items per bucket fits the item buffer.
+/// Returns the total number of items.
+pub fn check_item_capacity(ctx: &ProcessSetupContext) -> Result<usize, ValidationResult> {
+    let mut total_items = 0;
+    for (bucket, &n) in ctx.num_items.iter().enumerate() {
+        total_items += n as usize;
+        if total_items > ctx.items.2 {
+            return Err(result(ValidationCode::TooManyItems, bucket));
+        }
+    }
+    Ok(total_items)
+}
+
+/// Checks what the core library can't check because it needs valid values of its types:
+/// whether the items fit the item buffer, and whether enums and bools are valid.
+/// Only reads the parts of the buffers that `lo_run` would read.
+pub fn validate_raw(ctx: &ProcessSetupContext) -> Result<(), ValidationResult> {
+    let total_items = check_item_capacity(ctx)?;
+
+    let items = ctx.items.0;
+    for idx in 0..total_items {
+        let (energy_type, exotic, bonus_mode) = unsafe {
+            (
+                raw_byte(items, idx, offset_of!(ProcessItem, energy_type)),
+                raw_byte(items, idx, offset_of!(ProcessItem, exotic)),
+                raw_byte(items, idx, offset_of!(ProcessItem, bonus_mode)),
+            )
+        };
+        if energy_type > MAX_ENERGY_TYPE {
+            return Err(result(ValidationCode::InvalidItemEnergyType, idx));
+        }
+        if exotic > 1 {
+            return Err(result(ValidationCode::InvalidItemExotic, idx));
+        }
+        if bonus_mode > MAX_BONUS_MODE {
+            return Err(result(ValidationCode::InvalidItemBonusMode, idx));
+        }
+    }
+
+    let mods = ctx.mods.0;
+    for idx in 0..15 {
+        let energy_type = unsafe { raw_byte(mods, idx, offset_of!(ProcessMod, energy_type)) };
+        if energy_type > MAX_ENERGY_TYPE {
+            return Err(result(ValidationCode::InvalidModEnergyType, idx));
+        }
+    }
+
+    let energy_type_offset =
+        offset_of!(ProcessStatMod, inner_mod) + offset_of!(ProcessMod, energy_type);
+    for idx in 0..ctx.num_auto_mods {
+        let energy_type = unsafe { raw_byte(ctx.auto_mods.0, idx, energy_type_offset) };
+        if energy_type > MAX_ENERGY_TYPE {
+            return Err(result(ValidationCode::InvalidAutoModEnergyType, idx));
+        }
+    }
+
+    Ok(())
+}
+
+/// Maps an error of the core library to a code and an index into the buffers,
+/// given the number of items per bucket.
+pub fn map_process_error(num_items: &[u16; NUM_ITEM_BUCKETS], e: ProcessError) -> ValidationResult {
+    let item_idx = |bucket: usize, index: usize| {
+        num_items[..bucket]
+            .iter()
+            .map(|&n| n as usize)
+            .sum::<usize>()
+            + index
+    };
+    let mod_idx = |list: ModList, index: usize| list as usize * NUM_ITEM_BUCKETS + index;
+    match e {
+        ProcessError::ItemEnergyOverCapacity { bucket, index } => result(
+            ValidationCode::ItemEnergyOverCapacity,
+            item_idx(bucket, index),
+        ),
+        ProcessError::ItemEnergyTooHigh { bucket, index } => {
+            result(ValidationCode::ItemEnergyTooHigh, item_idx(bucket, index))
+        }
+        ProcessError::ModEnergyTooHigh { list, index } => {
+            result(ValidationCode::ModEnergyTooHigh, mod_idx(list, index))
+        }
+        ProcessError::AutoModEnergyTooHigh { index } => {
+            result(ValidationCode::AutoModEnergyTooHigh, index)
+        }
+        ProcessError::EmptyModNotZeroed { list, index } => {
+            result(ValidationCode::ModNotZeroed, mod_idx(list, index))
+        }
+        ProcessError::GeneralModsNotPacked { index } => {
+            result(ValidationCode::GeneralModsNotPacked, index)
+        }
+        ProcessError::InvertedBounds { stat } => result(ValidationCode::InvertedBounds, stat),
+        ProcessError::LowerBoundAboveCap { stat } => {
+            result(ValidationCode::LowerBoundAboveCap, stat)
+        }
+        ProcessError::TooManyAutoMods { auto_mods } => {
+            result(ValidationCode::TooManyAutoMods, auto_mods as usize)
+        }
+        ProcessError::ItemStatTooHigh { bucket, index } => {
+            result(ValidationCode::ItemStatTooHigh, item_idx(bucket, index))
+        }
+        ProcessError::AutoModStatTooHigh { index } => {
+            result(ValidationCode::AutoModStatTooHigh, index)
+        }
+        ProcessError::StatTooHigh { stat } => result(ValidationCode::StatTooHigh, stat),
+    }
+}
+
+pub fn map_packed_error(e: PackedError) -> ValidationResult {
+    match e {
+        PackedError::BadMagic => result(ValidationCode::MalformedPackedInput, 0),
+        PackedError::UnexpectedEnd { offset } | PackedError::TrailingBytes { offset } => {
+            result(ValidationCode::MalformedPackedInput, offset)
+        }
+        PackedError::InvalidValue { offset } => result(ValidationCode::InvalidPackedValue, offset),
+        PackedError::UnsupportedVersion(version) => {
+            result(ValidationCode::UnsupportedPackedVersion, version as usize)
+        }
+    }
+}